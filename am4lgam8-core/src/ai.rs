@@ -0,0 +1,370 @@
+//! Computer players, which only play moves accepted by the real move validator.
+//!
+//! Every strategy looks at the moves of every tile in every direction, applies them to a copy of
+//! the grid, and rates the result with the same heuristic : the points scored, the free space
+//! left and the merges made possible.
+//!
+//! * Greedy picks the best move right away
+//! * Expectimax also averages over every tile that can spawn afterwards, wherever it can spawn
+//! * Monte-Carlo plays random runs after each move, and picks the move that did best on average
+
+use std::{fmt, str::FromStr};
+
+use rand::{seq::SliceRandom, Rng};
+
+use crate::{
+    grid::{GridConfig, GridCoordinates, TileGrid},
+    moves::{MoveDirection, ValidEvent, ValidatedEventQueue},
+    score::GameScore,
+    session::GameSession,
+    tile::TileType,
+};
+
+/// How much a point is worth, compared to the heuristic
+const POINTS_WEIGHT: f32 = 10.;
+const FREE_CELL_WEIGHT: f32 = 4.;
+const MERGE_WEIGHT: f32 = 2.;
+const WALL_WEIGHT: f32 = 3.;
+/// Rating of a grid where the run is over
+const GAME_OVER_VALUE: f32 = -1000.;
+/// Chance nodes of the expectimax only look at this many spawn cells, to keep big grids fast
+const MAX_SPAWN_CELLS: usize = 12;
+
+const DIRECTIONS: [MoveDirection; 4] = [
+    MoveDirection::Up,
+    MoveDirection::Down,
+    MoveDirection::Left,
+    MoveDirection::Right,
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AiMove {
+    pub source_coords: GridCoordinates,
+    pub move_direction: MoveDirection,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Strategy {
+    Greedy,
+    /// Looks `depth` moves ahead, each followed by every possible spawn
+    Expectimax {
+        depth: u32,
+    },
+    /// Plays `rollouts` random runs of `depth` moves after each candidate move
+    MonteCarlo {
+        rollouts: u32,
+        depth: u32,
+    },
+}
+
+impl Default for Strategy {
+    fn default() -> Self {
+        Strategy::Expectimax { depth: 1 }
+    }
+}
+
+impl Strategy {
+    pub const NAMES: [&'static str; 3] = ["greedy", "expectimax", "montecarlo"];
+
+    /// Returns None if there is no valid move left
+    pub fn choose_move<R: Rng>(&self, tile_grid: &TileGrid, rng: &mut R) -> Option<AiMove> {
+        let successors = successors(tile_grid);
+        let mut rate = |(ai_move, after, points): &(AiMove, TileGrid, i64)| -> f32 {
+            let points = *points as f32 * POINTS_WEIGHT;
+            match *self {
+                Strategy::Greedy => points + evaluate(after),
+                Strategy::Expectimax { depth } => points + expected_value(after, depth.max(1)),
+                Strategy::MonteCarlo { rollouts, depth } => {
+                    let rollouts = rollouts.max(1);
+                    let total: f32 = (0..rollouts)
+                        .map(|_| rollout(tile_grid, ai_move, depth, rng))
+                        .sum();
+                    total / rollouts as f32
+                }
+            }
+        };
+
+        let mut best: Option<(AiMove, f32)> = None;
+        for successor in &successors {
+            let value = rate(successor);
+            if best
+                .as_ref()
+                .is_none_or(|(_, best_value)| value > *best_value)
+            {
+                best = Some((successor.0.clone(), value));
+            }
+        }
+        best.map(|(ai_move, _)| ai_move)
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Strategy::Greedy => Strategy::NAMES[0],
+            Strategy::Expectimax { .. } => Strategy::NAMES[1],
+            Strategy::MonteCarlo { .. } => Strategy::NAMES[2],
+        }
+    }
+}
+
+impl fmt::Display for Strategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for Strategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "greedy" => Ok(Strategy::Greedy),
+            "expectimax" => Ok(Strategy::Expectimax { depth: 1 }),
+            "montecarlo" => Ok(Strategy::MonteCarlo {
+                rollouts: 10,
+                depth: 8,
+            }),
+            _ => Err(format!(
+                "Unknown strategy `{s}`, expected one of: {}",
+                Strategy::NAMES.join(", ")
+            )),
+        }
+    }
+}
+
+/// Every move accepted by the move validator, with the events it leads to
+fn moves_with_events(tile_grid: &TileGrid) -> Vec<(AiMove, Vec<ValidEvent>)> {
+    let GridConfig { width, height } = *tile_grid.config();
+    let mut moves = Vec::new();
+    for x in 0..width {
+        for y in 0..height {
+            let source_coords = GridCoordinates { x, y };
+            if !tile_grid
+                .get(&source_coords)
+                .is_some_and(TileType::is_movable)
+            {
+                continue;
+            }
+            for move_direction in DIRECTIONS {
+                let candidate_coords =
+                    source_coords.candidate_coords_for_dir(move_direction, tile_grid.config());
+                if let ValidatedEventQueue::ValidMove(events) =
+                    ValidatedEventQueue::validate_move(tile_grid, candidate_coords, move_direction)
+                {
+                    let ai_move = AiMove {
+                        source_coords: source_coords.clone(),
+                        move_direction,
+                    };
+                    moves.push((ai_move, events));
+                }
+            }
+        }
+    }
+    moves
+}
+
+pub fn valid_moves(tile_grid: &TileGrid) -> Vec<AiMove> {
+    moves_with_events(tile_grid)
+        .into_iter()
+        .map(|(ai_move, _)| ai_move)
+        .collect()
+}
+
+/// Every valid move, with the grid it leads to (before the next spawn) and the points it scores
+pub fn successors(tile_grid: &TileGrid) -> Vec<(AiMove, TileGrid, i64)> {
+    moves_with_events(tile_grid)
+        .into_iter()
+        .map(|(ai_move, events)| {
+            let mut after = tile_grid.clone();
+            let points = after.apply_events(&events).points();
+            (ai_move, after, points)
+        })
+        .collect()
+}
+
+/// Rates a grid by itself, regardless of the points scored to get there
+pub fn evaluate(tile_grid: &TileGrid) -> f32 {
+    let free_cells = tile_grid.unused_coordinates().count();
+    if free_cells == 0 && !tile_grid.has_any_possible_moves() {
+        return GAME_OVER_VALUE;
+    }
+
+    let mut merges = 0;
+    let mut walls = 0;
+    for (coords, tile_type) in tile_grid.tiles() {
+        if !tile_grid.is_inside(coords) {
+            continue;
+        }
+        if tile_type.is_wall() {
+            walls += 1;
+        }
+        // Each pair is only counted once, from its bottom or left tile
+        for neighbour in [
+            coords.coords_after_move(MoveDirection::Up),
+            coords.coords_after_move(MoveDirection::Right),
+        ] {
+            if let Some(other) = tile_grid.get(&neighbour) {
+                if tile_type.try_combine_with(other).is_some() {
+                    merges += 1;
+                }
+            }
+        }
+    }
+
+    free_cells as f32 * FREE_CELL_WEIGHT + merges as f32 * MERGE_WEIGHT - walls as f32 * WALL_WEIGHT
+}
+
+/// Average value of the grid over the tiles that can spawn next, then `depth - 1` more moves
+fn expected_value(tile_grid: &TileGrid, depth: u32) -> f32 {
+    let cells: Vec<&GridCoordinates> = tile_grid.unused_coordinates().collect();
+    if cells.is_empty() {
+        return evaluate(tile_grid);
+    }
+    // Evenly spread over the free cells when there are too many of them
+    let step = cells.len().div_ceil(MAX_SPAWN_CELLS);
+    let spawn_table = tile_grid.spawn_table();
+    let total_weight = spawn_table.total_weight();
+
+    let mut total = 0.;
+    let mut samples = 0;
+    for coords in cells.into_iter().step_by(step) {
+        samples += 1;
+        for (tile_type, weight) in spawn_table.weights() {
+            // Tiles that can't spawn yet, like big bombs early in the run
+            if *weight == 0 {
+                continue;
+            }
+            let mut spawned = tile_grid.clone();
+            spawned.insert(coords.clone(), *tile_type);
+            let value = if depth > 1 {
+                best_value(&spawned, depth - 1)
+            } else {
+                evaluate(&spawned)
+            };
+            total += value * *weight as f32 / total_weight as f32;
+        }
+    }
+    total / samples as f32
+}
+
+fn best_value(tile_grid: &TileGrid, depth: u32) -> f32 {
+    successors(tile_grid)
+        .iter()
+        .map(|(_, after, points)| *points as f32 * POINTS_WEIGHT + expected_value(after, depth))
+        .reduce(f32::max)
+        .unwrap_or_else(|| evaluate(tile_grid))
+}
+
+/// Plays the move then `depth` random moves, with spawns the real run can't know about
+fn rollout<R: Rng>(tile_grid: &TileGrid, first_move: &AiMove, depth: u32, rng: &mut R) -> f32 {
+    let mut simulated = tile_grid.clone();
+    simulated.reseed_rng(rng.gen());
+    let mut session = GameSession::from_grid(simulated, GameScore::default());
+    session.play(&first_move.source_coords, first_move.move_direction);
+
+    for _ in 0..depth {
+        let moves = valid_moves(session.tile_grid());
+        let Some(ai_move) = moves.choose(rng) else {
+            break;
+        };
+        session.play(&ai_move.source_coords, ai_move.move_direction);
+    }
+
+    let value = if session.is_over() {
+        GAME_OVER_VALUE
+    } else {
+        evaluate(session.tile_grid())
+    };
+    session.score().get() as f32 * POINTS_WEIGHT + value
+}
+
+#[cfg(test)]
+pub mod tests {
+    use crate::{
+        ai::{valid_moves, AiMove, Strategy},
+        grid::{GridConfig, GridCoordinates, TileGrid},
+        moves::MoveDirection,
+        rng::rng_from_seed,
+        session::GameSession,
+        tile::{CoinValue, TileType},
+    };
+
+    fn all_strategies() -> [Strategy; 3] {
+        [
+            Strategy::Greedy,
+            Strategy::Expectimax { depth: 1 },
+            Strategy::MonteCarlo {
+                rollouts: 4,
+                depth: 3,
+            },
+        ]
+    }
+
+    #[test]
+    fn should_explode_bombs_when_possible() {
+        let mut tile_grid = TileGrid::default();
+        tile_grid.setup_default_grid();
+        tile_grid.insert(GridCoordinates { x: 0, y: 0 }, TileType::Bomb);
+        tile_grid.insert(GridCoordinates { x: 1, y: 0 }, TileType::Bomb);
+        tile_grid.insert(
+            GridCoordinates { x: 1, y: 1 },
+            TileType::Coin(CoinValue::EIGHT),
+        );
+        tile_grid.insert(
+            GridCoordinates { x: 3, y: 3 },
+            TileType::Coin(CoinValue::ONE),
+        );
+
+        let mut rng = rng_from_seed(0);
+        for strategy in all_strategies() {
+            let ai_move = strategy.choose_move(&tile_grid, &mut rng);
+            assert!(
+                matches!(
+                    ai_move,
+                    Some(AiMove {
+                        source_coords: GridCoordinates { x: 0, y: 0 },
+                        move_direction: MoveDirection::Right,
+                    }) | Some(AiMove {
+                        source_coords: GridCoordinates { x: 1, y: 0 },
+                        move_direction: MoveDirection::Left,
+                    })
+                ),
+                "{strategy} chose {ai_move:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn should_find_no_move_on_a_stuck_grid() {
+        let mut tile_grid = TileGrid::new(GridConfig::square(1), 0);
+        tile_grid.setup_default_grid();
+        tile_grid.insert(GridCoordinates { x: 0, y: 0 }, TileType::Bomb);
+
+        assert!(valid_moves(&tile_grid).is_empty());
+        let mut rng = rng_from_seed(0);
+        for strategy in all_strategies() {
+            assert_eq!(strategy.choose_move(&tile_grid, &mut rng), None);
+        }
+    }
+
+    #[test]
+    fn should_play_a_whole_run_with_valid_moves() {
+        let mut session = GameSession::new(GridConfig::default(), 3);
+        let mut rng = rng_from_seed(3);
+        while let Some(ai_move) = Strategy::Greedy.choose_move(session.tile_grid(), &mut rng) {
+            session
+                .play(&ai_move.source_coords, ai_move.move_direction)
+                .expect("The AI chose an invalid move");
+        }
+        assert!(session.is_over());
+        assert!(session.moves_played() > 0);
+    }
+
+    #[test]
+    fn should_parse_strategy_names() {
+        for name in Strategy::NAMES {
+            let strategy: Strategy = name.parse().expect("Unknown strategy");
+            assert_eq!(strategy.to_string(), name);
+        }
+        assert!("random".parse::<Strategy>().is_err());
+    }
+}
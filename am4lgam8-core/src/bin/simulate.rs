@@ -0,0 +1,282 @@
+//! Plays many seeded runs without rendering anything, and reports how they went, to tune the
+//! spawn table with data.
+//!
+//! Usage: simulate [--games <n>] [--seed <first seed>] [--size <width>x<height>]
+//!                 [--bot random|greedy|expectimax|montecarlo] [--spawns <table>]
+//!                 [--max-moves <n>]
+//!
+//! Run `i` uses the seed `first seed + i`, so that two tables can be compared on the same seeds.
+//! The spawn table is written like `one=41,two=20,bomb=34,wall=6`, and then stays the same during
+//! the whole run. Without it, the runs go through the levels of the game.
+
+use std::{process::ExitCode, str::FromStr};
+
+use am4lgam8_core::{
+    ai::{valid_moves, Strategy},
+    grid::{GridConfig, TileGrid},
+    rng::{rng_from_seed, GameRng},
+    session::GameSession,
+    spawn::{SpawnRules, SpawnTable},
+    tile::TileType,
+};
+use rand::seq::SliceRandom;
+
+const USAGE: &str = "Usage: simulate [--games <n>] [--seed <first seed>] \
+    [--size <width>x<height>] [--bot random|greedy|expectimax|montecarlo] [--spawns <table>] \
+    [--max-moves <n>]";
+
+/// Who plays the simulated runs
+#[derive(Debug, Clone, Copy)]
+enum Bot {
+    Random,
+    Ai(Strategy),
+}
+
+impl Bot {
+    fn play(&self, session: &mut GameSession, rng: &mut GameRng) -> bool {
+        let ai_move = match self {
+            Bot::Random => valid_moves(session.tile_grid()).choose(rng).cloned(),
+            Bot::Ai(strategy) => strategy.choose_move(session.tile_grid(), rng),
+        };
+        ai_move
+            .and_then(|ai_move| session.play(&ai_move.source_coords, ai_move.move_direction))
+            .is_some()
+    }
+}
+
+impl FromStr for Bot {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "random" => Ok(Bot::Random),
+            _ => s.parse().map(Bot::Ai),
+        }
+    }
+}
+
+struct Options {
+    games: u64,
+    first_seed: u64,
+    grid_config: GridConfig,
+    bot: Bot,
+    /// The default rules are used when it is not set
+    spawn_table: Option<SpawnTable>,
+    max_moves: u32,
+}
+
+impl Options {
+    fn from_args() -> Result<Self, String> {
+        let mut options = Options {
+            games: 1000,
+            first_seed: 0,
+            grid_config: GridConfig::default(),
+            bot: Bot::Ai(Strategy::Greedy),
+            spawn_table: None,
+            max_moves: 10_000,
+        };
+
+        let mut args = std::env::args().skip(1);
+        while let Some(arg) = args.next() {
+            let value = args
+                .next()
+                .ok_or_else(|| format!("Missing value for {arg}"))?;
+            let invalid = || format!("Invalid value for {arg}: {value}");
+            match arg.as_str() {
+                "--games" => options.games = value.parse().map_err(|_| invalid())?,
+                "--seed" => options.first_seed = value.parse().map_err(|_| invalid())?,
+                "--size" => options.grid_config = parse_size(&value).ok_or_else(invalid)?,
+                "--bot" => options.bot = value.parse()?,
+                "--spawns" => options.spawn_table = Some(value.parse()?),
+                "--max-moves" => options.max_moves = value.parse().map_err(|_| invalid())?,
+                _ => return Err(format!("Unknown option {arg}")),
+            }
+        }
+        Ok(options)
+    }
+}
+
+fn parse_size(size: &str) -> Option<GridConfig> {
+    let (width, height) = size.split_once('x')?;
+    let grid_config = GridConfig {
+        width: width.parse().ok()?,
+        height: height.parse().ok()?,
+    };
+    (grid_config.width > 0 && grid_config.height > 0).then_some(grid_config)
+}
+
+/// What filled the grid when the run ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum GameOverCause {
+    Coins,
+    Bombs,
+    Walls,
+    /// The run was stopped before it was over
+    MoveLimit,
+}
+
+impl GameOverCause {
+    const ALL: [GameOverCause; 4] = [
+        GameOverCause::Coins,
+        GameOverCause::Bombs,
+        GameOverCause::Walls,
+        GameOverCause::MoveLimit,
+    ];
+
+    fn label(&self) -> &'static str {
+        match self {
+            GameOverCause::Coins => "clogged by coins/blocks",
+            GameOverCause::Bombs => "clogged by bombs",
+            GameOverCause::Walls => "walled in",
+            GameOverCause::MoveLimit => "move limit reached",
+        }
+    }
+}
+
+struct RunStats {
+    score: i64,
+    moves_played: u32,
+    /// Walls on the grid at the end, border excluded. Explosions break some of them, so this is
+    /// not every wall that spawned.
+    walls: u32,
+    level: usize,
+    cause: GameOverCause,
+}
+
+fn simulate_run(options: &Options, seed: u64) -> RunStats {
+    let spawn_rules = options
+        .spawn_table
+        .clone()
+        .map(SpawnRules::from)
+        .unwrap_or_default();
+    let mut session = GameSession::with_spawn_rules(options.grid_config, seed, spawn_rules);
+    // Kept apart from the grid's RNG, so that the bot's choices don't depend on the spawns
+    let mut rng = rng_from_seed(!seed);
+    while !session.is_over()
+        && session.moves_played() < options.max_moves
+        && options.bot.play(&mut session, &mut rng)
+    {}
+
+    let tile_grid = session.tile_grid();
+    let (coins, bombs, walls) = count_tiles(tile_grid);
+    let cause = if !session.is_over() {
+        GameOverCause::MoveLimit
+    } else if walls >= coins.max(bombs) {
+        GameOverCause::Walls
+    } else if bombs >= coins {
+        GameOverCause::Bombs
+    } else {
+        GameOverCause::Coins
+    };
+    RunStats {
+        score: session.score().get(),
+        moves_played: session.moves_played(),
+        walls,
+        level: session.level(),
+        cause,
+    }
+}
+
+/// Coins (and blocks), bombs and walls inside the grid
+fn count_tiles(tile_grid: &TileGrid) -> (u32, u32, u32) {
+    let mut counts = (0, 0, 0);
+    for (coords, tile_type) in tile_grid.tiles() {
+        if !tile_grid.is_inside(coords) {
+            continue;
+        }
+        match tile_type {
+            TileType::Coin(_) | TileType::Block => counts.0 += 1,
+            TileType::Bomb | TileType::BigBomb => counts.1 += 1,
+            TileType::Wall | TileType::CrackedWall(_) => counts.2 += 1,
+        }
+    }
+    counts
+}
+
+/// Nearest-rank percentile of sorted values
+fn percentile<T: Copy>(sorted: &[T], percent: usize) -> T {
+    let rank = (sorted.len() * percent).div_ceil(100).max(1);
+    sorted[rank - 1]
+}
+
+fn mean(values: impl Iterator<Item = f64>) -> f64 {
+    let (sum, count) = values.fold((0., 0), |(sum, count), value| (sum + value, count + 1));
+    if count == 0 {
+        0.
+    } else {
+        sum / count as f64
+    }
+}
+
+fn print_distribution(name: &str, mut values: Vec<i64>) {
+    values.sort_unstable();
+    println!(
+        "{name:<8} mean {:>8.1} | min {} p10 {} p25 {} median {} p75 {} p90 {} max {}",
+        mean(values.iter().map(|&value| value as f64)),
+        values[0],
+        percentile(&values, 10),
+        percentile(&values, 25),
+        percentile(&values, 50),
+        percentile(&values, 75),
+        percentile(&values, 90),
+        values[values.len() - 1],
+    );
+}
+
+fn main() -> ExitCode {
+    let options = match Options::from_args() {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("{err}");
+            eprintln!("{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+    if options.games == 0 {
+        eprintln!("{USAGE}");
+        return ExitCode::FAILURE;
+    }
+
+    let runs: Vec<RunStats> = (0..options.games)
+        .map(|i| simulate_run(&options, options.first_seed.wrapping_add(i)))
+        .collect();
+
+    let bot = match options.bot {
+        Bot::Random => "random".to_string(),
+        Bot::Ai(strategy) => strategy.to_string(),
+    };
+    let spawns = options
+        .spawn_table
+        .as_ref()
+        .map(SpawnTable::to_string)
+        .unwrap_or_else(|| "by level".to_string());
+    println!(
+        "{} runs on {}x{}, seeds {}.., bot {bot}, spawns {spawns}",
+        options.games, options.grid_config.width, options.grid_config.height, options.first_seed,
+    );
+    println!();
+    print_distribution("Score", runs.iter().map(|run| run.score).collect());
+    print_distribution(
+        "Moves",
+        runs.iter().map(|run| run.moves_played.into()).collect(),
+    );
+    print_distribution("Walls", runs.iter().map(|run| run.walls.into()).collect());
+    print_distribution("Level", runs.iter().map(|run| run.level as i64).collect());
+    let moves: u64 = runs.iter().map(|run| u64::from(run.moves_played)).sum();
+    let walls: u64 = runs.iter().map(|run| u64::from(run.walls)).sum();
+    println!(
+        "Walls per 100 moves: {:.2}",
+        walls as f64 * 100. / moves.max(1) as f64
+    );
+    println!();
+    println!("Game over:");
+    for cause in GameOverCause::ALL {
+        let count = runs.iter().filter(|run| run.cause == cause).count();
+        println!(
+            "  {:<20} {count:>6} ({:.1}%)",
+            cause.label(),
+            count as f64 * 100. / runs.len() as f64
+        );
+    }
+    ExitCode::SUCCESS
+}
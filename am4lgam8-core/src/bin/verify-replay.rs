@@ -0,0 +1,67 @@
+//! Recomputes the final score of a replay file, without rendering anything.
+//!
+//! Usage: verify-replay <replay file> [--spawns <spawns.ron>]
+//!
+//! The run is played again with the spawn rules of the game, or the ones read from `--spawns`,
+//! which have to be the ones it was recorded with. Reading them takes the `serde` feature.
+
+use std::process::ExitCode;
+
+use am4lgam8_core::{replay::Replay, spawn::SpawnRules};
+
+const USAGE: &str = "Usage: verify-replay <replay file> [--spawns <spawns.ron>]";
+
+fn spawn_rules(path: Option<&String>) -> Result<SpawnRules, String> {
+    let Some(path) = path else {
+        return Ok(SpawnRules::default());
+    };
+    #[cfg(feature = "serde")]
+    {
+        let text = std::fs::read_to_string(path).map_err(|err| format!("{path}: {err}"))?;
+        let rules: SpawnRules = ron::from_str(&text).map_err(|err| format!("{path}: {err}"))?;
+        rules.validate().map_err(|err| format!("{path}: {err}"))?;
+        Ok(rules)
+    }
+    #[cfg(not(feature = "serde"))]
+    Err(format!("Reading {path} takes the `serde` feature"))
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let (path, spawns) = match &args[..] {
+        [path] => (path, None),
+        [path, flag, spawns] if flag == "--spawns" => (path, Some(spawns)),
+        _ => {
+            eprintln!("{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+    let spawn_rules = match spawn_rules(spawns) {
+        Ok(spawn_rules) => spawn_rules,
+        Err(err) => {
+            eprintln!("{err}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let outcome = std::fs::read_to_string(path)
+        .map_err(|err| err.to_string())
+        .and_then(|text| text.parse::<Replay>().map_err(|err| err.to_string()))
+        .and_then(|replay| replay.verify(&spawn_rules).map_err(|err| err.to_string()));
+
+    match outcome {
+        Ok(outcome) => {
+            println!("Score: {}", outcome.score.get());
+            println!("Moves: {}", outcome.moves_played);
+            println!(
+                "Game over: {}",
+                if outcome.game_over { "yes" } else { "no" }
+            );
+            ExitCode::SUCCESS
+        }
+        Err(err) => {
+            eprintln!("{path}: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,131 @@
+//! Blitz : the run is played against the clock. Tiles also spawn on their own every few seconds,
+//! whether moves are played or not, and explosions buy some time back.
+
+use std::time::Duration;
+
+use crate::moves::ChainReaction;
+
+/// What the clock starts with, and can't go above
+pub const BLITZ_DURATION: Duration = Duration::from_secs(120);
+/// Time between two tiles spawned by the clock
+pub const SPAWN_INTERVAL: Duration = Duration::from_secs(4);
+/// Given back for each tile exploded
+pub const TIME_BONUS_PER_TILE: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlitzClock {
+    remaining: Duration,
+    until_spawn: Duration,
+}
+
+impl Default for BlitzClock {
+    fn default() -> Self {
+        BlitzClock {
+            remaining: BLITZ_DURATION,
+            until_spawn: SPAWN_INTERVAL,
+        }
+    }
+}
+
+impl BlitzClock {
+    pub fn remaining(&self) -> Duration {
+        self.remaining
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.remaining.is_zero()
+    }
+
+    /// Lets time pass, and returns how many tiles are due to spawn meanwhile
+    pub fn tick(&mut self, delta: Duration) -> u32 {
+        let mut elapsed = delta.min(self.remaining);
+        self.remaining -= elapsed;
+
+        let mut spawns = 0;
+        while elapsed >= self.until_spawn {
+            elapsed -= self.until_spawn;
+            self.until_spawn = SPAWN_INTERVAL;
+            spawns += 1;
+        }
+        self.until_spawn -= elapsed;
+        spawns
+    }
+
+    /// Buys time back for the tiles the explosions destroyed, and returns how much. Once the
+    /// clock ran out, it is too late.
+    pub fn add_bonus(&mut self, chain_reaction: &ChainReaction) -> Duration {
+        if self.is_over() {
+            return Duration::ZERO;
+        }
+        let tiles: u32 = chain_reaction
+            .waves
+            .iter()
+            .map(|wave| wave.destroyed.len() as u32)
+            .sum();
+        let before = self.remaining;
+        self.remaining = (self.remaining + TIME_BONUS_PER_TILE * tiles).min(BLITZ_DURATION);
+        self.remaining - before
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use std::time::Duration;
+
+    use crate::{
+        blitz::{BlitzClock, BLITZ_DURATION, SPAWN_INTERVAL},
+        grid::GridCoordinates,
+        moves::{ChainReaction, ExplosionWave},
+        tile::{CoinValue, TileType},
+    };
+
+    fn chain_reaction(tiles: usize) -> ChainReaction {
+        ChainReaction {
+            waves: vec![ExplosionWave {
+                explosions: Vec::new(),
+                destroyed: vec![
+                    (GridCoordinates::default(), TileType::Coin(CoinValue::ONE));
+                    tiles
+                ],
+                damaged: Vec::new(),
+                base_points: tiles as i64,
+            }],
+        }
+    }
+
+    #[test]
+    fn should_spawn_tiles_on_a_timer() {
+        let mut clock = BlitzClock::default();
+        assert_eq!(clock.tick(Duration::from_millis(3900)), 0);
+        assert_eq!(clock.tick(Duration::from_millis(100)), 1);
+        // A long frame can be due more than one
+        assert_eq!(clock.tick(SPAWN_INTERVAL * 2 + Duration::from_secs(1)), 2);
+        assert_eq!(clock.tick(Duration::from_secs(3)), 1);
+        assert_eq!(clock.remaining(), BLITZ_DURATION - Duration::from_secs(16));
+    }
+
+    #[test]
+    fn should_run_out_once() {
+        let mut clock = BlitzClock::default();
+        clock.tick(BLITZ_DURATION - Duration::from_secs(1));
+        assert!(!clock.is_over());
+        clock.tick(Duration::from_secs(10));
+        assert!(clock.is_over());
+        assert_eq!(clock.remaining(), Duration::ZERO);
+        assert_eq!(clock.tick(Duration::from_secs(10)), 0);
+        assert_eq!(clock.add_bonus(&chain_reaction(4)), Duration::ZERO);
+    }
+
+    #[test]
+    fn explosions_should_buy_time_back() {
+        let mut clock = BlitzClock::default();
+        // The clock is full
+        assert_eq!(clock.add_bonus(&chain_reaction(4)), Duration::ZERO);
+
+        clock.tick(Duration::from_secs(10));
+        assert_eq!(clock.add_bonus(&chain_reaction(4)), Duration::from_secs(2));
+        assert_eq!(clock.add_bonus(&chain_reaction(0)), Duration::ZERO);
+        assert_eq!(clock.add_bonus(&chain_reaction(40)), Duration::from_secs(8));
+        assert_eq!(clock.remaining(), BLITZ_DURATION);
+    }
+}
@@ -0,0 +1,222 @@
+//! The daily challenge : one run a day on the default grid, from a seed derived from the date.
+//!
+//! The seed only depends on the number of days since the epoch, through integer operations, so
+//! every player gets the same run on the same (UTC) day, whatever the platform.
+
+use std::{fmt, str::FromStr};
+
+#[cfg(feature = "bevy")]
+use bevy_ecs::prelude::Resource;
+
+use crate::date::Date;
+
+const HISTORY_HEADER: &str = "AM4LGAM8-DAILY 1";
+/// Keeps the daily seeds apart from the small seeds typed on the title screen
+const SEED_SALT: u64 = 0xA4A1_6A48_DA11_0000;
+
+/// The seed of the day's run, the same on every platform
+pub fn daily_seed(date: &Date) -> u64 {
+    // SplitMix64, a single round is enough to spread consecutive days apart
+    let mut z = (date.days_since_epoch() as u64)
+        .wrapping_add(SEED_SALT)
+        .wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// How far the run of a day went. It is kept up to date while the run goes on, so that leaving
+/// it still uses up the attempt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DailyResult {
+    pub date: Date,
+    pub score: i64,
+    pub moves_played: u32,
+}
+
+/// Every daily run played, one per day, oldest first.
+///
+/// Stored as plain text, one day per line after a header, e.g. `2024-03-09 120 45` for 120
+/// points in 45 moves.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "bevy", derive(Resource))]
+pub struct DailyHistory {
+    results: Vec<DailyResult>,
+}
+
+impl DailyHistory {
+    pub fn results(&self) -> &[DailyResult] {
+        &self.results
+    }
+
+    pub fn result(&self, date: &Date) -> Option<&DailyResult> {
+        self.results.iter().find(|result| result.date == *date)
+    }
+
+    pub fn has_played(&self, date: &Date) -> bool {
+        self.result(date).is_some()
+    }
+
+    /// Adds the result of the day, or updates it
+    pub fn record(&mut self, result: DailyResult) {
+        match self
+            .results
+            .binary_search_by(|other| other.date.cmp(&result.date))
+        {
+            Ok(index) => self.results[index] = result,
+            Err(index) => self.results.insert(index, result),
+        }
+    }
+
+    /// Days in a row played up to today. Today not being played yet does not break the streak.
+    pub fn streak(&self, today: &Date) -> u32 {
+        let mut day = today.days_since_epoch();
+        if !self.has_played(today) {
+            day -= 1;
+        }
+        let mut streak = 0;
+        for result in self.results.iter().rev() {
+            let played = result.date.days_since_epoch();
+            if played > day {
+                continue;
+            }
+            if played < day {
+                break;
+            }
+            streak += 1;
+            day -= 1;
+        }
+        streak
+    }
+
+    pub fn best_streak(&self) -> u32 {
+        let mut best = 0;
+        let mut streak = 0;
+        let mut previous_day = None;
+        for result in &self.results {
+            let day = result.date.days_since_epoch();
+            streak = if previous_day == Some(day - 1) {
+                streak + 1
+            } else {
+                1
+            };
+            best = best.max(streak);
+            previous_day = Some(day);
+        }
+        best
+    }
+}
+
+impl fmt::Display for DailyHistory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{HISTORY_HEADER}")?;
+        for result in &self.results {
+            writeln!(
+                f,
+                "{} {} {}",
+                result.date, result.score, result.moves_played
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for DailyHistory {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut lines = s.lines();
+        if lines.next().map(str::trim) != Some(HISTORY_HEADER) {
+            return Err(format!("Expected the `{HISTORY_HEADER}` header"));
+        }
+        let mut history = DailyHistory::default();
+        for line in lines.map(str::trim).filter(|line| !line.is_empty()) {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let [date, score, moves_played] = fields[..] else {
+                return Err(format!("Expected `date score moves`, got `{line}`"));
+            };
+            history.record(DailyResult {
+                date: date.parse()?,
+                score: score
+                    .parse()
+                    .map_err(|_| format!("Invalid score `{score}`"))?,
+                moves_played: moves_played
+                    .parse()
+                    .map_err(|_| format!("Invalid number of moves `{moves_played}`"))?,
+            });
+        }
+        Ok(history)
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use crate::{
+        daily::{daily_seed, DailyHistory, DailyResult},
+        date::Date,
+    };
+
+    fn date(text: &str) -> Date {
+        text.parse().unwrap()
+    }
+
+    fn result(text: &str, score: i64) -> DailyResult {
+        DailyResult {
+            date: date(text),
+            score,
+            moves_played: 10,
+        }
+    }
+
+    #[test]
+    fn daily_seeds_should_never_change() {
+        // Players on every platform, and every release, get the same run on the same day
+        assert_eq!(daily_seed(&date("2024-03-09")), 5_963_257_001_968_172_910);
+        assert_eq!(daily_seed(&date("2024-03-10")), 16_039_454_258_828_872_122);
+        assert_ne!(
+            daily_seed(&date("2024-03-09")),
+            daily_seed(&date("2025-03-09"))
+        );
+    }
+
+    #[test]
+    fn should_count_days_in_a_row() {
+        let mut history = DailyHistory::default();
+        assert_eq!(history.streak(&date("2024-03-09")), 0);
+
+        history.record(result("2024-03-09", 10));
+        history.record(result("2024-03-07", 20));
+        history.record(result("2024-03-08", 30));
+        history.record(result("2024-03-04", 40));
+        history.record(result("2024-03-05", 50));
+        // Updated, not added twice
+        history.record(result("2024-03-09", 60));
+        assert_eq!(history.results().len(), 5);
+        assert_eq!(history.result(&date("2024-03-09")).unwrap().score, 60);
+
+        assert_eq!(history.streak(&date("2024-03-09")), 3);
+        // Not played yet today
+        assert_eq!(history.streak(&date("2024-03-10")), 3);
+        // A day was missed
+        assert_eq!(history.streak(&date("2024-03-11")), 0);
+        assert_eq!(history.best_streak(), 3);
+    }
+
+    #[test]
+    fn should_read_the_history_back() {
+        let mut history = DailyHistory::default();
+        history.record(result("2024-03-09", 120));
+        history.record(result("2024-02-29", 8));
+
+        let text = history.to_string();
+        assert_eq!(
+            text,
+            "AM4LGAM8-DAILY 1\n2024-02-29 8 10\n2024-03-09 120 10\n"
+        );
+        assert_eq!(text.parse::<DailyHistory>(), Ok(history));
+        assert!("2024-03-09 120 10".parse::<DailyHistory>().is_err());
+        assert!("AM4LGAM8-DAILY 1\n2024-03-09 120"
+            .parse::<DailyHistory>()
+            .is_err());
+    }
+}
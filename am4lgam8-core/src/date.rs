@@ -0,0 +1,100 @@
+//! Calendar dates, without depending on the platform's clock : the caller provides the number of
+//! days since the Unix epoch.
+
+use std::{fmt, str::FromStr};
+
+/// A day of the proleptic Gregorian calendar, written as `YYYY-MM-DD`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Date {
+    pub year: i32,
+    pub month: u32,
+    pub day: u32,
+}
+
+impl Date {
+    /// Converts a number of days since 1970-01-01
+    pub fn from_days_since_epoch(days: i64) -> Self {
+        // Howard Hinnant's civil_from_days, with eras of 400 years starting on March 1st
+        let z = days + 719_468;
+        let era = z.div_euclid(146_097);
+        let day_of_era = z.rem_euclid(146_097);
+        let year_of_era =
+            (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let shifted_month = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+        let month = if shifted_month < 10 {
+            shifted_month + 3
+        } else {
+            shifted_month - 9
+        };
+        let year = year_of_era + era * 400 + i64::from(month <= 2);
+        Date {
+            year: year as i32,
+            month: month as u32,
+            day: day as u32,
+        }
+    }
+
+    /// The inverse of `from_days_since_epoch`
+    pub fn days_since_epoch(&self) -> i64 {
+        let year = i64::from(self.year) - i64::from(self.month <= 2);
+        let era = year.div_euclid(400);
+        let year_of_era = year.rem_euclid(400);
+        let shifted_month = (i64::from(self.month) + 9) % 12;
+        let day_of_year = (153 * shifted_month + 2) / 5 + i64::from(self.day) - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+        era * 146_097 + day_of_era - 719_468
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+impl FromStr for Date {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Invalid date `{s}`");
+        let mut parts = s.splitn(3, '-');
+        let mut next_part = || parts.next().ok_or_else(invalid);
+        let year = next_part()?.parse().map_err(|_| invalid())?;
+        let month = next_part()?.parse().map_err(|_| invalid())?;
+        let day = next_part()?.parse().map_err(|_| invalid())?;
+        if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+            return Err(invalid());
+        }
+        Ok(Date { year, month, day })
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use crate::date::Date;
+
+    #[test]
+    fn should_convert_days_since_epoch() {
+        let cases = [
+            (0, "1970-01-01"),
+            (-1, "1969-12-31"),
+            (11_016, "2000-02-29"),
+            (19_631, "2023-10-01"),
+        ];
+        for (days, text) in cases {
+            let date = Date::from_days_since_epoch(days);
+            assert_eq!(date.to_string(), text);
+            assert_eq!(date.days_since_epoch(), days);
+            assert_eq!(text.parse::<Date>(), Ok(date));
+        }
+    }
+
+    #[test]
+    fn should_reject_invalid_dates() {
+        assert!("2023-13-01".parse::<Date>().is_err());
+        assert!("2023-10".parse::<Date>().is_err());
+        assert!("yesterday".parse::<Date>().is_err());
+    }
+}
@@ -0,0 +1,1142 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+#[cfg(feature = "bevy")]
+use bevy_ecs::prelude::{Component, Event, Resource};
+use rand::Rng;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    moves::{
+        CanCombineResult, CanMoveResult, ChainReaction, ExplosionEvent, ExplosionWave,
+        MergeTilesEvent, MoveDirection, SolidifyEvent, ValidEvent, ValidatedEventQueue,
+    },
+    rng::{rng_from_seed, GameRng},
+    spawn::SpawnTable,
+    tile::{CoinValue, ExplosionResult, TileType},
+};
+
+// Components
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "bevy", derive(Component))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct GridCoordinates {
+    pub x: i32,
+    pub y: i32,
+}
+
+impl GridCoordinates {
+    pub fn coords_after_move(&self, dir: MoveDirection) -> GridCoordinates {
+        let GridCoordinates { x, y } = self;
+        match dir {
+            MoveDirection::Left => GridCoordinates { x: x - 1, y: *y },
+            MoveDirection::Right => GridCoordinates { x: x + 1, y: *y },
+            MoveDirection::Up => GridCoordinates { x: *x, y: y + 1 },
+            MoveDirection::Down => GridCoordinates { x: *x, y: y - 1 },
+        }
+    }
+
+    /// The diamond of coordinates at most `radius` steps away, starting with these ones. A
+    /// radius of 1 is a plus shape.
+    pub fn explosion_radius(&self, radius: i32) -> Vec<GridCoordinates> {
+        let mut area = vec![self.clone()];
+        for dx in -radius..=radius {
+            let reach = radius - dx.abs();
+            for dy in -reach..=reach {
+                if (dx, dy) != (0, 0) {
+                    area.push(GridCoordinates {
+                        x: self.x + dx,
+                        y: self.y + dy,
+                    });
+                }
+            }
+        }
+        area
+    }
+
+    pub fn candidate_coords_for_dir(
+        &self,
+        move_direction: MoveDirection,
+        grid_config: &GridConfig,
+    ) -> Vec<GridCoordinates> {
+        match move_direction {
+            MoveDirection::Left => (-1..=self.x)
+                .rev()
+                .map(|x| GridCoordinates { x, y: self.y })
+                .collect(),
+            MoveDirection::Right => (self.x..=grid_config.width)
+                .map(|x| GridCoordinates { x, y: self.y })
+                .collect(),
+            MoveDirection::Up => (self.y..=grid_config.height)
+                .map(|y| GridCoordinates { x: self.x, y })
+                .collect(),
+            MoveDirection::Down => (-1..=self.y)
+                .rev()
+                .map(|y| GridCoordinates { x: self.x, y })
+                .collect(),
+        }
+    }
+}
+
+// Events
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "bevy", derive(Event))]
+pub struct MoveTileEvent {
+    pub source: GridCoordinates,
+    pub target: GridCoordinates,
+}
+
+// Resource
+
+/// Dimensions of the playable area, walls excluded
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "bevy", derive(Resource))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct GridConfig {
+    pub width: i32,
+    pub height: i32,
+}
+
+impl Default for GridConfig {
+    fn default() -> Self {
+        GridConfig::PRESETS[0]
+    }
+}
+
+impl GridConfig {
+    /// Grid sizes that can be picked from the title screen
+    pub const PRESETS: [GridConfig; 4] = [
+        GridConfig::square(4),
+        GridConfig::square(5),
+        GridConfig::square(7),
+        GridConfig::square(9),
+    ];
+
+    /// The widest and tallest grid a file can ask for
+    pub const MAX_SIZE: i32 = 16;
+
+    pub const fn square(size: i32) -> Self {
+        GridConfig {
+            width: size,
+            height: size,
+        }
+    }
+
+    /// Whether the grid has at least one cell, and fits in `MAX_SIZE`
+    pub fn is_valid(&self) -> bool {
+        (1..=GridConfig::MAX_SIZE).contains(&self.width)
+            && (1..=GridConfig::MAX_SIZE).contains(&self.height)
+    }
+
+    /// Returns the preset following this one, wrapping around
+    pub fn next_preset(&self) -> GridConfig {
+        let idx = GridConfig::PRESETS
+            .iter()
+            .position(|preset| preset == self)
+            .map(|idx| (idx + 1) % GridConfig::PRESETS.len())
+            .unwrap_or_default();
+        GridConfig::PRESETS[idx]
+    }
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "bevy", derive(Resource))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TileGrid {
+    config: GridConfig,
+    grid: HashMap<GridCoordinates, TileType>,
+    // Ordered, so that picking a random spawn point only depends on the RNG
+    unused_coordinates: BTreeSet<GridCoordinates>,
+    seed: u64,
+    // Every spawn decision goes through this RNG
+    rng: GameRng,
+    // Set by the game rather than saved with the run
+    #[cfg_attr(feature = "serde", serde(skip))]
+    spawn_table: SpawnTable,
+}
+
+impl Default for TileGrid {
+    fn default() -> Self {
+        TileGrid::new(GridConfig::default(), 0)
+    }
+}
+
+impl TileGrid {
+    pub fn new(config: GridConfig, seed: u64) -> Self {
+        let unused_coordinates = (0..config.width)
+            .flat_map(|x| (0..config.height).map(move |y| GridCoordinates { x, y }))
+            .collect();
+        TileGrid {
+            config,
+            grid: HashMap::default(),
+            unused_coordinates,
+            seed,
+            rng: rng_from_seed(seed),
+            spawn_table: SpawnTable::default(),
+        }
+    }
+
+    pub fn config(&self) -> &GridConfig {
+        &self.config
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn can_move_tile(&self, at_coords: &GridCoordinates, dir: MoveDirection) -> CanMoveResult {
+        let target_coords = at_coords.coords_after_move(dir);
+        if let Some(tile_type) = self.grid.get(&target_coords) {
+            if !tile_type.is_movable() {
+                return CanMoveResult::No;
+            }
+            return CanMoveResult::YesIfNextCanMove;
+        }
+
+        CanMoveResult::Yes
+    }
+
+    pub fn can_combine_tile(
+        &self,
+        at_coords: &GridCoordinates,
+        dir: MoveDirection,
+    ) -> CanCombineResult {
+        let src_type = self.grid.get(at_coords).expect("Failed to find source");
+        let target_coords = at_coords.coords_after_move(dir);
+
+        if let Some(tile_type) = self.grid.get(&target_coords) {
+            if let Some(result) = src_type.try_combine_with(tile_type) {
+                return CanCombineResult::Yes(result);
+            }
+            return CanCombineResult::No;
+        }
+        CanCombineResult::No
+    }
+
+    /// Applies the events of a valid move, returning the chain reaction of its explosions. They
+    /// all go off together, as the first wave.
+    pub fn apply_events(&mut self, events: &Vec<ValidEvent>) -> ChainReaction {
+        let mut chain_reaction = ChainReaction::default();
+        let mut exploded = false;
+        for event in events {
+            match event {
+                ValidEvent::Move(e) => self.handle_move_tile_event(e),
+                ValidEvent::Merge(e) => self.handle_combine_event(e),
+                ValidEvent::Explosions(_) if exploded => (),
+                ValidEvent::Explosions(_) => {
+                    exploded = true;
+                    chain_reaction = self.handle_explosion_events(events.iter().filter_map(
+                        |event| match event {
+                            ValidEvent::Explosions(e) => Some(e),
+                            _ => None,
+                        },
+                    ));
+                }
+                ValidEvent::Solidify(e) => self.handle_solidify_event(e),
+            }
+        }
+        chain_reaction
+    }
+
+    pub fn handle_move_tile_event(&mut self, event: &MoveTileEvent) {
+        if let Some(tile_type) = self.grid.remove(&event.source) {
+            self.insert(event.target.clone(), tile_type);
+        }
+        self.unused_coordinates.insert(event.source.clone());
+    }
+
+    pub fn handle_move_tile_events<'a, I: Iterator<Item = &'a MoveTileEvent>>(
+        &mut self,
+        events: I,
+    ) {
+        let new_tiles: Vec<(GridCoordinates, TileType)> = events
+            .map(|MoveTileEvent { source, target }| {
+                let src_type = self
+                    .grid
+                    .remove(source)
+                    .expect("Failed to find source in grid");
+                self.unused_coordinates.insert(source.clone());
+                (target.clone(), src_type)
+            })
+            .collect();
+        for (coord, new_type) in new_tiles {
+            self.insert(coord, new_type);
+        }
+    }
+
+    pub fn handle_combine_event(&mut self, event: &MergeTilesEvent) {
+        let MergeTilesEvent {
+            source,
+            target,
+            resulting_type,
+        } = event;
+        self.grid.remove(source);
+        self.grid.remove(target);
+        self.unused_coordinates.insert(source.clone());
+        self.unused_coordinates.insert(target.clone());
+        self.maybe_insert(target.clone(), *resulting_type);
+    }
+
+    pub fn handle_combine_events<'a, I: Iterator<Item = &'a MergeTilesEvent>>(
+        &mut self,
+        events: I,
+    ) {
+        let mut deletions = Vec::default();
+        let mut maybe_insertions = Vec::default();
+        for event in events {
+            let MergeTilesEvent {
+                source,
+                target,
+                resulting_type,
+            } = event;
+            deletions.push(source);
+            deletions.push(target);
+            maybe_insertions.push((target.clone(), *resulting_type));
+        }
+
+        for deletion_coords in deletions {
+            self.unused_coordinates.insert(deletion_coords.clone());
+            self.grid.remove(deletion_coords);
+        }
+        for (coords, maybe_tile_type) in maybe_insertions {
+            self.maybe_insert(coords, maybe_tile_type);
+        }
+    }
+
+    pub fn handle_solidify_event(&mut self, event: &SolidifyEvent) {
+        let SolidifyEvent { source, target } = event;
+        self.insert(source.clone(), TileType::Wall);
+        self.insert(target.clone(), TileType::Wall);
+    }
+
+    /// Removes the exploded tiles, and those caught by the bombs they set off
+    pub fn handle_explosion_event(&mut self, event: &ExplosionEvent) -> ChainReaction {
+        self.handle_explosion_events(std::iter::once(event))
+    }
+
+    /// Sets off the explosions together, and then the bombs caught in them, wave after wave
+    pub fn handle_explosion_events<'a, I: Iterator<Item = &'a ExplosionEvent>>(
+        &mut self,
+        events: I,
+    ) -> ChainReaction {
+        let mut chain_reaction = ChainReaction::default();
+        let mut explosions: Vec<ExplosionEvent> = events.cloned().collect();
+        while !explosions.is_empty() {
+            let mut destroyed = Vec::new();
+            let mut damaged = Vec::new();
+            let mut base_points = 0;
+            let mut next_explosions = Vec::new();
+            // Overlapping blasts only hit a wall once per wave
+            let mut hit = HashSet::new();
+            for coords in explosions.iter().flat_map(ExplosionEvent::area) {
+                let Some(tile) = self.get(&coords).copied() else {
+                    continue;
+                };
+                if !hit.insert(coords.clone()) {
+                    continue;
+                }
+                // The border holds the grid together
+                let result = if self.is_inside(&coords) {
+                    tile.explosion_result()
+                } else {
+                    ExplosionResult::NoExplosion
+                };
+                match result {
+                    ExplosionResult::NoExplosion => (),
+                    ExplosionResult::Damage(damaged_tile) => {
+                        self.grid.insert(coords.clone(), damaged_tile);
+                        damaged.push((coords, damaged_tile));
+                    }
+                    ExplosionResult::ScorePoints(tile_points) => {
+                        base_points += i64::from(tile_points);
+                        self.unused_coordinates.insert(coords.clone());
+                        self.grid.remove(&coords);
+                        // The bombs that went off already don't go off again
+                        let radius = tile.blast_radius();
+                        if radius > 0 && explosions.iter().all(|e| e.target != coords) {
+                            next_explosions.push(ExplosionEvent {
+                                target: coords.clone(),
+                                radius,
+                            });
+                        }
+                        destroyed.push((coords, tile));
+                    }
+                }
+            }
+            chain_reaction.waves.push(ExplosionWave {
+                explosions,
+                destroyed,
+                damaged,
+                base_points,
+            });
+            explosions = next_explosions;
+        }
+        chain_reaction
+    }
+
+    /// How far the RNG went, which tells apart grids that will spawn their next tiles elsewhere
+    pub(crate) fn rng_position(&self) -> u128 {
+        self.rng.get_word_pos()
+    }
+
+    fn get_unused_coordinate(&mut self) -> Option<GridCoordinates> {
+        // Quick and hacky way to get an element from a set
+        // I don't have a better idea rn
+        if self.unused_coordinates.is_empty() {
+            return None;
+        }
+        let idx = self.rng.gen_range(0..self.unused_coordinates.len());
+        self.unused_coordinates.iter().nth(idx).cloned()
+    }
+
+    pub fn insert(&mut self, coords: GridCoordinates, tile_type: TileType) -> Option<TileType> {
+        self.unused_coordinates.remove(&coords);
+        self.grid.insert(coords, tile_type)
+    }
+
+    pub fn maybe_insert(
+        &mut self,
+        coords: GridCoordinates,
+        maybe_tile_type: Option<TileType>,
+    ) -> Option<TileType> {
+        if let Some(tile_type) = maybe_tile_type {
+            self.unused_coordinates.remove(&coords);
+            return self.insert(coords, tile_type);
+        }
+        self.unused_coordinates.insert(coords.clone());
+        self.grid.remove(&coords)
+    }
+
+    pub fn get(&self, coords: &GridCoordinates) -> Option<&TileType> {
+        self.grid.get(coords)
+    }
+
+    /// Every tile on the grid, walls included
+    pub fn tiles(&self) -> impl Iterator<Item = (&GridCoordinates, &TileType)> {
+        self.grid.iter()
+    }
+
+    pub fn has_any_possible_moves(&self) -> bool {
+        let GridConfig { width, height } = self.config;
+        (0..width)
+            .flat_map(move |x| (0..height).map(move |y| GridCoordinates { x, y }))
+            .any(|coords| {
+                self.has_possible_moves_for_direction(&coords, MoveDirection::Left)
+                    || self.has_possible_moves_for_direction(&coords, MoveDirection::Right)
+                    || self.has_possible_moves_for_direction(&coords, MoveDirection::Up)
+                    || self.has_possible_moves_for_direction(&coords, MoveDirection::Down)
+            })
+    }
+
+    fn has_possible_moves_for_direction(
+        &self,
+        coords: &GridCoordinates,
+        move_direction: MoveDirection,
+    ) -> bool {
+        let candidate_coords = coords.candidate_coords_for_dir(move_direction, &self.config);
+        match ValidatedEventQueue::validate_move(self, candidate_coords, move_direction) {
+            ValidatedEventQueue::ValidMove(_) => true,
+            ValidatedEventQueue::InvalidMove => false,
+        }
+    }
+
+    pub fn is_inside(&self, coords: &GridCoordinates) -> bool {
+        (0..self.config.width).contains(&coords.x) && (0..self.config.height).contains(&coords.y)
+    }
+
+    /// Where a selection cursor lands when moved from `coords` : the next cell in that direction
+    /// that is not a wall, if there is one inside the grid.
+    pub fn next_selectable_coords(
+        &self,
+        coords: &GridCoordinates,
+        dir: MoveDirection,
+    ) -> Option<GridCoordinates> {
+        let mut candidate = coords.coords_after_move(dir);
+        while self.is_inside(&candidate) {
+            if !self.get(&candidate).is_some_and(TileType::is_wall) {
+                return Some(candidate);
+            }
+            candidate = candidate.coords_after_move(dir);
+        }
+        None
+    }
+
+    /// Cells where a new tile can spawn
+    pub fn unused_coordinates(&self) -> impl Iterator<Item = &GridCoordinates> {
+        self.unused_coordinates.iter()
+    }
+
+    /// Gives the grid a new source of randomness, e.g. so that a simulation of the rest of the
+    /// run can't know which tiles will actually spawn. The seed of the run is kept.
+    pub fn reseed_rng(&mut self, seed: u64) {
+        self.rng = rng_from_seed(seed);
+    }
+
+    pub fn spawn_table(&self) -> &SpawnTable {
+        &self.spawn_table
+    }
+
+    /// Only affects the tiles spawned from now on
+    pub fn set_spawn_table(&mut self, spawn_table: SpawnTable) {
+        self.spawn_table = spawn_table;
+    }
+
+    pub fn has_unused_coordinates(&self) -> bool {
+        !self.unused_coordinates.is_empty()
+    }
+
+    pub fn try_spawn_new_tile(&mut self) -> Option<SpawnEvent> {
+        let coords = self.get_unused_coordinate()?;
+        let tile_type = self.spawn_table.pick(&mut self.rng);
+        self.insert(coords.clone(), tile_type);
+        Some(SpawnEvent { coords, tile_type })
+    }
+
+    pub fn spawn_first_tile(&mut self) -> Option<SpawnEvent> {
+        self.try_spawn_tile(TileType::Coin(CoinValue::ONE))
+    }
+
+    /// Spawns this tile rather than one from the spawn table, e.g. the next one of a puzzle
+    pub fn try_spawn_tile(&mut self, tile_type: TileType) -> Option<SpawnEvent> {
+        let coords = self.get_unused_coordinate()?;
+        self.insert(coords.clone(), tile_type);
+        Some(SpawnEvent { coords, tile_type })
+    }
+
+    pub fn setup_default_grid(&mut self) -> Vec<SpawnEvent> {
+        let GridConfig { width, height } = self.config;
+        let mut spawn_events = Vec::default();
+        for x in -1..=width {
+            let tile_type = TileType::Wall;
+            let coords_bottom = GridCoordinates { x, y: -1 };
+            self.insert(coords_bottom.clone(), tile_type);
+            spawn_events.push(SpawnEvent {
+                coords: coords_bottom,
+                tile_type,
+            });
+
+            let coords_top = GridCoordinates { x, y: height };
+            self.insert(coords_top.clone(), tile_type);
+            spawn_events.push(SpawnEvent {
+                coords: coords_top,
+                tile_type,
+            });
+        }
+        for y in -1..=height {
+            let tile_type = TileType::Wall;
+            let coords_left = GridCoordinates { x: -1, y };
+            self.insert(coords_left.clone(), tile_type);
+            spawn_events.push(SpawnEvent {
+                coords: coords_left,
+                tile_type,
+            });
+
+            let coords_right = GridCoordinates { x: width, y };
+            self.insert(coords_right.clone(), tile_type);
+            spawn_events.push(SpawnEvent {
+                coords: coords_right,
+                tile_type,
+            });
+        }
+
+        spawn_events
+    }
+}
+
+pub struct SpawnEvent {
+    pub coords: GridCoordinates,
+    pub tile_type: TileType,
+}
+
+#[cfg(test)]
+pub mod tests {
+    use crate::{
+        grid::{GridConfig, GridCoordinates, MoveTileEvent, SpawnEvent, TileGrid},
+        moves::{
+            CanMoveResult, ExplosionEvent, MergeTilesEvent, MoveDirection, ValidEvent,
+            ValidatedEventQueue,
+        },
+        tile::{CoinValue, TileType, WALL_HIT_POINTS},
+    };
+
+    #[test]
+    fn can_move_tile_when_empty() {
+        let mut tile_grid = TileGrid::default();
+        tile_grid.insert(
+            GridCoordinates { x: 0, y: 0 },
+            TileType::Coin(CoinValue::ONE),
+        );
+
+        assert_eq!(
+            tile_grid.can_move_tile(&GridCoordinates { x: 0, y: 0 }, MoveDirection::Right),
+            CanMoveResult::Yes
+        );
+        assert_eq!(
+            tile_grid.can_move_tile(&GridCoordinates { x: 0, y: 0 }, MoveDirection::Left),
+            CanMoveResult::Yes
+        );
+        assert_eq!(
+            tile_grid.can_move_tile(&GridCoordinates { x: 0, y: 0 }, MoveDirection::Up),
+            CanMoveResult::Yes
+        );
+        assert_eq!(
+            tile_grid.can_move_tile(&GridCoordinates { x: 0, y: 0 }, MoveDirection::Down),
+            CanMoveResult::Yes
+        );
+    }
+
+    #[test]
+    fn can_not_move_when_next_to_wall() {
+        let mut tile_grid = TileGrid::default();
+        tile_grid.insert(
+            GridCoordinates { x: 0, y: 0 },
+            TileType::Coin(CoinValue::ONE),
+        );
+        tile_grid.insert(GridCoordinates { x: 1, y: 0 }, TileType::Wall);
+
+        assert_eq!(
+            tile_grid.can_move_tile(&GridCoordinates { x: 0, y: 0 }, MoveDirection::Right),
+            CanMoveResult::No
+        );
+        assert_eq!(
+            tile_grid.can_move_tile(&GridCoordinates { x: 0, y: 0 }, MoveDirection::Left),
+            CanMoveResult::Yes
+        );
+        assert_eq!(
+            tile_grid.can_move_tile(&GridCoordinates { x: 0, y: 0 }, MoveDirection::Up),
+            CanMoveResult::Yes
+        );
+        assert_eq!(
+            tile_grid.can_move_tile(&GridCoordinates { x: 0, y: 0 }, MoveDirection::Down),
+            CanMoveResult::Yes
+        );
+    }
+
+    #[test]
+    fn can_move_when_next_to_coin() {
+        let mut tile_grid = TileGrid::default();
+        tile_grid.insert(
+            GridCoordinates { x: 0, y: 0 },
+            TileType::Coin(CoinValue::ONE),
+        );
+        tile_grid.insert(
+            GridCoordinates { x: 1, y: 0 },
+            TileType::Coin(CoinValue::ONE),
+        );
+
+        assert_eq!(
+            tile_grid.can_move_tile(&GridCoordinates { x: 0, y: 0 }, MoveDirection::Right),
+            CanMoveResult::YesIfNextCanMove
+        );
+        assert_eq!(
+            tile_grid.can_move_tile(&GridCoordinates { x: 0, y: 0 }, MoveDirection::Left),
+            CanMoveResult::Yes
+        );
+        assert_eq!(
+            tile_grid.can_move_tile(&GridCoordinates { x: 0, y: 0 }, MoveDirection::Up),
+            CanMoveResult::Yes
+        );
+        assert_eq!(
+            tile_grid.can_move_tile(&GridCoordinates { x: 0, y: 0 }, MoveDirection::Down),
+            CanMoveResult::Yes
+        );
+    }
+
+    #[test]
+    fn should_maybe_insert_tile() {
+        let mut tile_grid = TileGrid::default();
+        tile_grid.insert(GridCoordinates { x: 0, y: 0 }, TileType::Wall);
+
+        tile_grid.maybe_insert(GridCoordinates { x: 1, y: 0 }, Some(TileType::Wall));
+        assert_eq!(
+            tile_grid.get(&GridCoordinates { x: 1, y: 0 }),
+            Some(&TileType::Wall)
+        );
+
+        tile_grid.maybe_insert(GridCoordinates { x: 1, y: 0 }, None);
+        assert_eq!(tile_grid.get(&GridCoordinates { x: 1, y: 0 }), None);
+    }
+
+    #[allow(clippy::useless_vec)]
+    #[test]
+    fn should_combine_coin_tiles() {
+        let mut tile_grid = TileGrid::default();
+        tile_grid.insert(
+            GridCoordinates { x: 0, y: 0 },
+            TileType::Coin(CoinValue::ONE),
+        );
+        tile_grid.insert(
+            GridCoordinates { x: 1, y: 0 },
+            TileType::Coin(CoinValue::ONE),
+        );
+
+        let events = vec![MergeTilesEvent {
+            source: GridCoordinates { x: 0, y: 0 },
+            target: GridCoordinates { x: 1, y: 0 },
+            resulting_type: Some(TileType::Coin(CoinValue::TWO)),
+        }];
+        tile_grid.handle_combine_events(events.iter());
+
+        assert_eq!(tile_grid.get(&GridCoordinates { x: 0, y: 0 }), None);
+        assert_eq!(
+            tile_grid.get(&GridCoordinates { x: 1, y: 0 }),
+            Some(&TileType::Coin(CoinValue::TWO))
+        );
+    }
+
+    #[test]
+    fn should_explode_tiles() {
+        let mut tile_grid = TileGrid::default();
+        // x
+        // x
+        // x11 1
+        // xxxxxx
+        // Explosion at 0,0 should remove :
+        // * 0,0
+        // * 1,0
+        // * Leave walls untouched
+        tile_grid.insert(GridCoordinates { x: -1, y: 0 }, TileType::Wall);
+        tile_grid.insert(
+            GridCoordinates { x: 0, y: 0 },
+            TileType::Coin(CoinValue::ONE),
+        );
+        tile_grid.insert(
+            GridCoordinates { x: 1, y: 0 },
+            TileType::Coin(CoinValue::ONE),
+        );
+        tile_grid.insert(
+            GridCoordinates { x: 3, y: 0 },
+            TileType::Coin(CoinValue::TWO),
+        );
+
+        tile_grid.handle_explosion_event(&ExplosionEvent {
+            target: GridCoordinates { x: 0, y: 0 },
+            radius: 1,
+        });
+
+        assert_eq!(
+            tile_grid.get(&GridCoordinates { x: -1, y: 0 }),
+            Some(&TileType::Wall)
+        );
+        assert_eq!(tile_grid.get(&GridCoordinates { x: 0, y: 0 }), None);
+        assert_eq!(tile_grid.get(&GridCoordinates { x: 1, y: 0 }), None,);
+        assert_eq!(
+            tile_grid.get(&GridCoordinates { x: 3, y: 0 }),
+            Some(&TileType::Coin(CoinValue::TWO))
+        );
+    }
+
+    #[test]
+    fn should_explode_big_bombs_further() {
+        let mut tile_grid = TileGrid::default();
+        let coin = TileType::Coin(CoinValue::ONE);
+        tile_grid.insert(GridCoordinates { x: 1, y: 1 }, TileType::BigBomb);
+        tile_grid.insert(GridCoordinates { x: 2, y: 1 }, TileType::Bomb);
+        tile_grid.insert(GridCoordinates { x: 1, y: 3 }, coin);
+        tile_grid.insert(GridCoordinates { x: 0, y: 2 }, coin);
+        tile_grid.insert(GridCoordinates { x: 3, y: 3 }, coin);
+
+        let coords = GridCoordinates { x: 2, y: 1 }
+            .candidate_coords_for_dir(MoveDirection::Left, tile_grid.config());
+        let ValidatedEventQueue::ValidMove(events) =
+            ValidatedEventQueue::validate_move(&tile_grid, coords, MoveDirection::Left)
+        else {
+            panic!("Pushing a bomb into a big bomb should be valid");
+        };
+        assert!(events.contains(&ValidEvent::Explosions(ExplosionEvent {
+            target: GridCoordinates { x: 1, y: 1 },
+            radius: 2,
+        })));
+
+        // Both bombs, and the coins up to 2 steps away from the big one, but not further
+        assert_eq!(tile_grid.apply_events(&events).points(), 5);
+        assert_eq!(tile_grid.get(&GridCoordinates { x: 1, y: 3 }), None);
+        assert_eq!(tile_grid.get(&GridCoordinates { x: 0, y: 2 }), None);
+        assert_eq!(tile_grid.get(&GridCoordinates { x: 3, y: 3 }), Some(&coin));
+    }
+
+    #[test]
+    fn should_update_unused_coordinates() {
+        let mut tile_grid = TileGrid::default();
+        tile_grid.insert(GridCoordinates { x: 0, y: 0 }, TileType::Wall);
+
+        assert!(!tile_grid
+            .unused_coordinates
+            .contains(&GridCoordinates { x: 0, y: 0 }));
+        assert!(tile_grid
+            .unused_coordinates
+            .contains(&GridCoordinates { x: 1, y: 0 }));
+
+        // Deletes the tile at 0,0
+        tile_grid.maybe_insert(GridCoordinates { x: 0, y: 0 }, None);
+        assert!(tile_grid
+            .unused_coordinates
+            .contains(&GridCoordinates { x: 0, y: 0 }));
+
+        // Inserts a tile at 1,0
+        tile_grid.maybe_insert(GridCoordinates { x: 1, y: 0 }, Some(TileType::Bomb));
+        assert!(!tile_grid
+            .unused_coordinates
+            .contains(&GridCoordinates { x: 1, y: 0 }));
+    }
+
+    #[allow(clippy::useless_vec)]
+    #[test]
+    fn unused_coordinates_sync_after_move_event() {
+        let mut tile_grid = TileGrid::default();
+        tile_grid.insert(GridCoordinates { x: 1, y: 0 }, TileType::Bomb);
+        tile_grid.insert(
+            GridCoordinates { x: 2, y: 0 },
+            TileType::Coin(CoinValue::ONE),
+        );
+
+        // Moves tile 1,0 -> 2,0
+        let events = vec![
+            MoveTileEvent {
+                source: GridCoordinates { x: 1, y: 0 },
+                target: GridCoordinates { x: 2, y: 0 },
+            },
+            MoveTileEvent {
+                source: GridCoordinates { x: 2, y: 0 },
+                target: GridCoordinates { x: 3, y: 0 },
+            },
+        ];
+        tile_grid.handle_move_tile_events(events.iter());
+        assert!(tile_grid
+            .unused_coordinates
+            .contains(&GridCoordinates { x: 1, y: 0 }));
+        assert!(!tile_grid
+            .unused_coordinates
+            .contains(&GridCoordinates { x: 2, y: 0 }));
+        assert!(!tile_grid
+            .unused_coordinates
+            .contains(&GridCoordinates { x: 3, y: 0 }));
+    }
+
+    #[test]
+    fn unused_coordinates_sync_after_combine_event() {
+        let mut tile_grid = TileGrid::default();
+        tile_grid.insert(
+            GridCoordinates { x: 1, y: 0 },
+            TileType::Coin(CoinValue::ONE),
+        );
+        tile_grid.insert(
+            GridCoordinates { x: 2, y: 0 },
+            TileType::Coin(CoinValue::ONE),
+        );
+
+        // Combine tiles 1,0 and 2,0 in 2,0
+        tile_grid.handle_combine_event(&MergeTilesEvent {
+            source: GridCoordinates { x: 1, y: 0 },
+            target: GridCoordinates { x: 2, y: 0 },
+            resulting_type: Some(TileType::Coin(CoinValue::TWO)),
+        });
+        assert!(tile_grid
+            .unused_coordinates
+            .contains(&GridCoordinates { x: 1, y: 0 }));
+        assert!(!tile_grid
+            .unused_coordinates
+            .contains(&GridCoordinates { x: 2, y: 0 }));
+    }
+
+    #[test]
+    fn unused_coordinates_sync_after_explosion_event() {
+        let mut tile_grid = TileGrid::default();
+        tile_grid.insert(
+            GridCoordinates { x: 1, y: 0 },
+            TileType::Coin(CoinValue::ONE),
+        );
+        tile_grid.insert(
+            GridCoordinates { x: 2, y: 0 },
+            TileType::Coin(CoinValue::ONE),
+        );
+        tile_grid.insert(
+            GridCoordinates { x: 3, y: 0 },
+            TileType::Coin(CoinValue::ONE),
+        );
+        tile_grid.insert(
+            GridCoordinates { x: 4, y: 0 },
+            TileType::Coin(CoinValue::ONE),
+        );
+
+        // Combine tiles 1,0 and 2,0 in 2,0
+        tile_grid.handle_explosion_event(&ExplosionEvent {
+            target: GridCoordinates { x: 2, y: 0 },
+            radius: 1,
+        });
+        assert!(tile_grid
+            .unused_coordinates
+            .contains(&GridCoordinates { x: 1, y: 0 }));
+        assert!(tile_grid
+            .unused_coordinates
+            .contains(&GridCoordinates { x: 2, y: 0 }));
+        assert!(tile_grid
+            .unused_coordinates
+            .contains(&GridCoordinates { x: 3, y: 0 }));
+        assert!(!tile_grid
+            .unused_coordinates
+            .contains(&GridCoordinates { x: 4, y: 0 }));
+    }
+
+    #[test]
+    fn has_any_possible_moves_should_work() {
+        let mut tile_grid = TileGrid::default();
+        tile_grid.insert(GridCoordinates { x: 0, y: 0 }, TileType::Bomb);
+
+        assert!(tile_grid.has_any_possible_moves());
+
+        let GridConfig { width, height } = GridConfig::default();
+        for coord in
+            (-1..=width).flat_map(move |x| (-1..height).map(move |y| GridCoordinates { x, y }))
+        {
+            tile_grid.insert(coord, TileType::Wall);
+        }
+        tile_grid.insert(GridCoordinates { x: 0, y: 0 }, TileType::Bomb);
+
+        assert!(!tile_grid.has_any_possible_moves());
+    }
+
+    #[test]
+    fn should_setup_non_square_grid() {
+        let mut tile_grid = TileGrid::new(
+            GridConfig {
+                width: 5,
+                height: 7,
+            },
+            0,
+        );
+        let spawn_events = tile_grid.setup_default_grid();
+
+        // Corners are spawned twice, but the walls surround the whole grid
+        assert!(spawn_events
+            .iter()
+            .all(|event| event.tile_type == TileType::Wall));
+        assert_eq!(tile_grid.unused_coordinates.len(), 5 * 7);
+        assert_eq!(
+            tile_grid.get(&GridCoordinates { x: 5, y: 7 }),
+            Some(&TileType::Wall)
+        );
+        assert_eq!(
+            tile_grid.get(&GridCoordinates { x: -1, y: 6 }),
+            Some(&TileType::Wall)
+        );
+        assert_eq!(tile_grid.get(&GridCoordinates { x: 4, y: 6 }), None);
+
+        let candidates = GridCoordinates { x: 4, y: 0 }
+            .candidate_coords_for_dir(MoveDirection::Up, tile_grid.config());
+        assert_eq!(candidates.len(), 8);
+        assert_eq!(candidates.last(), Some(&GridCoordinates { x: 4, y: 7 }));
+    }
+
+    #[test]
+    fn should_cycle_through_grid_presets() {
+        let mut grid_config = GridConfig::default();
+        for preset in GridConfig::PRESETS.iter().skip(1) {
+            grid_config = grid_config.next_preset();
+            assert_eq!(&grid_config, preset);
+        }
+        assert_eq!(grid_config.next_preset(), GridConfig::default());
+    }
+
+    #[test]
+    fn should_score_exploded_tiles_once() {
+        let mut tile_grid = TileGrid::default();
+        tile_grid.insert(GridCoordinates { x: -1, y: 0 }, TileType::Wall);
+        tile_grid.insert(GridCoordinates { x: 0, y: 0 }, TileType::Bomb);
+        tile_grid.insert(GridCoordinates { x: 1, y: 0 }, TileType::Bomb);
+        tile_grid.insert(
+            GridCoordinates { x: 1, y: 1 },
+            TileType::Coin(CoinValue::FOUR),
+        );
+        tile_grid.insert(
+            GridCoordinates { x: 2, y: 0 },
+            TileType::Coin(CoinValue::TWO),
+        );
+
+        // Both blasts overlap, but each tile only scores once. Walls don't score.
+        let chain_reaction = tile_grid.handle_explosion_events(
+            [
+                ExplosionEvent {
+                    target: GridCoordinates { x: 0, y: 0 },
+                    radius: 1,
+                },
+                ExplosionEvent {
+                    target: GridCoordinates { x: 1, y: 0 },
+                    radius: 1,
+                },
+            ]
+            .iter(),
+        );
+        assert_eq!(chain_reaction.points(), 1 + 1 + 4 + 2);
+    }
+
+    #[test]
+    fn should_only_break_walls_inside_the_grid() {
+        let mut tile_grid = TileGrid::default();
+        let border = GridCoordinates { x: -1, y: 0 };
+        let inside = GridCoordinates { x: 1, y: 0 };
+        tile_grid.insert(border.clone(), TileType::Wall);
+        tile_grid.insert(inside.clone(), TileType::Wall);
+        let explosion = ExplosionEvent {
+            target: GridCoordinates { x: 0, y: 0 },
+            radius: 1,
+        };
+
+        let chain_reaction = tile_grid.handle_explosion_event(&explosion);
+        assert_eq!(
+            chain_reaction.waves[0].damaged,
+            [(inside.clone(), TileType::CrackedWall(WALL_HIT_POINTS - 1))]
+        );
+        for _ in 1..WALL_HIT_POINTS {
+            tile_grid.handle_explosion_event(&explosion);
+        }
+        assert_eq!(tile_grid.get(&inside), None);
+        assert!(tile_grid.unused_coordinates.contains(&inside));
+        assert_eq!(tile_grid.get(&border), Some(&TileType::Wall));
+    }
+
+    #[test]
+    fn should_set_off_bombs_caught_in_a_blast() {
+        let mut tile_grid = TileGrid::default();
+        let coin = |value, x, y| (GridCoordinates { x, y }, TileType::Coin(value));
+        for (coords, tile_type) in [
+            (GridCoordinates { x: 0, y: 0 }, TileType::Bomb),
+            (GridCoordinates { x: 1, y: 0 }, TileType::Bomb),
+            coin(CoinValue::ONE, 0, 1),
+            // Caught by the first wave
+            (GridCoordinates { x: 2, y: 0 }, TileType::Bomb),
+            coin(CoinValue::TWO, 3, 0),
+            // Caught by the second one
+            (GridCoordinates { x: 2, y: 1 }, TileType::BigBomb),
+            coin(CoinValue::FOUR, 2, 3),
+            // Out of reach
+            coin(CoinValue::EIGHT, 4, 3),
+        ] {
+            tile_grid.insert(coords, tile_type);
+        }
+
+        let chain_reaction = tile_grid.handle_explosion_events(
+            [
+                ExplosionEvent {
+                    target: GridCoordinates { x: 0, y: 0 },
+                    radius: 1,
+                },
+                ExplosionEvent {
+                    target: GridCoordinates { x: 1, y: 0 },
+                    radius: 1,
+                },
+            ]
+            .iter(),
+        );
+
+        let waves: Vec<(Vec<ExplosionEvent>, usize, i64)> = chain_reaction
+            .waves
+            .iter()
+            .map(|wave| {
+                (
+                    wave.explosions.clone(),
+                    wave.destroyed.len(),
+                    wave.base_points,
+                )
+            })
+            .collect();
+        assert_eq!(
+            waves[1..],
+            [
+                (
+                    vec![ExplosionEvent {
+                        target: GridCoordinates { x: 2, y: 0 },
+                        radius: 1,
+                    }],
+                    2,
+                    2 + 2,
+                ),
+                (
+                    vec![ExplosionEvent {
+                        target: GridCoordinates { x: 2, y: 1 },
+                        radius: 2,
+                    }],
+                    1,
+                    4,
+                ),
+            ]
+        );
+        assert_eq!((waves[0].1, waves[0].2), (4, 1 + 1 + 1 + 1));
+        // Each wave is worth more than the last
+        assert_eq!(chain_reaction.points(), 4 + 4 * 2 + 4 * 3);
+        assert_eq!(tile_grid.tiles().count(), 1);
+    }
+
+    #[test]
+    fn same_seed_should_spawn_same_tiles() {
+        let spawn_tiles = |seed| {
+            let mut tile_grid = TileGrid::new(GridConfig::default(), seed);
+            tile_grid.setup_default_grid();
+            let first = tile_grid.spawn_first_tile().expect("Grid is empty");
+            let mut spawned = vec![(first.coords, first.tile_type)];
+            while let Some(SpawnEvent { coords, tile_type }) = tile_grid.try_spawn_new_tile() {
+                spawned.push((coords, tile_type));
+            }
+            spawned
+        };
+
+        let spawned = spawn_tiles(42);
+        assert_eq!(spawned.len(), 16);
+        assert_eq!(spawned, spawn_tiles(42));
+        assert_ne!(spawned, spawn_tiles(43));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn saved_grid_should_keep_spawning_the_same_tiles() {
+        use std::collections::HashMap;
+
+        let mut tile_grid = TileGrid::new(GridConfig::default(), 7);
+        tile_grid.setup_default_grid();
+        tile_grid.spawn_first_tile();
+        tile_grid.try_spawn_new_tile();
+
+        let saved = ron::to_string(&tile_grid).expect("Failed to save grid");
+        let mut restored: TileGrid = ron::from_str(&saved).expect("Failed to load grid");
+        assert_eq!(restored.seed(), tile_grid.seed());
+        assert_eq!(
+            restored.tiles().collect::<HashMap<_, _>>(),
+            tile_grid.tiles().collect::<HashMap<_, _>>()
+        );
+        while let Some(SpawnEvent { coords, tile_type }) = tile_grid.try_spawn_new_tile() {
+            let restored_spawn = restored.try_spawn_new_tile().expect("Grid is full");
+            assert_eq!(restored_spawn.coords, coords);
+            assert_eq!(restored_spawn.tile_type, tile_type);
+        }
+        assert!(!restored.has_unused_coordinates());
+    }
+
+    #[test]
+    fn selection_should_skip_walls() {
+        let mut tile_grid = TileGrid::default();
+        tile_grid.setup_default_grid();
+        tile_grid.insert(GridCoordinates { x: 1, y: 0 }, TileType::Wall);
+        tile_grid.insert(GridCoordinates { x: 2, y: 0 }, TileType::CrackedWall(1));
+        tile_grid.insert(GridCoordinates { x: 0, y: 1 }, TileType::Bomb);
+
+        let origin = GridCoordinates { x: 0, y: 0 };
+        assert_eq!(
+            tile_grid.next_selectable_coords(&origin, MoveDirection::Right),
+            Some(GridCoordinates { x: 3, y: 0 })
+        );
+        assert_eq!(
+            tile_grid.next_selectable_coords(&origin, MoveDirection::Up),
+            Some(GridCoordinates { x: 0, y: 1 })
+        );
+        assert_eq!(
+            tile_grid.next_selectable_coords(&origin, MoveDirection::Left),
+            None
+        );
+        assert_eq!(
+            tile_grid.next_selectable_coords(&GridCoordinates { x: 3, y: 0 }, MoveDirection::Right),
+            None
+        );
+    }
+}
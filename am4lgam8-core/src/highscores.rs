@@ -0,0 +1,203 @@
+//! The best runs played on this device.
+//!
+//! The table is stored as plain text, one run per line, best first :
+//!
+//! ```text
+//! AM4LGAM8-SCORES 1
+//! 1234 ABC 2023-10-01 4x4 987654321 42
+//! ```
+//!
+//! Each line holds the score, the initials (`-` when none were entered), the date, the grid size,
+//! the seed and the number of moves played.
+
+use std::{fmt, str::FromStr};
+
+#[cfg(feature = "bevy")]
+use bevy_ecs::prelude::Resource;
+
+use crate::{date::Date, grid::GridConfig};
+
+const HEADER: &str = "AM4LGAM8-SCORES 1";
+
+/// How many runs the table keeps
+pub const MAX_HIGH_SCORES: usize = 10;
+/// How many letters the initials have, at most
+pub const MAX_INITIALS: usize = 3;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HighScoreEntry {
+    pub initials: String,
+    pub score: i64,
+    pub date: Date,
+    pub seed: u64,
+    pub grid_config: GridConfig,
+    pub moves_played: u32,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "bevy", derive(Resource))]
+pub struct HighScores {
+    entries: Vec<HighScoreEntry>,
+}
+
+impl HighScores {
+    /// Best first
+    pub fn entries(&self) -> &[HighScoreEntry] {
+        &self.entries
+    }
+
+    pub fn qualifies(&self, score: i64) -> bool {
+        score > 0
+            && (self.entries.len() < MAX_HIGH_SCORES
+                || self.entries.last().is_some_and(|last| score > last.score))
+    }
+
+    /// Adds the run to the table if it is good enough, and returns its rank. Ties go to the run
+    /// that was played first.
+    pub fn insert(&mut self, entry: HighScoreEntry) -> Option<usize> {
+        if !self.qualifies(entry.score) {
+            return None;
+        }
+        let rank = self
+            .entries
+            .iter()
+            .position(|other| entry.score > other.score)
+            .unwrap_or(self.entries.len());
+        self.entries.insert(rank, entry);
+        self.entries.truncate(MAX_HIGH_SCORES);
+        Some(rank)
+    }
+
+    /// Initials are kept to a few uppercase letters
+    pub fn set_initials(&mut self, rank: usize, initials: &str) {
+        if let Some(entry) = self.entries.get_mut(rank) {
+            entry.initials = initials
+                .chars()
+                .filter(char::is_ascii_alphabetic)
+                .map(|c| c.to_ascii_uppercase())
+                .take(MAX_INITIALS)
+                .collect();
+        }
+    }
+}
+
+impl fmt::Display for HighScores {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{HEADER}")?;
+        for entry in &self.entries {
+            let initials = if entry.initials.is_empty() {
+                "-"
+            } else {
+                &entry.initials
+            };
+            writeln!(
+                f,
+                "{} {} {} {}x{} {} {}",
+                entry.score,
+                initials,
+                entry.date,
+                entry.grid_config.width,
+                entry.grid_config.height,
+                entry.seed,
+                entry.moves_played
+            )?;
+        }
+        Ok(())
+    }
+}
+
+fn parse_entry(line: &str) -> Result<HighScoreEntry, String> {
+    let invalid = || format!("Invalid high score `{line}`");
+    let fields: Vec<&str> = line.split_whitespace().collect();
+    let [score, initials, date, size, seed, moves_played] = fields[..] else {
+        return Err(invalid());
+    };
+    let (width, height) = size.split_once('x').ok_or_else(invalid)?;
+    Ok(HighScoreEntry {
+        initials: if initials == "-" {
+            String::new()
+        } else {
+            initials.to_string()
+        },
+        score: score.parse().map_err(|_| invalid())?,
+        date: date.parse()?,
+        seed: seed.parse().map_err(|_| invalid())?,
+        grid_config: GridConfig {
+            width: width.parse().map_err(|_| invalid())?,
+            height: height.parse().map_err(|_| invalid())?,
+        },
+        moves_played: moves_played.parse().map_err(|_| invalid())?,
+    })
+}
+
+impl FromStr for HighScores {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut lines = s.lines();
+        if lines.next().map(str::trim) != Some(HEADER) {
+            return Err("Not an AM4LGAM8 high score table".to_string());
+        }
+
+        let mut entries: Vec<HighScoreEntry> = lines
+            .filter(|line| !line.trim().is_empty())
+            .map(parse_entry)
+            .collect::<Result<_, _>>()?;
+        // The file may have been edited by hand
+        entries.sort_by_key(|entry| std::cmp::Reverse(entry.score));
+        entries.truncate(MAX_HIGH_SCORES);
+        Ok(HighScores { entries })
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use crate::{
+        date::Date,
+        grid::GridConfig,
+        highscores::{HighScoreEntry, HighScores, MAX_HIGH_SCORES},
+    };
+
+    fn entry(score: i64) -> HighScoreEntry {
+        HighScoreEntry {
+            initials: String::new(),
+            score,
+            date: Date::from_days_since_epoch(19_631),
+            seed: 42,
+            grid_config: GridConfig::default(),
+            moves_played: 12,
+        }
+    }
+
+    #[test]
+    fn should_keep_the_best_scores_in_order() {
+        let mut high_scores = HighScores::default();
+        assert_eq!(high_scores.insert(entry(0)), None);
+        assert_eq!(high_scores.insert(entry(10)), Some(0));
+        assert_eq!(high_scores.insert(entry(30)), Some(0));
+        // Ties go to the older run
+        assert_eq!(high_scores.insert(entry(10)), Some(2));
+
+        for score in 100..(100 + MAX_HIGH_SCORES as i64) {
+            high_scores.insert(entry(score));
+        }
+        assert_eq!(high_scores.entries().len(), MAX_HIGH_SCORES);
+        assert!(!high_scores.qualifies(100));
+        assert_eq!(high_scores.insert(entry(50)), None);
+        assert_eq!(high_scores.insert(entry(1000)), Some(0));
+        assert_eq!(high_scores.entries().last().map(|e| e.score), Some(101));
+    }
+
+    #[test]
+    fn should_round_trip_through_text() {
+        let mut high_scores = HighScores::default();
+        high_scores.insert(entry(120));
+        let rank = high_scores.insert(entry(340)).unwrap();
+        high_scores.set_initials(rank, "ab1cd");
+        assert_eq!(high_scores.entries()[0].initials, "ABC");
+
+        let text = high_scores.to_string();
+        assert_eq!(text.parse::<HighScores>(), Ok(high_scores));
+        assert!("AM4LGAM8-SCORES 1\n12 ABC".parse::<HighScores>().is_err());
+    }
+}
@@ -0,0 +1,143 @@
+//! Undo/redo of whole moves, by keeping snapshots of the game state
+
+#[cfg(feature = "bevy")]
+use bevy_ecs::prelude::Resource;
+
+use crate::{grid::TileGrid, score::GameScore};
+
+/// Everything a move can change. The grid carries the RNG, so that redoing a move spawns the
+/// same tile again.
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    pub tile_grid: TileGrid,
+    pub score: GameScore,
+}
+
+#[derive(Debug, Default)]
+#[cfg_attr(feature = "bevy", derive(Resource))]
+pub struct MoveHistory {
+    undo_stack: Vec<Snapshot>,
+    redo_stack: Vec<Snapshot>,
+    used_undo: bool,
+}
+
+impl MoveHistory {
+    /// Records the state before a move is applied. This forgets the moves that could be redone.
+    pub fn record(&mut self, before_move: Snapshot) {
+        self.undo_stack.push(before_move);
+        self.redo_stack.clear();
+    }
+
+    /// Returns the state to go back to, if any
+    pub fn undo(&mut self, current: Snapshot) -> Option<Snapshot> {
+        let previous = self.undo_stack.pop()?;
+        self.redo_stack.push(current);
+        self.used_undo = true;
+        Some(previous)
+    }
+
+    /// Returns the state to go forward to, if any
+    pub fn redo(&mut self, current: Snapshot) -> Option<Snapshot> {
+        let next = self.redo_stack.pop()?;
+        self.undo_stack.push(current);
+        Some(next)
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo_stack.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo_stack.is_empty()
+    }
+
+    /// Runs that used undo are practice runs, and don't count for leaderboards
+    pub fn is_practice(&self) -> bool {
+        self.used_undo
+    }
+
+    /// Used when a run is resumed : the moves before are gone, but undo was used during them
+    pub fn mark_practice(&mut self) {
+        self.used_undo = true;
+    }
+
+    pub fn clear(&mut self) {
+        *self = MoveHistory::default();
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use crate::{
+        grid::{GridCoordinates, TileGrid},
+        history::{MoveHistory, Snapshot},
+        score::GameScore,
+        tile::{CoinValue, TileType},
+    };
+
+    fn snapshot_with_score(points: i64) -> Snapshot {
+        let mut score = GameScore::default();
+        score.add(points);
+        Snapshot {
+            tile_grid: TileGrid::default(),
+            score,
+        }
+    }
+
+    #[test]
+    fn should_undo_and_redo_moves() {
+        let mut history = MoveHistory::default();
+        history.record(snapshot_with_score(0));
+        history.record(snapshot_with_score(1));
+
+        let previous = history
+            .undo(snapshot_with_score(2))
+            .expect("Nothing to undo");
+        assert_eq!(previous.score.get(), 1);
+        let previous = history.undo(previous).expect("Nothing to undo");
+        assert_eq!(previous.score.get(), 0);
+        assert!(history.undo(previous.clone()).is_none());
+
+        let next = history.redo(previous).expect("Nothing to redo");
+        assert_eq!(next.score.get(), 1);
+        let next = history.redo(next).expect("Nothing to redo");
+        assert_eq!(next.score.get(), 2);
+        assert!(history.redo(next).is_none());
+        assert!(history.is_practice());
+    }
+
+    #[test]
+    fn new_move_should_forget_redo() {
+        let mut history = MoveHistory::default();
+        history.record(snapshot_with_score(0));
+        assert!(!history.is_practice());
+
+        let previous = history
+            .undo(snapshot_with_score(1))
+            .expect("Nothing to undo");
+        assert!(history.can_redo());
+        history.record(previous);
+        assert!(!history.can_redo());
+        assert!(history.is_practice());
+    }
+
+    #[test]
+    fn snapshot_should_restore_spawns() {
+        let mut tile_grid = TileGrid::default();
+        tile_grid.setup_default_grid();
+        tile_grid.insert(
+            GridCoordinates { x: 0, y: 0 },
+            TileType::Coin(CoinValue::ONE),
+        );
+        let snapshot = Snapshot {
+            tile_grid: tile_grid.clone(),
+            score: GameScore::default(),
+        };
+
+        let first_spawn = tile_grid.try_spawn_new_tile().expect("Grid is full");
+        let mut restored = snapshot.tile_grid;
+        let second_spawn = restored.try_spawn_new_tile().expect("Grid is full");
+        assert_eq!(first_spawn.coords, second_spawn.coords);
+        assert_eq!(first_spawn.tile_type, second_spawn.tile_type);
+    }
+}
@@ -0,0 +1,29 @@
+//! The domain logic of the game itself, decoupled from Bevy.
+//!
+//! Bots, servers and tests can depend on this crate without pulling in a renderer.
+//! With the `bevy` feature, the following Bevy traits are implemented to facilitate the
+//! integration with the engine :
+//!
+//! * Event
+//! * Component
+//! * Resource
+//!
+//! With the `serde` feature, the state of a run (grid, RNG, score and replay) can be
+//! serialized, to be saved and resumed later.
+
+pub mod ai;
+pub mod blitz;
+pub mod daily;
+pub mod date;
+pub mod grid;
+pub mod highscores;
+pub mod history;
+pub mod moves;
+pub mod puzzle;
+pub mod replay;
+pub mod rng;
+pub mod score;
+pub mod session;
+pub mod solver;
+pub mod spawn;
+pub mod tile;
@@ -1,28 +1,83 @@
-use bevy::prelude::Event;
+#[cfg(feature = "bevy")]
+use bevy_ecs::prelude::Event;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
-use super::{
+use crate::{
     grid::{GridCoordinates, MoveTileEvent, TileGrid},
     tile::{CombinationResult, TileType},
 };
 
 // Events
 
-#[derive(Debug, PartialEq, Eq, Event)]
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "bevy", derive(Event))]
 pub struct ValidMoveEvent {
     pub coords: GridCoordinates,
     pub move_direction: MoveDirection,
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, Event)]
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "bevy", derive(Event))]
 pub struct MergeTilesEvent {
     pub source: GridCoordinates,
     pub target: GridCoordinates,
     pub resulting_type: Option<TileType>,
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, Event)]
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "bevy", derive(Event))]
 pub struct ExplosionEvent {
     pub target: GridCoordinates,
+    /// From [`TileType::blast_radius`]
+    pub radius: i32,
+}
+
+impl ExplosionEvent {
+    /// Every coordinates caught in the blast, whether there is a tile there or not
+    pub fn area(&self) -> Vec<GridCoordinates> {
+        self.target.explosion_radius(self.radius)
+    }
+}
+
+/// The blasts that went off together, and the tiles they destroyed
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ExplosionWave {
+    pub explosions: Vec<ExplosionEvent>,
+    pub destroyed: Vec<(GridCoordinates, TileType)>,
+    /// The walls that stood, and what they turned into
+    pub damaged: Vec<(GridCoordinates, TileType)>,
+    /// What the destroyed tiles are worth, before the combo multiplier
+    pub base_points: i64,
+}
+
+/// The explosions of a move, wave after wave : the bombs caught in a wave go off in the next one
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct ChainReaction {
+    pub waves: Vec<ExplosionWave>,
+}
+
+impl ChainReaction {
+    /// The points of the wave at `depth` (from 0) are multiplied by this
+    pub fn multiplier(depth: usize) -> i64 {
+        depth as i64 + 1
+    }
+
+    pub fn points(&self) -> i64 {
+        self.waves
+            .iter()
+            .enumerate()
+            .map(|(depth, wave)| wave.base_points * ChainReaction::multiplier(depth))
+            .sum()
+    }
+}
+
+/// Two blocks that set into walls, where they stand
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "bevy", derive(Event))]
+pub struct SolidifyEvent {
+    pub source: GridCoordinates,
+    pub target: GridCoordinates,
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -30,6 +85,7 @@ pub enum ValidEvent {
     Move(MoveTileEvent),
     Merge(MergeTilesEvent),
     Explosions(ExplosionEvent),
+    Solidify(SolidifyEvent),
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -58,6 +114,7 @@ pub enum CanCombineResult {
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum MoveDirection {
     Up,
     Down,
@@ -82,8 +139,8 @@ impl ValidatedEventQueue {
         for coords in candidate_coords {
             match tile_grid.get(&coords) {
                 Some(tile_type) => match tile_type {
-                    TileType::Bomb | TileType::Coin(_) => (),
-                    TileType::Wall => break,
+                    TileType::Bomb | TileType::BigBomb | TileType::Block | TileType::Coin(_) => (),
+                    TileType::Wall | TileType::CrackedWall(_) => break,
                 },
                 None => break,
             }
@@ -100,9 +157,23 @@ impl ValidatedEventQueue {
                             }));
                         }
                         CombinationResult::Explosion => {
-                            valid_events
-                                .push(ValidEvent::Explosions(ExplosionEvent { target: coords }));
-                            valid_events.push(ValidEvent::Explosions(ExplosionEvent { target }));
+                            // Each bomb explodes as far as it reaches
+                            for target in [coords, target] {
+                                let radius =
+                                    tile_grid.get(&target).map_or(0, TileType::blast_radius);
+                                valid_events.push(ValidEvent::Explosions(ExplosionEvent {
+                                    target,
+                                    radius,
+                                }));
+                            }
+                        }
+                        CombinationResult::Solidify => {
+                            // The blocks don't make room, so the tiles pushing them stay put
+                            valid_events.clear();
+                            valid_events.push(ValidEvent::Solidify(SolidifyEvent {
+                                source: coords,
+                                target,
+                            }));
                         }
                     }
                     return ValidatedEventQueue::valid_move_set(valid_events);
@@ -140,18 +211,22 @@ impl ValidatedEventQueue {
 
 #[cfg(test)]
 pub mod tests {
-    use crate::game::{
+    use crate::{
         grid::{GridCoordinates, MoveTileEvent, TileGrid},
-        moves::{ExplosionEvent, MergeTilesEvent, MoveDirection, ValidEvent, ValidatedEventQueue},
+        moves::{
+            ChainReaction, ExplosionEvent, MergeTilesEvent, MoveDirection, SolidifyEvent,
+            ValidEvent, ValidatedEventQueue,
+        },
         tile::{CoinValue, TileType},
     };
 
+    #[allow(clippy::useless_vec, clippy::nonminimal_bool)]
     #[test]
     fn should_move_tile() {
         let mut tile_grid = TileGrid::default();
         tile_grid.insert(
             GridCoordinates { x: 0, y: 0 },
-            TileType::Coin(CoinValue::One),
+            TileType::Coin(CoinValue::ONE),
         );
 
         let move_events = vec![MoveTileEvent {
@@ -164,16 +239,17 @@ pub mod tests {
         assert!(tile_grid.get(&GridCoordinates { x: 1, y: 0 }).is_some());
     }
 
+    #[allow(clippy::useless_vec, clippy::nonminimal_bool)]
     #[test]
     fn should_move_many_tiles() {
         let mut tile_grid = TileGrid::default();
         tile_grid.insert(
             GridCoordinates { x: 0, y: 0 },
-            TileType::Coin(CoinValue::One),
+            TileType::Coin(CoinValue::ONE),
         );
         tile_grid.insert(
             GridCoordinates { x: 1, y: 0 },
-            TileType::Coin(CoinValue::Two),
+            TileType::Coin(CoinValue::TWO),
         );
 
         let move_events = vec![
@@ -191,11 +267,11 @@ pub mod tests {
         assert!(!tile_grid.get(&GridCoordinates { x: 0, y: 0 }).is_some());
         assert_eq!(
             tile_grid.get(&GridCoordinates { x: 1, y: 0 }),
-            Some(&TileType::Coin(CoinValue::One)),
+            Some(&TileType::Coin(CoinValue::ONE)),
         );
         assert_eq!(
             tile_grid.get(&GridCoordinates { x: 2, y: 0 }),
-            Some(&TileType::Coin(CoinValue::Two)),
+            Some(&TileType::Coin(CoinValue::TWO)),
         );
     }
 
@@ -204,11 +280,11 @@ pub mod tests {
         let mut tile_grid = TileGrid::default();
         tile_grid.insert(
             GridCoordinates { x: 0, y: 0 },
-            TileType::Coin(CoinValue::One),
+            TileType::Coin(CoinValue::ONE),
         );
         tile_grid.insert(
             GridCoordinates { x: 1, y: 0 },
-            TileType::Coin(CoinValue::Two),
+            TileType::Coin(CoinValue::TWO),
         );
 
         let validated_event_queue = ValidatedEventQueue::validate_move(
@@ -243,9 +319,10 @@ pub mod tests {
         tile_grid.insert(GridCoordinates { x: 0, y: 0 }, TileType::Bomb);
         tile_grid.insert(
             GridCoordinates { x: 1, y: 0 },
-            TileType::Coin(CoinValue::One),
+            TileType::Coin(CoinValue::ONE),
         );
-        let coords = GridCoordinates { x: 1, y: 0 }.candidate_coords_for_dir(MoveDirection::Left);
+        let coords = GridCoordinates { x: 1, y: 0 }
+            .candidate_coords_for_dir(MoveDirection::Left, tile_grid.config());
         let invalid_move =
             ValidatedEventQueue::validate_move(&tile_grid, coords, MoveDirection::Left);
         assert_eq!(invalid_move, ValidatedEventQueue::InvalidMove);
@@ -258,20 +335,23 @@ pub mod tests {
         tile_grid.insert(GridCoordinates { x: 1, y: 0 }, TileType::Bomb);
         tile_grid.insert(
             GridCoordinates { x: 2, y: 0 },
-            TileType::Coin(CoinValue::One),
+            TileType::Coin(CoinValue::ONE),
         );
 
-        let coords = GridCoordinates { x: 2, y: 0 }.candidate_coords_for_dir(MoveDirection::Left);
+        let coords = GridCoordinates { x: 2, y: 0 }
+            .candidate_coords_for_dir(MoveDirection::Left, tile_grid.config());
         let validated_event_queue =
             ValidatedEventQueue::validate_move(&tile_grid, coords, MoveDirection::Left);
         assert_eq!(
             validated_event_queue,
             ValidatedEventQueue::ValidMove(vec![
                 ValidEvent::Explosions(ExplosionEvent {
-                    target: GridCoordinates { x: 0, y: 0 }
+                    target: GridCoordinates { x: 0, y: 0 },
+                    radius: 1,
                 }),
                 ValidEvent::Explosions(ExplosionEvent {
-                    target: GridCoordinates { x: 1, y: 0 }
+                    target: GridCoordinates { x: 1, y: 0 },
+                    radius: 1,
                 }),
                 ValidEvent::Move(MoveTileEvent {
                     source: GridCoordinates { x: 2, y: 0 },
@@ -281,20 +361,62 @@ pub mod tests {
         );
     }
 
+    #[test]
+    fn should_push_to_solidify_blocks() {
+        let mut tile_grid = TileGrid::default();
+        tile_grid.insert(GridCoordinates { x: 0, y: 0 }, TileType::Block);
+        tile_grid.insert(GridCoordinates { x: 1, y: 0 }, TileType::Block);
+        tile_grid.insert(
+            GridCoordinates { x: 2, y: 0 },
+            TileType::Coin(CoinValue::ONE),
+        );
+
+        let coords = GridCoordinates { x: 2, y: 0 }
+            .candidate_coords_for_dir(MoveDirection::Left, tile_grid.config());
+        let validated_event_queue =
+            ValidatedEventQueue::validate_move(&tile_grid, coords, MoveDirection::Left);
+        // The coin stays behind the new walls
+        assert_eq!(
+            validated_event_queue,
+            ValidatedEventQueue::ValidMove(vec![ValidEvent::Solidify(SolidifyEvent {
+                source: GridCoordinates { x: 1, y: 0 },
+                target: GridCoordinates { x: 0, y: 0 },
+            })])
+        );
+
+        let ValidatedEventQueue::ValidMove(events) = validated_event_queue else {
+            unreachable!()
+        };
+        assert_eq!(tile_grid.apply_events(&events), ChainReaction::default());
+        assert_eq!(
+            tile_grid.get(&GridCoordinates { x: 0, y: 0 }),
+            Some(&TileType::Wall)
+        );
+        assert_eq!(
+            tile_grid.get(&GridCoordinates { x: 1, y: 0 }),
+            Some(&TileType::Wall)
+        );
+        assert_eq!(
+            tile_grid.get(&GridCoordinates { x: 2, y: 0 }),
+            Some(&TileType::Coin(CoinValue::ONE))
+        );
+    }
+
     #[test]
     fn should_push_to_merge_tiles_right() {
         let mut tile_grid = TileGrid::default();
         tile_grid.insert(GridCoordinates { x: 0, y: 0 }, TileType::Bomb);
         tile_grid.insert(
             GridCoordinates { x: 1, y: 0 },
-            TileType::Coin(CoinValue::One),
+            TileType::Coin(CoinValue::ONE),
         );
         tile_grid.insert(
             GridCoordinates { x: 2, y: 0 },
-            TileType::Coin(CoinValue::One),
+            TileType::Coin(CoinValue::ONE),
         );
 
-        let coords = GridCoordinates { x: 0, y: 0 }.candidate_coords_for_dir(MoveDirection::Right);
+        let coords = GridCoordinates { x: 0, y: 0 }
+            .candidate_coords_for_dir(MoveDirection::Right, tile_grid.config());
         let validated_event_queue =
             ValidatedEventQueue::validate_move(&tile_grid, coords, MoveDirection::Right);
         assert_eq!(
@@ -303,7 +425,7 @@ pub mod tests {
                 ValidEvent::Merge(MergeTilesEvent {
                     source: GridCoordinates { x: 1, y: 0 },
                     target: GridCoordinates { x: 2, y: 0 },
-                    resulting_type: Some(TileType::Coin(CoinValue::Two)),
+                    resulting_type: Some(TileType::Coin(CoinValue::TWO)),
                 }),
                 ValidEvent::Move(MoveTileEvent {
                     source: GridCoordinates { x: 0, y: 0 },
@@ -319,14 +441,15 @@ pub mod tests {
         tile_grid.insert(GridCoordinates { x: 2, y: 0 }, TileType::Bomb);
         tile_grid.insert(
             GridCoordinates { x: 1, y: 0 },
-            TileType::Coin(CoinValue::One),
+            TileType::Coin(CoinValue::ONE),
         );
         tile_grid.insert(
             GridCoordinates { x: 0, y: 0 },
-            TileType::Coin(CoinValue::One),
+            TileType::Coin(CoinValue::ONE),
         );
 
-        let coords = GridCoordinates { x: 2, y: 0 }.candidate_coords_for_dir(MoveDirection::Left);
+        let coords = GridCoordinates { x: 2, y: 0 }
+            .candidate_coords_for_dir(MoveDirection::Left, tile_grid.config());
         let validated_event_queue =
             ValidatedEventQueue::validate_move(&tile_grid, coords, MoveDirection::Left);
         assert_eq!(
@@ -335,7 +458,7 @@ pub mod tests {
                 ValidEvent::Merge(MergeTilesEvent {
                     source: GridCoordinates { x: 1, y: 0 },
                     target: GridCoordinates { x: 0, y: 0 },
-                    resulting_type: Some(TileType::Coin(CoinValue::Two)),
+                    resulting_type: Some(TileType::Coin(CoinValue::TWO)),
                 }),
                 ValidEvent::Move(MoveTileEvent {
                     source: GridCoordinates { x: 2, y: 0 },
@@ -351,21 +474,22 @@ pub mod tests {
         tile_grid.insert(GridCoordinates { x: -1, y: 0 }, TileType::Wall);
         tile_grid.insert(
             GridCoordinates { x: 1, y: 0 },
-            TileType::Coin(CoinValue::Two),
+            TileType::Coin(CoinValue::TWO),
         );
         tile_grid.insert(
             GridCoordinates { x: 2, y: 0 },
-            TileType::Coin(CoinValue::Two),
+            TileType::Coin(CoinValue::TWO),
         );
         tile_grid.insert(GridCoordinates { x: 3, y: 0 }, TileType::Bomb);
-        let coords = GridCoordinates { x: 3, y: 0 }.candidate_coords_for_dir(MoveDirection::Left);
+        let coords = GridCoordinates { x: 3, y: 0 }
+            .candidate_coords_for_dir(MoveDirection::Left, tile_grid.config());
         let validated_event_queue =
             ValidatedEventQueue::validate_move(&tile_grid, coords, MoveDirection::Left);
         let expected_events = vec![
             ValidEvent::Merge(MergeTilesEvent {
                 source: GridCoordinates { x: 2, y: 0 },
                 target: GridCoordinates { x: 1, y: 0 },
-                resulting_type: Some(TileType::Coin(CoinValue::Four)),
+                resulting_type: Some(TileType::Coin(CoinValue::FOUR)),
             }),
             ValidEvent::Move(MoveTileEvent {
                 source: GridCoordinates { x: 3, y: 0 },
@@ -384,7 +508,7 @@ pub mod tests {
         );
         assert_eq!(
             tile_grid.get(&GridCoordinates { x: 1, y: 0 }),
-            Some(&TileType::Coin(CoinValue::Four))
+            Some(&TileType::Coin(CoinValue::FOUR))
         );
         assert_eq!(
             tile_grid.get(&GridCoordinates { x: 2, y: 0 }),
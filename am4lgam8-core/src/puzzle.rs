@@ -0,0 +1,753 @@
+//! Handcrafted levels : a grid laid out by hand, a few moves to reach a goal.
+//!
+//! A puzzle is written in RON, with the grid drawn as rows of tile names, top row first. Tiles
+//! are named like in spawn tables (`one`, `two`, `bomb`, `wall`...), and `.` is an empty cell :
+//!
+//! ```ron
+//! (
+//!     name: "First blast",
+//!     rows: [
+//!         ".    .    .    .",
+//!         ".    one  two  .",
+//!         ".    bomb bomb .",
+//!         ".    .    .    .",
+//!     ],
+//!     // Optional : one tile spawns after each move, in order, until there are none left
+//!     spawns: ["one", "bomb"],
+//!     moves: 1,
+//!     goal: ClearCoins,
+//! )
+//! ```
+//!
+//! The goal is a score to reach (`Score(20)`), coins to clear (`ClearCoins`) or a number of tiles
+//! to explode (`ExplodeTiles(12)`). The best number of moves each puzzle was solved in is kept in
+//! [`PuzzleRecords`].
+
+use std::{collections::BTreeMap, fmt, str::FromStr};
+
+#[cfg(feature = "bevy")]
+use bevy_ecs::prelude::Resource;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    grid::{GridConfig, GridCoordinates, SpawnEvent, TileGrid},
+    moves::{ChainReaction, MoveDirection, ValidEvent, ValidatedEventQueue},
+    score::GameScore,
+    spawn::{parse_tile, tile_name},
+    tile::TileType,
+};
+
+const RECORDS_HEADER: &str = "AM4LGAM8-PUZZLES 1";
+const EMPTY_CELL: &str = ".";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum PuzzleGoal {
+    /// Reach this score
+    Score(i64),
+    /// Leave no coin on the grid
+    ClearCoins,
+    /// Explode this many tiles, bombs and broken walls included
+    ExplodeTiles(u32),
+}
+
+impl PuzzleGoal {
+    pub fn is_reached(&self, tile_grid: &TileGrid, score: i64, tiles_exploded: u32) -> bool {
+        match self {
+            PuzzleGoal::Score(target) => score >= *target,
+            PuzzleGoal::ClearCoins => !tile_grid
+                .tiles()
+                .any(|(_, tile_type)| matches!(tile_type, TileType::Coin(_))),
+            PuzzleGoal::ExplodeTiles(target) => tiles_exploded >= *target,
+        }
+    }
+
+    /// The next kind of goal, for the editor, wrapping around
+    pub fn next_kind(&self) -> PuzzleGoal {
+        match self {
+            PuzzleGoal::Score(_) => PuzzleGoal::ClearCoins,
+            PuzzleGoal::ClearCoins => PuzzleGoal::ExplodeTiles(4),
+            PuzzleGoal::ExplodeTiles(_) => PuzzleGoal::Score(10),
+        }
+    }
+
+    /// Raises or lowers the target, which stays above 0. Clearing the coins has none.
+    pub fn adjust_target(&self, delta: i32) -> PuzzleGoal {
+        match *self {
+            PuzzleGoal::Score(target) => {
+                PuzzleGoal::Score(target.saturating_add(i64::from(delta)).max(1))
+            }
+            PuzzleGoal::ClearCoins => PuzzleGoal::ClearCoins,
+            PuzzleGoal::ExplodeTiles(target) => {
+                PuzzleGoal::ExplodeTiles(target.saturating_add_signed(delta).max(1))
+            }
+        }
+    }
+}
+
+impl fmt::Display for PuzzleGoal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PuzzleGoal::Score(target) => write!(f, "Score {target} points"),
+            PuzzleGoal::ClearCoins => write!(f, "Clear the coins"),
+            PuzzleGoal::ExplodeTiles(target) => write!(f, "Explode {target} tiles"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(try_from = "PuzzleFile", into = "PuzzleFile")
+)]
+pub struct Puzzle {
+    /// Also identifies the puzzle in the records
+    pub name: String,
+    pub config: GridConfig,
+    /// Inside the grid, the border walls go around them
+    pub tiles: Vec<(GridCoordinates, TileType)>,
+    /// Spawned one after each move, in order, until there are none left
+    pub spawns: Vec<TileType>,
+    /// Moves allowed to reach the goal
+    pub moves: u32,
+    pub goal: PuzzleGoal,
+    /// Picks where the spawned tiles land
+    pub seed: u64,
+}
+
+impl Puzzle {
+    /// An empty grid to draw on, solved by clearing coins in a single move
+    pub fn new(name: &str, config: GridConfig) -> Self {
+        Puzzle {
+            name: name.to_string(),
+            config,
+            tiles: Vec::new(),
+            spawns: Vec::new(),
+            moves: 1,
+            goal: PuzzleGoal::ClearCoins,
+            seed: 0,
+        }
+    }
+
+    pub fn tile(&self, coords: &GridCoordinates) -> Option<TileType> {
+        self.tiles
+            .iter()
+            .find_map(|(tile_coords, tile_type)| (tile_coords == coords).then_some(*tile_type))
+    }
+
+    /// Puts the tile on the cell, or empties it. Cells outside the grid are left alone.
+    pub fn set_tile(&mut self, coords: &GridCoordinates, tile_type: Option<TileType>) {
+        let GridConfig { width, height } = self.config;
+        if !(0..width).contains(&coords.x) || !(0..height).contains(&coords.y) {
+            return;
+        }
+        self.tiles.retain(|(tile_coords, _)| tile_coords != coords);
+        if let Some(tile_type) = tile_type {
+            self.tiles.push((coords.clone(), tile_type));
+        }
+    }
+
+    /// Shrinking the grid drops the tiles that no longer fit
+    pub fn resize(&mut self, config: GridConfig) {
+        self.config = config;
+        self.tiles
+            .retain(|(coords, _)| coords.x < config.width && coords.y < config.height);
+    }
+
+    /// Puzzles read from a file may not make sense
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() || self.name.contains('\n') {
+            return Err("A puzzle needs a name, on a single line".to_string());
+        }
+        if self.config.width < 1 || self.config.height < 1 {
+            return Err("The grid needs at least one cell".to_string());
+        }
+        let mut tiles = TileGrid::new(self.config, self.seed);
+        for (coords, _) in &self.tiles {
+            if !tiles.is_inside(coords) {
+                return Err(format!(
+                    "Tile at {},{} outside the grid",
+                    coords.x, coords.y
+                ));
+            }
+            if tiles.insert(coords.clone(), TileType::Wall).is_some() {
+                return Err(format!("Two tiles at {},{}", coords.x, coords.y));
+            }
+        }
+        if self.moves == 0 {
+            return Err("A puzzle needs 1 move at least".to_string());
+        }
+        match self.goal {
+            PuzzleGoal::Score(target) if target <= 0 => {
+                Err("The score to reach must be above 0".to_string())
+            }
+            PuzzleGoal::ExplodeTiles(0) => Err("Tiles to explode must be above 0".to_string()),
+            // It would be solved before the first move
+            PuzzleGoal::ClearCoins
+                if !self
+                    .tiles
+                    .iter()
+                    .any(|(_, tile_type)| matches!(tile_type, TileType::Coin(_))) =>
+            {
+                Err("There are no coins to clear".to_string())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// The grid as laid out by the puzzle, border walls included
+    pub fn setup_grid(&self) -> TileGrid {
+        let mut tile_grid = TileGrid::new(self.config, self.seed);
+        tile_grid.setup_default_grid();
+        for (coords, tile_type) in &self.tiles {
+            tile_grid.insert(coords.clone(), *tile_type);
+        }
+        tile_grid
+    }
+
+    /// Reads the grid from its rows of tile names, top row first
+    pub fn parse_rows<S: AsRef<str>>(
+        rows: &[S],
+    ) -> Result<(GridConfig, Vec<(GridCoordinates, TileType)>), String> {
+        let height = rows.len() as i32;
+        let width = rows
+            .first()
+            .map_or(0, |row| row.as_ref().split_whitespace().count()) as i32;
+        let mut tiles = Vec::new();
+        for (row, y) in rows.iter().zip((0..height).rev()) {
+            let cells: Vec<&str> = row.as_ref().split_whitespace().collect();
+            if cells.len() as i32 != width {
+                return Err(format!(
+                    "Expected {width} cells on each row, got `{}`",
+                    row.as_ref()
+                ));
+            }
+            for (x, cell) in cells.into_iter().enumerate() {
+                if cell != EMPTY_CELL {
+                    tiles.push((GridCoordinates { x: x as i32, y }, parse_tile(cell)?));
+                }
+            }
+        }
+        Ok((GridConfig { width, height }, tiles))
+    }
+
+    /// The grid as rows of tile names, top row first, with the columns lined up
+    pub fn rows(&self) -> Vec<String> {
+        let GridConfig { width, height } = self.config;
+        let names: Vec<Vec<String>> = (0..height)
+            .rev()
+            .map(|y| {
+                (0..width)
+                    .map(|x| {
+                        self.tile(&GridCoordinates { x, y })
+                            .map_or(EMPTY_CELL.to_string(), |tile_type| tile_name(&tile_type))
+                    })
+                    .collect()
+            })
+            .collect();
+        let column_width = names.iter().flatten().map(String::len).max().unwrap_or(1);
+        names
+            .iter()
+            .map(|cells| {
+                let row: Vec<String> = cells
+                    .iter()
+                    .map(|name| format!("{name:<column_width$}"))
+                    .collect();
+                row.join(" ").trim_end().to_string()
+            })
+            .collect()
+    }
+}
+
+/// A puzzle as written in its file
+#[cfg(feature = "serde")]
+#[derive(Serialize, Deserialize)]
+pub struct PuzzleFile {
+    name: String,
+    rows: Vec<String>,
+    #[serde(default)]
+    spawns: Vec<String>,
+    moves: u32,
+    goal: PuzzleGoal,
+    #[serde(default)]
+    seed: u64,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<PuzzleFile> for Puzzle {
+    type Error = String;
+
+    fn try_from(file: PuzzleFile) -> Result<Self, Self::Error> {
+        let (config, tiles) = Puzzle::parse_rows(&file.rows)?;
+        let spawns = file
+            .spawns
+            .iter()
+            .map(|name| parse_tile(name))
+            .collect::<Result<_, _>>()?;
+        let puzzle = Puzzle {
+            name: file.name,
+            config,
+            tiles,
+            spawns,
+            moves: file.moves,
+            goal: file.goal,
+            seed: file.seed,
+        };
+        puzzle.validate()?;
+        Ok(puzzle)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<Puzzle> for PuzzleFile {
+    fn from(puzzle: Puzzle) -> Self {
+        PuzzleFile {
+            rows: puzzle.rows(),
+            spawns: puzzle.spawns.iter().map(tile_name).collect(),
+            name: puzzle.name,
+            moves: puzzle.moves,
+            goal: puzzle.goal,
+            seed: puzzle.seed,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PuzzleOutcome {
+    Playing,
+    Solved,
+    /// Out of moves, or stuck
+    Failed,
+}
+
+/// A puzzle being played. The grid and the score are kept apart, like in the game.
+#[derive(Debug, Clone)]
+pub struct PuzzleRun {
+    puzzle: Puzzle,
+    /// The tiles exploded by each move played, following undo and redo
+    exploded: Vec<u32>,
+    undone: Vec<u32>,
+}
+
+impl PuzzleRun {
+    pub fn new(puzzle: Puzzle) -> Self {
+        PuzzleRun {
+            puzzle,
+            exploded: Vec::new(),
+            undone: Vec::new(),
+        }
+    }
+
+    pub fn puzzle(&self) -> &Puzzle {
+        &self.puzzle
+    }
+
+    pub fn moves_played(&self) -> u32 {
+        self.exploded.len() as u32
+    }
+
+    pub fn moves_left(&self) -> u32 {
+        self.puzzle.moves.saturating_sub(self.moves_played())
+    }
+
+    pub fn tiles_exploded(&self) -> u32 {
+        self.exploded.iter().sum()
+    }
+
+    /// Counts the move, once applied to the grid. This forgets the moves that could be redone.
+    pub fn record_move(&mut self, chain_reaction: &ChainReaction) {
+        let exploded = chain_reaction
+            .waves
+            .iter()
+            .map(|wave| wave.destroyed.len() as u32)
+            .sum();
+        self.exploded.push(exploded);
+        self.undone.clear();
+    }
+
+    pub fn undo(&mut self) {
+        if let Some(undone) = self.exploded.pop() {
+            self.undone.push(undone);
+        }
+    }
+
+    pub fn redo(&mut self) {
+        if let Some(redone) = self.undone.pop() {
+            self.exploded.push(redone);
+        }
+    }
+
+    /// Spawns the tile that follows the last move, if the sequence goes that far and there is
+    /// room for it. The n-th tile always follows the n-th move, so undoing a move spawns the same
+    /// tile again.
+    pub fn spawn_next(&self, tile_grid: &mut TileGrid) -> Option<SpawnEvent> {
+        let index = self.moves_played().checked_sub(1)? as usize;
+        let tile_type = *self.puzzle.spawns.get(index)?;
+        tile_grid.try_spawn_tile(tile_type)
+    }
+
+    /// Solving the puzzle with its last move counts
+    pub fn outcome(&self, tile_grid: &TileGrid, score: &GameScore) -> PuzzleOutcome {
+        if self
+            .puzzle
+            .goal
+            .is_reached(tile_grid, score.get(), self.tiles_exploded())
+        {
+            PuzzleOutcome::Solved
+        } else if self.moves_left() == 0 || !tile_grid.has_any_possible_moves() {
+            PuzzleOutcome::Failed
+        } else {
+            PuzzleOutcome::Playing
+        }
+    }
+
+    /// Plays a whole turn headlessly, like `GameSession::play` : the move, then the next tile of
+    /// the sequence. Returns the events of the move, or `None` if the move is not valid.
+    pub fn play(
+        &mut self,
+        tile_grid: &mut TileGrid,
+        score: &mut GameScore,
+        source_coords: &GridCoordinates,
+        move_direction: MoveDirection,
+    ) -> Option<Vec<ValidEvent>> {
+        let candidate_coords =
+            source_coords.candidate_coords_for_dir(move_direction, tile_grid.config());
+        match ValidatedEventQueue::validate_move(tile_grid, candidate_coords, move_direction) {
+            ValidatedEventQueue::InvalidMove => None,
+            ValidatedEventQueue::ValidMove(events) => {
+                let chain_reaction = tile_grid.apply_events(&events);
+                score.score_turn(&chain_reaction, tile_grid);
+                self.record_move(&chain_reaction);
+                self.spawn_next(tile_grid);
+                Some(events)
+            }
+        }
+    }
+}
+
+/// The fewest moves each puzzle was solved in, by name.
+///
+/// Stored as plain text, one puzzle per line after a header, e.g. `4 First blast`.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "bevy", derive(Resource))]
+pub struct PuzzleRecords {
+    best_moves: BTreeMap<String, u32>,
+}
+
+impl PuzzleRecords {
+    pub fn best_moves(&self, name: &str) -> Option<u32> {
+        self.best_moves.get(name).copied()
+    }
+
+    pub fn is_solved(&self, name: &str) -> bool {
+        self.best_moves.contains_key(name)
+    }
+
+    /// Returns true if this is the first time the puzzle is solved, or in fewer moves than before
+    pub fn record(&mut self, name: &str, moves: u32) -> bool {
+        if self.best_moves(name).is_some_and(|best| best <= moves) {
+            return false;
+        }
+        self.best_moves.insert(name.to_string(), moves);
+        true
+    }
+}
+
+impl fmt::Display for PuzzleRecords {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{RECORDS_HEADER}")?;
+        for (name, moves) in &self.best_moves {
+            writeln!(f, "{moves} {name}")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for PuzzleRecords {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut lines = s.lines();
+        if lines.next().map(str::trim) != Some(RECORDS_HEADER) {
+            return Err(format!("Expected the `{RECORDS_HEADER}` header"));
+        }
+        let mut records = PuzzleRecords::default();
+        for line in lines.map(str::trim).filter(|line| !line.is_empty()) {
+            let (moves, name) = line
+                .split_once(' ')
+                .ok_or_else(|| format!("Expected `moves name`, got `{line}`"))?;
+            let moves = moves
+                .parse()
+                .map_err(|_| format!("Invalid number of moves `{moves}`"))?;
+            records.record(name, moves);
+        }
+        Ok(records)
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use crate::{
+        grid::{GridConfig, GridCoordinates, TileGrid},
+        moves::{ChainReaction, MoveDirection},
+        puzzle::{Puzzle, PuzzleGoal, PuzzleOutcome, PuzzleRecords, PuzzleRun},
+        score::GameScore,
+        tile::{CoinValue, TileType},
+    };
+
+    fn puzzle(rows: &[&str], spawns: Vec<TileType>, moves: u32, goal: PuzzleGoal) -> Puzzle {
+        let (config, tiles) = Puzzle::parse_rows(rows).unwrap();
+        Puzzle {
+            name: "Test".to_string(),
+            config,
+            tiles,
+            spawns,
+            moves,
+            goal,
+            seed: 0,
+        }
+    }
+
+    /// Tiles to move, and where
+    type Moves = [(i32, i32, MoveDirection)];
+
+    /// Plays the moves in order, and returns how the puzzle went
+    fn solve(puzzle: &Puzzle, moves: &Moves) -> PuzzleOutcome {
+        let mut tile_grid = puzzle.setup_grid();
+        let mut score = GameScore::default();
+        let mut run = PuzzleRun::new(puzzle.clone());
+        for (x, y, move_direction) in moves {
+            assert_eq!(run.outcome(&tile_grid, &score), PuzzleOutcome::Playing);
+            let coords = GridCoordinates { x: *x, y: *y };
+            assert!(
+                run.play(&mut tile_grid, &mut score, &coords, *move_direction)
+                    .is_some(),
+                "Invalid move from {x},{y}"
+            );
+        }
+        run.outcome(&tile_grid, &score)
+    }
+
+    #[test]
+    fn should_read_the_grid_from_its_rows() {
+        let puzzle = puzzle(
+            &["one  .    .", "bomb wall 16"],
+            Vec::new(),
+            1,
+            PuzzleGoal::ClearCoins,
+        );
+        assert_eq!(
+            puzzle.config,
+            GridConfig {
+                width: 3,
+                height: 2
+            }
+        );
+        let tile_grid = puzzle.setup_grid();
+        let at = |x, y| tile_grid.get(&GridCoordinates { x, y }).copied();
+        assert_eq!(at(0, 1), Some(TileType::Coin(CoinValue::ONE)));
+        assert_eq!(at(0, 0), Some(TileType::Bomb));
+        assert_eq!(at(1, 0), Some(TileType::Wall));
+        assert_eq!(
+            at(2, 0),
+            Some(TileType::Coin(CoinValue::from_tier(4).unwrap()))
+        );
+        assert_eq!(at(1, 1), None);
+        // The border
+        assert_eq!(at(-1, 0), Some(TileType::Wall));
+
+        assert_eq!(puzzle.rows(), vec!["one  .    .", "bomb wall 16"]);
+        assert!(Puzzle::parse_rows(&["one .", "one"]).is_err());
+        assert!(Puzzle::parse_rows(&["coin"]).is_err());
+    }
+
+    #[test]
+    fn should_reject_puzzles_that_make_no_sense() {
+        let valid = puzzle(&["one one"], Vec::new(), 1, PuzzleGoal::Score(2));
+        assert!(valid.validate().is_ok());
+        let no_moves = Puzzle {
+            moves: 0,
+            ..valid.clone()
+        };
+        assert!(no_moves.validate().is_err());
+        let no_target = Puzzle {
+            goal: PuzzleGoal::ExplodeTiles(0),
+            ..valid.clone()
+        };
+        assert!(no_target.validate().is_err());
+        let no_coins = puzzle(&["bomb wall"], Vec::new(), 1, PuzzleGoal::ClearCoins);
+        assert!(no_coins.validate().is_err());
+        let outside = Puzzle {
+            tiles: vec![(GridCoordinates { x: 2, y: 0 }, TileType::Bomb)],
+            ..valid
+        };
+        assert!(outside.validate().is_err());
+    }
+
+    #[test]
+    fn should_end_when_the_goal_is_reached_or_the_moves_run_out() {
+        let rows = [".    one  .", ".    bomb bomb", ".    .    ."];
+        let clear = puzzle(&rows, Vec::new(), 2, PuzzleGoal::ClearCoins);
+        assert_eq!(
+            solve(&clear, &[(1, 1, MoveDirection::Right)]),
+            PuzzleOutcome::Solved
+        );
+        assert_eq!(
+            solve(
+                &clear,
+                &[(1, 2, MoveDirection::Left), (1, 1, MoveDirection::Left)]
+            ),
+            PuzzleOutcome::Failed
+        );
+
+        // The bombs explode with each other, the coin included
+        let explode = puzzle(&rows, Vec::new(), 2, PuzzleGoal::ExplodeTiles(3));
+        assert_eq!(
+            solve(&explode, &[(1, 1, MoveDirection::Right)]),
+            PuzzleOutcome::Solved
+        );
+    }
+
+    #[test]
+    fn should_spawn_the_sequence_after_each_move() {
+        let spawns = vec![TileType::Bomb, TileType::Coin(CoinValue::TWO)];
+        let puzzle = puzzle(&["one .", ".   ."], spawns, 5, PuzzleGoal::Score(100));
+        let mut tile_grid = puzzle.setup_grid();
+        let mut score = GameScore::default();
+        let mut run = PuzzleRun::new(puzzle);
+        let one = GridCoordinates { x: 0, y: 1 };
+        run.play(&mut tile_grid, &mut score, &one, MoveDirection::Down);
+        let spawned: Vec<TileType> = tile_grid
+            .tiles()
+            .filter(|(coords, _)| tile_grid.is_inside(coords))
+            .map(|(_, tile_type)| *tile_type)
+            .filter(|tile_type| *tile_type == TileType::Bomb)
+            .collect();
+        assert_eq!(spawned, vec![TileType::Bomb]);
+
+        // Undoing a move takes back its place in the sequence
+        run.undo();
+        assert_eq!(run.moves_played(), 0);
+        assert!(run.spawn_next(&mut TileGrid::default()).is_none());
+        run.redo();
+        assert_eq!((run.moves_played(), run.moves_left()), (1, 4));
+
+        let spawn_after_move = |run: &mut PuzzleRun| {
+            run.record_move(&ChainReaction::default());
+            run.spawn_next(&mut TileGrid::default())
+                .map(|event| event.tile_type)
+        };
+        assert_eq!(
+            spawn_after_move(&mut run),
+            Some(TileType::Coin(CoinValue::TWO))
+        );
+        assert_eq!(spawn_after_move(&mut run), None);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn shipped_puzzles_should_be_solvable() {
+        use MoveDirection::{Down, Left, Right, Up};
+        let puzzles: [(&str, &Moves); 5] = [
+            (
+                include_str!("../../assets/puzzles/01-first-blast.puzzle.ron"),
+                &[(1, 1, Right)],
+            ),
+            (
+                include_str!("../../assets/puzzles/02-pocket-change.puzzle.ron"),
+                &[(0, 2, Right), (1, 3, Down), (1, 2, Down), (0, 0, Right)],
+            ),
+            (
+                include_str!("../../assets/puzzles/03-chain-reaction.puzzle.ron"),
+                &[(0, 0, Up), (0, 1, Right)],
+            ),
+            (
+                include_str!("../../assets/puzzles/04-brick-by-brick.puzzle.ron"),
+                &[(0, 2, Up), (0, 3, Right), (2, 0, Up), (1, 1, Right)],
+            ),
+            (
+                include_str!("../../assets/puzzles/05-incoming.puzzle.ron"),
+                &[
+                    (1, 2, Up),
+                    (1, 3, Right),
+                    (2, 2, Down),
+                    (2, 3, Down),
+                    (3, 2, Left),
+                    (2, 0, Up),
+                ],
+            ),
+        ];
+        for (text, solution) in puzzles {
+            let puzzle: Puzzle = ron::from_str(text).expect("Invalid puzzle");
+            assert_eq!(
+                solve(&puzzle, solution),
+                PuzzleOutcome::Solved,
+                "{}",
+                puzzle.name
+            );
+
+            // Written back the way it was read
+            let written = ron::to_string(&puzzle).unwrap();
+            assert_eq!(ron::from_str::<Puzzle>(&written), Ok(puzzle));
+        }
+    }
+
+    #[test]
+    fn should_edit_the_grid_cell_by_cell() {
+        let mut puzzle = Puzzle::new("Draft", GridConfig::square(4));
+        let coin = TileType::Coin(CoinValue::ONE);
+        puzzle.set_tile(&GridCoordinates { x: 0, y: 0 }, Some(coin));
+        puzzle.set_tile(&GridCoordinates { x: 3, y: 3 }, Some(TileType::Bomb));
+        puzzle.set_tile(&GridCoordinates { x: 3, y: 3 }, Some(TileType::Wall));
+        puzzle.set_tile(&GridCoordinates { x: 4, y: 0 }, Some(TileType::Bomb));
+        assert_eq!(puzzle.tiles.len(), 2);
+        assert_eq!(
+            puzzle.tile(&GridCoordinates { x: 3, y: 3 }),
+            Some(TileType::Wall)
+        );
+        assert_eq!(
+            puzzle.rows(),
+            vec![
+                ".    .    .    wall",
+                ".    .    .    .",
+                ".    .    .    .",
+                "one  .    .    .",
+            ]
+        );
+        assert!(puzzle.validate().is_ok());
+
+        puzzle.resize(GridConfig::square(3));
+        assert_eq!(puzzle.tiles, vec![(GridCoordinates { x: 0, y: 0 }, coin)]);
+        puzzle.set_tile(&GridCoordinates { x: 0, y: 0 }, None);
+        assert!(puzzle.tiles.is_empty());
+    }
+
+    #[test]
+    fn should_cycle_through_goals() {
+        let goal = PuzzleGoal::ClearCoins;
+        assert_eq!(goal.adjust_target(5), PuzzleGoal::ClearCoins);
+        let goal = goal.next_kind();
+        assert_eq!(goal.adjust_target(-10), PuzzleGoal::ExplodeTiles(1));
+        let goal = goal.next_kind();
+        assert_eq!(goal.adjust_target(5), PuzzleGoal::Score(15));
+        assert_eq!(goal.next_kind(), PuzzleGoal::ClearCoins);
+    }
+
+    #[test]
+    fn should_keep_the_fewest_moves_per_puzzle() {
+        let mut records = PuzzleRecords::default();
+        assert!(records.record("First blast", 3));
+        assert!(!records.record("First blast", 4));
+        assert!(records.record("First blast", 2));
+        assert!(records.record("Merge", 5));
+        assert_eq!(records.best_moves("First blast"), Some(2));
+        assert!(!records.is_solved("Other"));
+
+        let text = records.to_string();
+        assert_eq!(text, "AM4LGAM8-PUZZLES 1\n2 First blast\n5 Merge\n");
+        assert_eq!(text.parse::<PuzzleRecords>(), Ok(records));
+        assert!("2 First blast".parse::<PuzzleRecords>().is_err());
+    }
+}
@@ -0,0 +1,334 @@
+//! Recording of a run, as its seed and the ordered list of accepted moves.
+//!
+//! The file format is compact plain text :
+//!
+//! ```text
+//! AM4LGAM8-REPLAY 2
+//! 4x4 1234567890 a3b7c0d9e1f24658
+//! 0,0R 3,1U 2,2L 0,3D
+//! ```
+//!
+//! The first line is a version header, the second is the grid size, the seed and the fingerprint
+//! of the spawn rules the run was played with, and the rest are whitespace-separated moves : the
+//! source coordinates then the direction (U, D, L or R). Version 1 files have no fingerprint, they
+//! were all played with the default rules.
+
+use std::{fmt, str::FromStr};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    grid::{GridConfig, GridCoordinates},
+    moves::MoveDirection,
+    score::GameScore,
+    session::GameSession,
+    spawn::SpawnRules,
+};
+
+const HEADER: &str = "AM4LGAM8-REPLAY 2";
+const HEADER_V1: &str = "AM4LGAM8-REPLAY 1";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ReplayMove {
+    pub source_coords: GridCoordinates,
+    pub move_direction: MoveDirection,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Replay {
+    pub grid_config: GridConfig,
+    pub seed: u64,
+    /// See [`SpawnRules::fingerprint`]. The rules are not stored, only checked.
+    #[cfg_attr(feature = "serde", serde(default = "default_spawn_rules"))]
+    pub spawn_rules: u64,
+    pub moves: Vec<ReplayMove>,
+}
+
+fn default_spawn_rules() -> u64 {
+    SpawnRules::default().fingerprint()
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ReplayError {
+    Parse(String),
+    /// The move at this index is not valid on the replayed grid
+    InvalidMove(usize),
+    /// The run was played with other spawn rules than the ones given
+    OtherSpawnRules,
+}
+
+/// What a replay leads to, recomputed from scratch
+#[derive(Debug, PartialEq, Eq)]
+pub struct ReplayOutcome {
+    pub score: GameScore,
+    pub moves_played: u32,
+    pub game_over: bool,
+}
+
+impl Replay {
+    pub fn new(grid_config: GridConfig, seed: u64, spawn_rules: &SpawnRules) -> Self {
+        Replay {
+            grid_config,
+            seed,
+            spawn_rules: spawn_rules.fingerprint(),
+            moves: Vec::default(),
+        }
+    }
+
+    pub fn push(&mut self, source_coords: GridCoordinates, move_direction: MoveDirection) {
+        self.moves.push(ReplayMove {
+            source_coords,
+            move_direction,
+        });
+    }
+
+    /// Plays every move headlessly with the spawn rules the run was played with, and returns the
+    /// final score
+    pub fn verify(&self, spawn_rules: &SpawnRules) -> Result<ReplayOutcome, ReplayError> {
+        if spawn_rules.fingerprint() != self.spawn_rules {
+            return Err(ReplayError::OtherSpawnRules);
+        }
+        let mut session =
+            GameSession::with_spawn_rules(self.grid_config, self.seed, spawn_rules.clone());
+        for (idx, replay_move) in self.moves.iter().enumerate() {
+            session
+                .play(&replay_move.source_coords, replay_move.move_direction)
+                .ok_or(ReplayError::InvalidMove(idx))?;
+        }
+        Ok(ReplayOutcome {
+            score: session.score(),
+            moves_played: session.moves_played(),
+            game_over: session.is_over(),
+        })
+    }
+}
+
+fn direction_char(move_direction: MoveDirection) -> char {
+    match move_direction {
+        MoveDirection::Up => 'U',
+        MoveDirection::Down => 'D',
+        MoveDirection::Left => 'L',
+        MoveDirection::Right => 'R',
+    }
+}
+
+impl fmt::Display for Replay {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{HEADER}")?;
+        writeln!(
+            f,
+            "{}x{} {} {:016x}",
+            self.grid_config.width, self.grid_config.height, self.seed, self.spawn_rules
+        )?;
+        let moves: Vec<String> = self
+            .moves
+            .iter()
+            .map(
+                |ReplayMove {
+                     source_coords,
+                     move_direction,
+                 }| {
+                    format!(
+                        "{},{}{}",
+                        source_coords.x,
+                        source_coords.y,
+                        direction_char(*move_direction)
+                    )
+                },
+            )
+            .collect();
+        writeln!(f, "{}", moves.join(" "))
+    }
+}
+
+fn parse_move(token: &str) -> Result<ReplayMove, ReplayError> {
+    let invalid = || ReplayError::Parse(format!("Invalid move `{token}`"));
+    let (coords, direction) = token.split_at(token.len().checked_sub(1).ok_or_else(invalid)?);
+    let move_direction = match direction {
+        "U" => MoveDirection::Up,
+        "D" => MoveDirection::Down,
+        "L" => MoveDirection::Left,
+        "R" => MoveDirection::Right,
+        _ => return Err(invalid()),
+    };
+    let (x, y) = coords.split_once(',').ok_or_else(invalid)?;
+    Ok(ReplayMove {
+        source_coords: GridCoordinates {
+            x: x.parse().map_err(|_| invalid())?,
+            y: y.parse().map_err(|_| invalid())?,
+        },
+        move_direction,
+    })
+}
+
+impl FromStr for Replay {
+    type Err = ReplayError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut lines = s.lines();
+        let version_1 = match lines.next().map(str::trim) {
+            Some(HEADER) => false,
+            Some(HEADER_V1) => true,
+            _ => return Err(ReplayError::Parse("Not an AM4LGAM8 replay".to_string())),
+        };
+
+        let invalid_setup =
+            || ReplayError::Parse("Invalid grid size, seed or spawn rules".to_string());
+        let setup = lines.next().ok_or_else(invalid_setup)?;
+        let fields: Vec<&str> = setup.split_whitespace().collect();
+        let (size, seed, spawn_rules) = match fields[..] {
+            [size, seed] if version_1 => (size, seed, default_spawn_rules()),
+            [size, seed, spawn_rules] if !version_1 => (
+                size,
+                seed,
+                u64::from_str_radix(spawn_rules, 16).map_err(|_| invalid_setup())?,
+            ),
+            _ => return Err(invalid_setup()),
+        };
+        let (width, height) = size.split_once('x').ok_or_else(invalid_setup)?;
+        let grid_config = GridConfig {
+            width: width.parse().map_err(|_| invalid_setup())?,
+            height: height.parse().map_err(|_| invalid_setup())?,
+        };
+        if !grid_config.is_valid() {
+            return Err(ReplayError::Parse(format!(
+                "The grid can't be larger than {0}x{0}",
+                GridConfig::MAX_SIZE
+            )));
+        }
+        let seed = seed.parse().map_err(|_| invalid_setup())?;
+
+        let moves = lines
+            .flat_map(str::split_whitespace)
+            .map(parse_move)
+            .collect::<Result<_, _>>()?;
+
+        Ok(Replay {
+            grid_config,
+            seed,
+            spawn_rules,
+            moves,
+        })
+    }
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplayError::Parse(reason) => write!(f, "Failed to parse replay: {reason}"),
+            ReplayError::InvalidMove(idx) => write!(f, "Move #{} is not valid", idx + 1),
+            ReplayError::OtherSpawnRules => {
+                write!(f, "The run was played with other spawn rules")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {}
+
+#[cfg(test)]
+pub mod tests {
+    use crate::{
+        grid::{GridConfig, GridCoordinates},
+        moves::MoveDirection,
+        replay::{Replay, ReplayError},
+        session::GameSession,
+        spawn::{SpawnRules, SpawnTable},
+    };
+
+    /// Plays the first valid move found, in a fixed order, until the game is over
+    fn record_run(seed: u64, spawn_rules: &SpawnRules) -> (Replay, GameSession) {
+        let mut session =
+            GameSession::with_spawn_rules(GridConfig::default(), seed, spawn_rules.clone());
+        let mut replay = Replay::new(GridConfig::default(), seed, spawn_rules);
+        let directions = [
+            MoveDirection::Left,
+            MoveDirection::Down,
+            MoveDirection::Right,
+            MoveDirection::Up,
+        ];
+        while !session.is_over() {
+            let mut tiles: Vec<GridCoordinates> = session
+                .tile_grid()
+                .tiles()
+                .filter(|(_, tile_type)| tile_type.is_movable())
+                .map(|(coords, _)| coords.clone())
+                .collect();
+            tiles.sort();
+            let (coords, direction) = tiles
+                .iter()
+                .flat_map(|coords| directions.iter().map(move |dir| (coords.clone(), *dir)))
+                .find(|(coords, dir)| session.clone().play(coords, *dir).is_some())
+                .expect("The game is not over");
+            session.play(&coords, direction);
+            replay.push(coords, direction);
+        }
+        (replay, session)
+    }
+
+    #[test]
+    fn should_roundtrip_through_text() {
+        let (replay, _) = record_run(3, &SpawnRules::default());
+        let text = replay.to_string();
+        assert_eq!(text.parse::<Replay>(), Ok(replay));
+
+        // Older files were all played with the default rules
+        let replay: Replay = "AM4LGAM8-REPLAY 1\n4x4 5\n0,0R".parse().unwrap();
+        assert_eq!(replay.spawn_rules, SpawnRules::default().fingerprint());
+    }
+
+    #[test]
+    fn should_recompute_final_score() {
+        let (replay, session) = record_run(11, &SpawnRules::default());
+        let outcome = replay
+            .verify(&SpawnRules::default())
+            .expect("Replay should be valid");
+        assert_eq!(outcome.score, session.score());
+        assert_eq!(outcome.moves_played as usize, replay.moves.len());
+        assert!(outcome.game_over);
+    }
+
+    #[test]
+    fn should_verify_with_the_spawn_rules_of_the_run() {
+        let bombs_only: SpawnRules = "bomb=1,wall=1".parse::<SpawnTable>().unwrap().into();
+        let (replay, session) = record_run(11, &bombs_only);
+        let replay: Replay = replay.to_string().parse().unwrap();
+        assert_eq!(
+            replay.verify(&SpawnRules::default()),
+            Err(ReplayError::OtherSpawnRules)
+        );
+        let outcome = replay.verify(&bombs_only).expect("Replay should be valid");
+        assert_eq!(outcome.score, session.score());
+    }
+
+    #[test]
+    fn should_reject_invalid_moves() {
+        let mut replay = Replay::new(GridConfig::default(), 5, &SpawnRules::default());
+        replay.push(GridCoordinates { x: -1, y: -1 }, MoveDirection::Up);
+        assert_eq!(
+            replay.verify(&SpawnRules::default()),
+            Err(ReplayError::InvalidMove(0))
+        );
+
+        assert!(matches!(
+            "AM4LGAM8-REPLAY 1\n4x4 5\n0,0X".parse::<Replay>(),
+            Err(ReplayError::Parse(_))
+        ));
+        assert!(matches!(
+            "something else".parse::<Replay>(),
+            Err(ReplayError::Parse(_))
+        ));
+        // A crafted file can't ask for a huge grid
+        assert!(matches!(
+            "AM4LGAM8-REPLAY 2\n100000x100000 5 0\n".parse::<Replay>(),
+            Err(ReplayError::Parse(_))
+        ));
+        assert!(matches!(
+            "AM4LGAM8-REPLAY 2\n0x4 5 0\n".parse::<Replay>(),
+            Err(ReplayError::Parse(_))
+        ));
+    }
+}
@@ -0,0 +1,18 @@
+//! Seeded randomness, so that a run can be replayed exactly from its seed.
+//!
+//! ChaCha is used rather than `StdRng` because its output is guaranteed to be the same on every
+//! platform and across `rand` releases.
+
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+pub type GameRng = ChaCha8Rng;
+
+pub fn rng_from_seed(seed: u64) -> GameRng {
+    GameRng::seed_from_u64(seed)
+}
+
+/// Picks a seed for a run that was not given one
+pub fn random_seed() -> u64 {
+    rand::random()
+}
@@ -0,0 +1,225 @@
+//! Points scored by each turn : what the chain reaction of the move is worth, multiplied by the
+//! bonuses it earned.
+
+use std::fmt;
+
+#[cfg(feature = "bevy")]
+use bevy_ecs::prelude::Resource;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{grid::TileGrid, moves::ChainReaction, tile::TileType};
+
+// Coins exploded in a single turn to earn the `ManyCoins` bonus
+const MANY_COINS: usize = 5;
+// Coins of this tier (16) and above earn the `HighTier` bonus
+const HIGH_TIER: u8 = 4;
+// The streak bonus grows by 1 each turn, up to this
+const MAX_STREAK_BONUS: i32 = 3;
+const CLEAR_BOARD_BONUS: i32 = 2;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "bevy", derive(Resource))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct GameScore {
+    points: i64,
+    /// Turns in a row that set off an explosion, up to the last one
+    streak: u32,
+}
+
+/// A reason for a turn to score more
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bonus {
+    ManyCoins,
+    HighTier,
+    /// Turns in a row with an explosion, 2 or more
+    Streak(u32),
+    /// Nothing but walls left inside the grid
+    ClearBoard,
+}
+
+impl Bonus {
+    /// Added to the multiplier of the turn
+    pub fn extra_multiplier(&self) -> i32 {
+        match self {
+            Bonus::ManyCoins | Bonus::HighTier => 1,
+            Bonus::Streak(turns) => (*turns as i32 - 1).min(MAX_STREAK_BONUS),
+            Bonus::ClearBoard => CLEAR_BOARD_BONUS,
+        }
+    }
+}
+
+impl fmt::Display for Bonus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Bonus::ManyCoins => write!(f, "HORDE"),
+            Bonus::HighTier => write!(f, "RICH"),
+            Bonus::Streak(turns) => write!(f, "STREAK {turns}"),
+            Bonus::ClearBoard => write!(f, "CLEAR"),
+        }
+    }
+}
+
+/// What a turn scored, and why
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ScoreBreakdown {
+    /// Worth of the chain reaction, its own multipliers included
+    pub points: i64,
+    pub bonuses: Vec<Bonus>,
+}
+
+impl ScoreBreakdown {
+    pub fn multiplier(&self) -> i32 {
+        1 + self
+            .bonuses
+            .iter()
+            .map(Bonus::extra_multiplier)
+            .sum::<i32>()
+    }
+
+    pub fn total(&self) -> i64 {
+        self.points * i64::from(self.multiplier())
+    }
+}
+
+impl GameScore {
+    /// The score stops at its maximum, however long the run goes on
+    pub fn add(&mut self, points: i64) {
+        self.points = self.points.saturating_add(points);
+    }
+
+    /// Scores the chain reaction of a move, once applied to the grid (and before the next spawn).
+    /// A move without explosions scores nothing and ends the streak.
+    pub fn score_turn(
+        &mut self,
+        chain_reaction: &ChainReaction,
+        tile_grid: &TileGrid,
+    ) -> ScoreBreakdown {
+        if chain_reaction.waves.is_empty() {
+            self.streak = 0;
+            return ScoreBreakdown::default();
+        }
+        self.streak += 1;
+
+        let coins: Vec<u8> = chain_reaction
+            .waves
+            .iter()
+            .flat_map(|wave| &wave.destroyed)
+            .filter_map(|(_, tile_type)| match tile_type {
+                TileType::Coin(value) => Some(value.tier()),
+                _ => None,
+            })
+            .collect();
+        let mut bonuses = Vec::new();
+        if coins.len() >= MANY_COINS {
+            bonuses.push(Bonus::ManyCoins);
+        }
+        if coins.iter().any(|tier| *tier >= HIGH_TIER) {
+            bonuses.push(Bonus::HighTier);
+        }
+        if self.streak >= 2 {
+            bonuses.push(Bonus::Streak(self.streak));
+        }
+        let cleared = tile_grid
+            .tiles()
+            .all(|(coords, tile_type)| tile_type.is_wall() || !tile_grid.is_inside(coords));
+        if cleared {
+            bonuses.push(Bonus::ClearBoard);
+        }
+
+        let breakdown = ScoreBreakdown {
+            points: chain_reaction.points(),
+            bonuses,
+        };
+        self.add(breakdown.total());
+        breakdown
+    }
+
+    pub fn reset(&mut self) {
+        *self = GameScore::default();
+    }
+
+    pub fn get(&self) -> i64 {
+        self.points
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use crate::{
+        grid::{GridCoordinates, TileGrid},
+        moves::{ChainReaction, ExplosionEvent},
+        score::{Bonus, GameScore},
+        tile::{CoinValue, TileType},
+    };
+
+    fn explode(tile_grid: &mut TileGrid, x: i32, y: i32) -> ChainReaction {
+        tile_grid.handle_explosion_event(&ExplosionEvent {
+            target: GridCoordinates { x, y },
+            radius: 1,
+        })
+    }
+
+    #[test]
+    fn should_multiply_the_points_of_the_turn_by_its_bonuses() {
+        let mut tile_grid = TileGrid::default();
+        tile_grid.insert(GridCoordinates { x: 0, y: 0 }, TileType::Bomb);
+        let sixteen = CoinValue::from_tier(4).unwrap();
+        tile_grid.insert(GridCoordinates { x: 1, y: 0 }, TileType::Coin(sixteen));
+        tile_grid.insert(GridCoordinates { x: 3, y: 3 }, TileType::Bomb);
+
+        let mut score = GameScore::default();
+        let chain_reaction = explode(&mut tile_grid, 0, 0);
+        let breakdown = score.score_turn(&chain_reaction, &tile_grid);
+        assert_eq!(breakdown.points, 1 + 16);
+        assert_eq!(breakdown.bonuses, [Bonus::HighTier]);
+        assert_eq!((breakdown.total(), score.get()), (17 * 2, 34));
+
+        // The second explosion in a row clears the board
+        let chain_reaction = explode(&mut tile_grid, 3, 3);
+        let breakdown = score.score_turn(&chain_reaction, &tile_grid);
+        assert_eq!(breakdown.bonuses, [Bonus::Streak(2), Bonus::ClearBoard]);
+        assert_eq!(breakdown.total(), 4);
+        assert_eq!(score.get(), 34 + 4);
+    }
+
+    #[test]
+    fn should_end_the_streak_on_a_turn_without_explosions() {
+        let mut tile_grid = TileGrid::default();
+        tile_grid.insert(GridCoordinates { x: 3, y: 3 }, TileType::Wall);
+        let mut score = GameScore::default();
+        for (x, y) in [(0, 0), (2, 0)] {
+            tile_grid.insert(GridCoordinates { x, y }, TileType::Bomb);
+            let chain_reaction = explode(&mut tile_grid, x, y);
+            score.score_turn(&chain_reaction, &tile_grid);
+        }
+
+        let breakdown = score.score_turn(&ChainReaction::default(), &tile_grid);
+        assert_eq!(breakdown.total(), 0);
+
+        tile_grid.insert(GridCoordinates { x: 0, y: 0 }, TileType::Bomb);
+        let chain_reaction = explode(&mut tile_grid, 0, 0);
+        let breakdown = score.score_turn(&chain_reaction, &tile_grid);
+        assert_eq!(breakdown.bonuses, [Bonus::ClearBoard]);
+    }
+
+    #[test]
+    fn should_score_the_highest_coins_without_overflowing() {
+        let mut tile_grid = TileGrid::default();
+        let last = CoinValue::from_tier(CoinValue::MAX_TIER).unwrap();
+        tile_grid.insert(GridCoordinates { x: 0, y: 0 }, TileType::Bomb);
+        tile_grid.insert(GridCoordinates { x: 1, y: 0 }, TileType::Coin(last));
+        tile_grid.insert(GridCoordinates { x: 0, y: 1 }, TileType::Coin(last));
+
+        let mut score = GameScore::default();
+        let chain_reaction = explode(&mut tile_grid, 0, 0);
+        let breakdown = score.score_turn(&chain_reaction, &tile_grid);
+        // Above what an i32 holds
+        assert_eq!(breakdown.points, 1 + 2 * (1 << CoinValue::MAX_TIER));
+        assert_eq!(breakdown.bonuses, [Bonus::HighTier, Bonus::ClearBoard]);
+        assert_eq!(score.get(), breakdown.points * 4);
+
+        score.add(i64::MAX);
+        assert_eq!(score.get(), i64::MAX);
+    }
+}
@@ -0,0 +1,203 @@
+//! A whole run played headlessly, following the same turn structure as the game
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    grid::{GridConfig, GridCoordinates, TileGrid},
+    moves::{MoveDirection, ValidEvent, ValidatedEventQueue},
+    score::GameScore,
+    spawn::SpawnRules,
+};
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct GameSession {
+    tile_grid: TileGrid,
+    score: GameScore,
+    moves_played: u32,
+    /// Without rules, the grid keeps spawning from the table it has
+    #[cfg_attr(feature = "serde", serde(skip))]
+    spawn_rules: Option<SpawnRules>,
+}
+
+impl GameSession {
+    /// Sets up the walls and the first tile, like entering the playing screen does
+    pub fn new(config: GridConfig, seed: u64) -> Self {
+        GameSession::with_spawn_rules(config, seed, SpawnRules::default())
+    }
+
+    pub fn with_spawn_rules(config: GridConfig, seed: u64, spawn_rules: SpawnRules) -> Self {
+        let mut tile_grid = TileGrid::new(config, seed);
+        tile_grid.setup_default_grid();
+        tile_grid.spawn_first_tile();
+        let mut session = GameSession {
+            tile_grid,
+            score: GameScore::default(),
+            moves_played: 0,
+            spawn_rules: Some(spawn_rules),
+        };
+        session.update_spawn_table();
+        session.spawn_while_stuck();
+        session
+    }
+
+    /// Picks a run back up from the state of its grid
+    pub fn from_grid(tile_grid: TileGrid, score: GameScore) -> Self {
+        GameSession {
+            tile_grid,
+            score,
+            moves_played: 0,
+            spawn_rules: None,
+        }
+    }
+
+    pub fn tile_grid(&self) -> &TileGrid {
+        &self.tile_grid
+    }
+
+    pub fn score(&self) -> GameScore {
+        self.score
+    }
+
+    pub fn moves_played(&self) -> u32 {
+        self.moves_played
+    }
+
+    /// Plays a whole turn : the move itself, then the spawn of a new tile.
+    /// Returns the events of the move, or `None` if the move is not valid.
+    pub fn play(
+        &mut self,
+        source_coords: &GridCoordinates,
+        move_direction: MoveDirection,
+    ) -> Option<Vec<ValidEvent>> {
+        let candidate_coords =
+            source_coords.candidate_coords_for_dir(move_direction, self.tile_grid.config());
+        match ValidatedEventQueue::validate_move(&self.tile_grid, candidate_coords, move_direction)
+        {
+            ValidatedEventQueue::InvalidMove => None,
+            ValidatedEventQueue::ValidMove(events) => {
+                let chain_reaction = self.tile_grid.apply_events(&events);
+                self.score.score_turn(&chain_reaction, &self.tile_grid);
+                self.moves_played += 1;
+                self.update_spawn_table();
+                self.tile_grid.try_spawn_new_tile();
+                self.spawn_while_stuck();
+                Some(events)
+            }
+        }
+    }
+
+    /// Always 1 without spawn rules
+    pub fn level(&self) -> usize {
+        self.spawn_rules
+            .as_ref()
+            .map(|rules| rules.level_at(self.moves_played, self.score.get()))
+            .unwrap_or(1)
+    }
+
+    pub fn is_over(&self) -> bool {
+        !self.tile_grid.has_any_possible_moves()
+    }
+
+    // Same as `update_spawn_table` in the game. If no tile could spawn at this point, the last
+    // table is kept.
+    fn update_spawn_table(&mut self) {
+        if let Some(Ok(table)) = self
+            .spawn_rules
+            .as_ref()
+            .map(|rules| rules.table_at(self.moves_played, self.score.get()))
+        {
+            self.tile_grid.set_spawn_table(table);
+        }
+    }
+
+    // Same as `check_for_game_over` : when no move is possible, tiles keep spawning until one
+    // is, or the grid is full
+    fn spawn_while_stuck(&mut self) {
+        while !self.tile_grid.has_any_possible_moves() && self.tile_grid.has_unused_coordinates() {
+            self.tile_grid.try_spawn_new_tile();
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use crate::{
+        grid::{GridConfig, GridCoordinates, TileGrid},
+        moves::{MoveDirection, ValidEvent},
+        score::GameScore,
+        session::GameSession,
+        tile::{CoinValue, TileType},
+    };
+
+    #[test]
+    fn should_start_with_a_single_coin() {
+        let session = GameSession::new(GridConfig::default(), 7);
+        let coins: Vec<_> = session
+            .tile_grid()
+            .tiles()
+            .filter(|(_, tile_type)| **tile_type != TileType::Wall)
+            .collect();
+        assert_eq!(coins.len(), 1);
+        assert_eq!(coins[0].1, &TileType::Coin(CoinValue::ONE));
+        assert!(!session.is_over());
+    }
+
+    #[test]
+    fn should_only_count_valid_moves() {
+        let mut session = GameSession::new(GridConfig::default(), 7);
+        assert!(session
+            .play(&GridCoordinates { x: -1, y: 0 }, MoveDirection::Right)
+            .is_none());
+        assert_eq!(session.moves_played(), 0);
+
+        let (coords, _) = session
+            .tile_grid()
+            .tiles()
+            .find(|(_, tile_type)| **tile_type != TileType::Wall)
+            .map(|(coords, tile_type)| (coords.clone(), *tile_type))
+            .expect("No coin on the grid");
+        let direction = if coords.x == 0 {
+            MoveDirection::Right
+        } else {
+            MoveDirection::Left
+        };
+        assert!(session.play(&coords, direction).is_some());
+        assert_eq!(session.moves_played(), 1);
+    }
+
+    #[test]
+    fn should_not_spawn_after_solidifying_on_a_full_grid() {
+        let mut tile_grid = TileGrid::new(GridConfig::default(), 7);
+        tile_grid.setup_default_grid();
+        // Coins that can't merge with their neighbours, and two blocks side by side
+        for x in 0..4 {
+            for y in 0..4 {
+                let value = if (x + y) % 2 == 0 {
+                    CoinValue::ONE
+                } else {
+                    CoinValue::TWO
+                };
+                tile_grid.insert(GridCoordinates { x, y }, TileType::Coin(value));
+            }
+        }
+        tile_grid.insert(GridCoordinates { x: 0, y: 0 }, TileType::Block);
+        tile_grid.insert(GridCoordinates { x: 1, y: 0 }, TileType::Block);
+        assert!(!tile_grid.has_unused_coordinates());
+        let rng_position = tile_grid.rng_position();
+
+        let mut session = GameSession::from_grid(tile_grid, GameScore::default());
+        let events = session
+            .play(&GridCoordinates { x: 1, y: 0 }, MoveDirection::Left)
+            .expect("The blocks can solidify");
+        assert!(matches!(events[..], [ValidEvent::Solidify(_)]));
+        // No cell was freed, so no tile was picked or placed
+        assert_eq!(session.tile_grid().rng_position(), rng_position);
+        assert_eq!(
+            session.tile_grid().get(&GridCoordinates { x: 1, y: 0 }),
+            Some(&TileType::Wall)
+        );
+        assert!(session.is_over());
+    }
+}
@@ -0,0 +1,258 @@
+//! Proves that a puzzle can be solved, and finds its shortest solution.
+//!
+//! The tiles a puzzle spawns, and where they land, only depend on the moves played, so each move
+//! leads to a single grid. The solver plays every valid move from every grid it reaches, breadth
+//! first, so the first solution it finds is one of the shortest. A grid reached by two different
+//! orders of moves is only looked at once.
+
+use std::collections::HashSet;
+
+use crate::{
+    ai::{valid_moves, AiMove},
+    grid::{GridCoordinates, TileGrid},
+    puzzle::{Puzzle, PuzzleOutcome, PuzzleRun},
+    score::GameScore,
+    tile::TileType,
+};
+
+/// Enough for the shipped puzzles many times over, while staying under a second
+pub const DEFAULT_MAX_STATES: usize = 200_000;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SolverOutcome {
+    /// One of the shortest solutions, already solved puzzles needing no move
+    Solved(Vec<AiMove>),
+    /// No sequence of moves reaches the goal within the moves allowed
+    Unsolvable,
+    /// Gave up after looking at this many grids
+    GaveUp(usize),
+}
+
+/// A puzzle being solved, as it stands after some moves
+#[derive(Clone)]
+struct State {
+    tile_grid: TileGrid,
+    score: GameScore,
+    run: PuzzleRun,
+}
+
+/// What tells two states apart, in a hashable form
+#[derive(PartialEq, Eq, Hash)]
+struct StateKey {
+    tiles: Vec<(GridCoordinates, TileType)>,
+    score: GameScore,
+    tiles_exploded: u32,
+    moves_played: u32,
+    rng_position: u128,
+}
+
+impl State {
+    fn key(&self) -> StateKey {
+        let mut tiles: Vec<_> = self
+            .tile_grid
+            .tiles()
+            .filter(|(coords, _)| self.tile_grid.is_inside(coords))
+            .map(|(coords, tile_type)| (coords.clone(), *tile_type))
+            .collect();
+        tiles.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+        StateKey {
+            tiles,
+            score: self.score,
+            tiles_exploded: self.run.tiles_exploded(),
+            moves_played: self.run.moves_played(),
+            rng_position: self.tile_grid.rng_position(),
+        }
+    }
+
+    fn outcome(&self) -> PuzzleOutcome {
+        self.run.outcome(&self.tile_grid, &self.score)
+    }
+}
+
+/// Looks for the shortest solution, giving up once more than `max_states` grids were reached
+pub fn solve(puzzle: &Puzzle, max_states: usize) -> SolverOutcome {
+    let start = State {
+        tile_grid: puzzle.setup_grid(),
+        score: GameScore::default(),
+        run: PuzzleRun::new(puzzle.clone()),
+    };
+    match start.outcome() {
+        PuzzleOutcome::Solved => return SolverOutcome::Solved(Vec::new()),
+        PuzzleOutcome::Failed => return SolverOutcome::Unsolvable,
+        PuzzleOutcome::Playing => {}
+    }
+
+    let mut visited = HashSet::from([start.key()]);
+    // Each move played, with the index of the move it followed, to walk the solution back
+    let mut steps: Vec<(Option<usize>, AiMove)> = Vec::new();
+    let mut frontier = vec![(start, None)];
+    while !frontier.is_empty() {
+        let mut next_frontier = Vec::new();
+        for (state, step) in frontier {
+            for ai_move in valid_moves(&state.tile_grid) {
+                let mut next = state.clone();
+                next.run
+                    .play(
+                        &mut next.tile_grid,
+                        &mut next.score,
+                        &ai_move.source_coords,
+                        ai_move.move_direction,
+                    )
+                    .expect("The move was validated");
+                if !visited.insert(next.key()) {
+                    continue;
+                }
+                steps.push((step, ai_move));
+                let next_step = steps.len() - 1;
+                match next.outcome() {
+                    PuzzleOutcome::Solved => {
+                        return SolverOutcome::Solved(solution(&steps, next_step))
+                    }
+                    PuzzleOutcome::Failed => continue,
+                    PuzzleOutcome::Playing => {}
+                }
+                if visited.len() > max_states {
+                    return SolverOutcome::GaveUp(visited.len());
+                }
+                next_frontier.push((next, Some(next_step)));
+            }
+        }
+        frontier = next_frontier;
+    }
+    SolverOutcome::Unsolvable
+}
+
+/// The moves that led to the last step, in order
+fn solution(steps: &[(Option<usize>, AiMove)], last_step: usize) -> Vec<AiMove> {
+    let mut moves = Vec::new();
+    let mut step = Some(last_step);
+    while let Some(index) = step {
+        let (previous, ai_move) = &steps[index];
+        moves.push(ai_move.clone());
+        step = *previous;
+    }
+    moves.reverse();
+    moves
+}
+
+#[cfg(test)]
+pub mod tests {
+    use crate::{
+        puzzle::{Puzzle, PuzzleGoal, PuzzleOutcome, PuzzleRun},
+        score::GameScore,
+        solver::{solve, SolverOutcome, DEFAULT_MAX_STATES},
+    };
+
+    fn puzzle(rows: &[&str], moves: u32, goal: PuzzleGoal) -> Puzzle {
+        let (config, tiles) = Puzzle::parse_rows(rows).unwrap();
+        Puzzle {
+            tiles,
+            moves,
+            goal,
+            ..Puzzle::new("Test", config)
+        }
+    }
+
+    /// Plays the solution from the start, it has to solve the puzzle
+    fn assert_solves(puzzle: &Puzzle, outcome: &SolverOutcome) -> usize {
+        let SolverOutcome::Solved(moves) = outcome else {
+            panic!("{} is not solved: {outcome:?}", puzzle.name);
+        };
+        let mut tile_grid = puzzle.setup_grid();
+        let mut score = GameScore::default();
+        let mut run = PuzzleRun::new(puzzle.clone());
+        for ai_move in moves {
+            run.play(
+                &mut tile_grid,
+                &mut score,
+                &ai_move.source_coords,
+                ai_move.move_direction,
+            )
+            .expect("The solution has an invalid move");
+        }
+        assert_eq!(run.outcome(&tile_grid, &score), PuzzleOutcome::Solved);
+        moves.len()
+    }
+
+    #[test]
+    fn should_find_the_shortest_solution() {
+        // Takes 2 moves, there are 3 to spare
+        let chain_reaction = puzzle(
+            &[
+                ".    one  .    .    .",
+                ".    bomb two  .    .",
+                ".    bomb .    .    .",
+                ".    bomb one  .    .",
+                "bomb .    .    .    .",
+            ],
+            5,
+            PuzzleGoal::ExplodeTiles(7),
+        );
+        let outcome = solve(&chain_reaction, DEFAULT_MAX_STATES);
+        assert_eq!(assert_solves(&chain_reaction, &outcome), 2);
+
+        let already_solved = puzzle(&[".    .", "bomb ."], 1, PuzzleGoal::ClearCoins);
+        assert_eq!(
+            solve(&already_solved, 10),
+            SolverOutcome::Solved(Vec::new())
+        );
+    }
+
+    #[test]
+    fn should_prove_a_puzzle_unsolvable() {
+        let first_blast = puzzle(
+            &[
+                ".    .    .    .",
+                ".    one  two  .",
+                ".    bomb bomb .",
+                ".    .    .    .",
+            ],
+            3,
+            PuzzleGoal::Score(1000),
+        );
+        assert_eq!(
+            solve(&first_blast, DEFAULT_MAX_STATES),
+            SolverOutcome::Unsolvable
+        );
+
+        // No move from the start
+        let stuck = puzzle(&["wall one", "one  wall"], 1, PuzzleGoal::ClearCoins);
+        assert_eq!(solve(&stuck, DEFAULT_MAX_STATES), SolverOutcome::Unsolvable);
+    }
+
+    #[test]
+    fn should_give_up_past_the_limit() {
+        let open = puzzle(
+            &[
+                "one  .    .    two",
+                ".    bomb .    .",
+                ".    .    bomb .",
+                "two  .    .    one",
+            ],
+            8,
+            PuzzleGoal::Score(1000),
+        );
+        assert!(matches!(solve(&open, 10), SolverOutcome::GaveUp(_)));
+    }
+
+    /// Every level file is checked, not only the ones the game lists
+    #[cfg(feature = "serde")]
+    #[test]
+    fn every_level_file_should_be_solvable() {
+        let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/../assets/puzzles");
+        let mut checked = 0;
+        for entry in std::fs::read_dir(dir).expect("No puzzles directory") {
+            let path = entry.unwrap().path();
+            if !path.to_string_lossy().ends_with(".puzzle.ron") {
+                continue;
+            }
+            let text = std::fs::read_to_string(&path).unwrap();
+            let puzzle: Puzzle =
+                ron::from_str(&text).unwrap_or_else(|err| panic!("{}: {err}", path.display()));
+            let moves = assert_solves(&puzzle, &solve(&puzzle, DEFAULT_MAX_STATES));
+            assert!(moves as u32 <= puzzle.moves);
+            checked += 1;
+        }
+        assert!(checked > 0, "No level file in {dir}");
+    }
+}
@@ -0,0 +1,492 @@
+//! Which tiles spawn after each move, and how often.
+//!
+//! A table can be written as text, one `tile=weight` pair per tile, e.g. the default table is
+//! `one=41,two=20,bomb=34,wall=6`. Coins are named after their value : `one`, `two`, `four` and
+//! `eight`, and then `16`, `32`... Big bombs are `bigbomb`, and blocks `block`.
+//!
+//! The game reads its table from [`SpawnRules`], where weights can also ramp up or down as the run
+//! goes on, e.g. to make walls more and more frequent. The rules also split the run into levels,
+//! each one adding to the weights of some tiles.
+
+use std::{
+    fmt::{self, Write},
+    str::FromStr,
+};
+
+use rand::Rng;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::tile::{CoinValue, TileType};
+
+/// Tiles that can spawn, with their relative chance of doing so
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpawnTable {
+    weights: Vec<(TileType, u32)>,
+}
+
+impl Default for SpawnTable {
+    /// The odds picked during the jam
+    fn default() -> Self {
+        SpawnTable {
+            weights: vec![
+                (TileType::Coin(CoinValue::ONE), 41),
+                (TileType::Coin(CoinValue::TWO), 20),
+                (TileType::Bomb, 34),
+                (TileType::Wall, 6),
+            ],
+        }
+    }
+}
+
+impl SpawnTable {
+    /// Fails if no tile can spawn at all, or if the weights add up to more than a `u32`
+    pub fn new(weights: Vec<(TileType, u32)>) -> Result<Self, String> {
+        if weights.iter().all(|(_, weight)| *weight == 0) {
+            return Err("The spawn table needs at least one weight above 0".to_string());
+        }
+        let total = weights
+            .iter()
+            .try_fold(0u32, |total, (_, weight)| total.checked_add(*weight));
+        if total.is_none() {
+            return Err(format!(
+                "The weights can't add up to more than {}",
+                u32::MAX
+            ));
+        }
+        Ok(SpawnTable { weights })
+    }
+
+    pub fn weights(&self) -> &[(TileType, u32)] {
+        &self.weights
+    }
+
+    /// Can't overflow, `new` checked it
+    pub fn total_weight(&self) -> u32 {
+        self.weights.iter().map(|(_, weight)| weight).sum()
+    }
+
+    /// Always takes a single number from the RNG, so that a seed spawns the same tiles for the
+    /// same table
+    pub fn pick<R: Rng>(&self, rng: &mut R) -> TileType {
+        let mut roll = rng.gen_range(0..self.total_weight());
+        for (tile_type, weight) in &self.weights {
+            if roll < *weight {
+                return *tile_type;
+            }
+            roll -= weight;
+        }
+        unreachable!("The roll is below the total weight")
+    }
+}
+
+/// The name of the tile in spawn tables and puzzles. Coins above eight are named by their value,
+/// e.g. `16`.
+pub fn tile_name(tile_type: &TileType) -> String {
+    match tile_type {
+        TileType::Coin(CoinValue::ONE) => "one".to_string(),
+        TileType::Coin(CoinValue::TWO) => "two".to_string(),
+        TileType::Coin(CoinValue::FOUR) => "four".to_string(),
+        TileType::Coin(CoinValue::EIGHT) => "eight".to_string(),
+        TileType::Coin(value) => value.points().to_string(),
+        TileType::Bomb => "bomb".to_string(),
+        TileType::BigBomb => "bigbomb".to_string(),
+        TileType::Block => "block".to_string(),
+        TileType::Wall => "wall".to_string(),
+        TileType::CrackedWall(hit_points) => format!("cracked{hit_points}"),
+    }
+}
+
+pub(crate) fn parse_tile(name: &str) -> Result<TileType, String> {
+    match name {
+        "one" => Ok(TileType::Coin(CoinValue::ONE)),
+        "two" => Ok(TileType::Coin(CoinValue::TWO)),
+        "four" => Ok(TileType::Coin(CoinValue::FOUR)),
+        "eight" => Ok(TileType::Coin(CoinValue::EIGHT)),
+        "bomb" => Ok(TileType::Bomb),
+        "bigbomb" => Ok(TileType::BigBomb),
+        "block" => Ok(TileType::Block),
+        _ if name.starts_with("cracked") => name["cracked".len()..]
+            .parse()
+            .map(TileType::CrackedWall)
+            .map_err(|_| format!("Unknown tile `{name}`")),
+        "wall" => Ok(TileType::Wall),
+        _ => match name.parse::<i32>() {
+            Ok(points) => CoinValue::try_from(points).map(TileType::Coin),
+            Err(_) => Err(format!("Unknown tile `{name}`")),
+        },
+    }
+}
+
+impl fmt::Display for SpawnTable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let pairs: Vec<String> = self
+            .weights
+            .iter()
+            .map(|(tile_type, weight)| format!("{}={weight}", tile_name(tile_type)))
+            .collect();
+        write!(f, "{}", pairs.join(","))
+    }
+}
+
+impl FromStr for SpawnTable {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let weights = s
+            .split(',')
+            .map(|pair| {
+                let (name, weight) = pair
+                    .trim()
+                    .split_once('=')
+                    .ok_or_else(|| format!("Expected `tile=weight`, got `{pair}`"))?;
+                let weight = weight
+                    .parse()
+                    .map_err(|_| format!("Invalid weight `{weight}`"))?;
+                Ok((parse_tile(name)?, weight))
+            })
+            .collect::<Result<_, String>>()?;
+        SpawnTable::new(weights)
+    }
+}
+
+/// What a ramp follows
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Progress {
+    /// Moves played
+    Turns,
+    Score,
+}
+
+/// Changes a weight by `step` every `every` turns or points, until it reaches `limit`
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Ramp {
+    pub by: Progress,
+    pub every: u32,
+    pub step: i32,
+    pub limit: u32,
+}
+
+impl Ramp {
+    fn apply(&self, weight: u32, turn: u32, score: i64) -> u32 {
+        let progress = match self.by {
+            Progress::Turns => i64::from(turn),
+            Progress::Score => score.max(0),
+        };
+        let ramped =
+            i64::from(weight) + progress / i64::from(self.every.max(1)) * i64::from(self.step);
+        let limit = i64::from(self.limit);
+        let ramped = if self.step >= 0 {
+            ramped.min(limit)
+        } else {
+            ramped.max(limit)
+        };
+        ramped.max(0) as u32
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SpawnRule {
+    pub tile: TileType,
+    /// Weight at the start of the run
+    pub weight: u32,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub ramp: Option<Ramp>,
+}
+
+/// A stage of the run, reached after `turn` moves or `score` points, whichever comes first
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Level {
+    pub turn: u32,
+    pub score: i64,
+    /// Added to the weights of these tiles, for as long as the level lasts
+    pub extra: Vec<(TileType, i32)>,
+}
+
+impl Level {
+    fn is_reached(&self, turn: u32, score: i64) -> bool {
+        turn >= self.turn || score >= self.score
+    }
+}
+
+/// The spawn table of a run, as it evolves with the turn and the score
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SpawnRules {
+    pub tiles: Vec<SpawnRule>,
+    /// The levels after the first one, in order
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub levels: Vec<Level>,
+}
+
+impl Default for SpawnRules {
+    /// The jam odds, with more and more walls at each level. Big bombs and blocks only spawn from
+    /// level 2.
+    fn default() -> Self {
+        let level = |turn, score, walls, big_bombs, blocks| Level {
+            turn,
+            score,
+            extra: vec![
+                (TileType::Wall, walls),
+                (TileType::BigBomb, big_bombs),
+                (TileType::Block, blocks),
+            ],
+        };
+        let mut rules: SpawnRules = SpawnTable::default().into();
+        // A weight of 0 doesn't change the rolls of the first level
+        for tile in [TileType::BigBomb, TileType::Block] {
+            rules.tiles.push(SpawnRule {
+                tile,
+                weight: 0,
+                ramp: None,
+            });
+        }
+        SpawnRules {
+            levels: vec![
+                level(30, 60, 2, 2, 3),
+                level(60, 120, 4, 3, 4),
+                level(100, 200, 7, 4, 5),
+                level(150, 300, 10, 5, 5),
+            ],
+            ..rules
+        }
+    }
+}
+
+impl From<SpawnTable> for SpawnRules {
+    fn from(table: SpawnTable) -> Self {
+        SpawnRules {
+            tiles: table
+                .weights
+                .into_iter()
+                .map(|(tile, weight)| SpawnRule {
+                    tile,
+                    weight,
+                    ramp: None,
+                })
+                .collect(),
+            levels: Vec::new(),
+        }
+    }
+}
+
+impl SpawnRules {
+    /// Rules read from a file may not make sense
+    pub fn validate(&self) -> Result<(), String> {
+        if self
+            .tiles
+            .iter()
+            .any(|rule| rule.ramp.as_ref().is_some_and(|ramp| ramp.every == 0))
+        {
+            return Err("Ramps must change every 1 turn or point at least".to_string());
+        }
+        let in_order = self
+            .levels
+            .windows(2)
+            .all(|pair| pair[0].turn <= pair[1].turn && pair[0].score <= pair[1].score);
+        if !in_order {
+            return Err("Levels must be in order of turn and score".to_string());
+        }
+        self.table_at(0, 0).map(|_| ())
+    }
+
+    /// Tells two sets of rules apart, e.g. to check that a replay is verified with the rules it
+    /// was recorded with. It only depends on the rules, whatever the platform or the release.
+    pub fn fingerprint(&self) -> u64 {
+        let mut text = String::new();
+        for rule in &self.tiles {
+            let _ = write!(text, "{}={}", tile_name(&rule.tile), rule.weight);
+            if let Some(ramp) = &rule.ramp {
+                let by = match ramp.by {
+                    Progress::Turns => "turns",
+                    Progress::Score => "score",
+                };
+                let _ = write!(text, "~{by}/{}{:+}<{}", ramp.every, ramp.step, ramp.limit);
+            }
+            text.push(';');
+        }
+        for level in &self.levels {
+            let _ = write!(text, "@{}/{}", level.turn, level.score);
+            for (tile, extra) in &level.extra {
+                let _ = write!(text, ",{}{extra:+}", tile_name(tile));
+            }
+            text.push(';');
+        }
+        // FNV-1a
+        text.bytes().fold(0xCBF2_9CE4_8422_2325, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01B3)
+        })
+    }
+
+    /// The level of the run once `turn` moves were played and `score` points were scored, from 1
+    pub fn level_at(&self, turn: u32, score: i64) -> usize {
+        1 + self
+            .levels
+            .iter()
+            .take_while(|level| level.is_reached(turn, score))
+            .count()
+    }
+
+    /// The table once `turn` moves were played and `score` points were scored. Fails if no tile
+    /// can spawn at that point.
+    pub fn table_at(&self, turn: u32, score: i64) -> Result<SpawnTable, String> {
+        let extra = match self.level_at(turn, score) {
+            1 => &[][..],
+            level => &self.levels[level - 2].extra[..],
+        };
+        SpawnTable::new(
+            self.tiles
+                .iter()
+                .map(|rule| {
+                    let weight = match &rule.ramp {
+                        Some(ramp) => ramp.apply(rule.weight, turn, score),
+                        None => rule.weight,
+                    };
+                    let bonus: i32 = extra
+                        .iter()
+                        .filter(|(tile, _)| *tile == rule.tile)
+                        .map(|(_, bonus)| bonus)
+                        .sum();
+                    let weight = (i64::from(weight) + i64::from(bonus)).max(0) as u32;
+                    (rule.tile, weight)
+                })
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use crate::{
+        rng::rng_from_seed,
+        spawn::{Level, Progress, Ramp, SpawnRule, SpawnRules, SpawnTable},
+        tile::{CoinValue, TileType},
+    };
+
+    #[test]
+    fn should_only_spawn_tiles_with_a_weight() {
+        let table = SpawnTable::new(vec![
+            (TileType::Bomb, 0),
+            (TileType::Coin(CoinValue::FOUR), 3),
+        ])
+        .unwrap();
+        let mut rng = rng_from_seed(5);
+        for _ in 0..100 {
+            assert_eq!(table.pick(&mut rng), TileType::Coin(CoinValue::FOUR));
+        }
+        assert!(SpawnTable::new(vec![(TileType::Bomb, 0)]).is_err());
+        assert!(SpawnTable::new(vec![(TileType::Bomb, u32::MAX), (TileType::Wall, 1)]).is_err());
+    }
+
+    #[test]
+    fn should_round_trip_through_text() {
+        let table = SpawnTable::default();
+        assert_eq!(table.to_string(), "one=41,two=20,bomb=34,wall=6");
+        assert_eq!(table.to_string().parse(), Ok(table));
+
+        let table: SpawnTable = "eight=1,16=2".parse().unwrap();
+        assert_eq!(table.to_string(), "eight=1,16=2");
+
+        assert!("one=1,gold=2".parse::<SpawnTable>().is_err());
+        assert!("one=1,12=2".parse::<SpawnTable>().is_err());
+        assert!("one:1".parse::<SpawnTable>().is_err());
+        assert!("wall=0".parse::<SpawnTable>().is_err());
+    }
+
+    #[test]
+    fn should_ramp_weights_up_to_their_limit() {
+        let rules = SpawnRules {
+            tiles: vec![
+                SpawnRule {
+                    tile: TileType::Bomb,
+                    weight: 10,
+                    ramp: Some(Ramp {
+                        by: Progress::Score,
+                        every: 100,
+                        step: -4,
+                        limit: 2,
+                    }),
+                },
+                SpawnRule {
+                    tile: TileType::Wall,
+                    weight: 0,
+                    ramp: Some(Ramp {
+                        by: Progress::Turns,
+                        every: 10,
+                        step: 3,
+                        limit: 7,
+                    }),
+                },
+            ],
+            levels: Vec::new(),
+        };
+        let weights = |turn, score| rules.table_at(turn, score).unwrap().weights().to_vec();
+        assert_eq!(weights(9, 99), [(TileType::Bomb, 10), (TileType::Wall, 0)]);
+        assert_eq!(weights(25, 150), [(TileType::Bomb, 6), (TileType::Wall, 6)]);
+        assert_eq!(
+            weights(500, 5000),
+            [(TileType::Bomb, 2), (TileType::Wall, 7)]
+        );
+        assert!(rules.validate().is_ok());
+    }
+
+    #[test]
+    fn should_add_the_weights_of_the_level_reached() {
+        let level = |turn, score, extra| Level {
+            turn,
+            score,
+            extra: vec![(TileType::Wall, extra)],
+        };
+        let rules = SpawnRules {
+            levels: vec![level(10, 50, 5), level(20, 100, -10)],
+            ..SpawnTable::new(vec![(TileType::Bomb, 1), (TileType::Wall, 3)])
+                .unwrap()
+                .into()
+        };
+        assert!(rules.validate().is_ok());
+
+        let walls = |turn, score| rules.table_at(turn, score).unwrap().weights()[1].1;
+        assert_eq!((rules.level_at(9, 49), walls(9, 49)), (1, 3));
+        // Either the turn or the score is enough
+        assert_eq!((rules.level_at(10, 0), walls(10, 0)), (2, 8));
+        assert_eq!((rules.level_at(0, 50), walls(0, 50)), (2, 8));
+        assert_eq!((rules.level_at(15, 100), walls(15, 100)), (3, 0));
+
+        let unordered = SpawnRules {
+            levels: vec![level(20, 100, 1), level(10, 50, 1)],
+            ..rules
+        };
+        assert!(unordered.validate().is_err());
+    }
+
+    #[test]
+    fn fingerprints_should_never_change() {
+        let rules = SpawnRules::default();
+        // Replays recorded with the default rules are checked against this value
+        assert_eq!(rules.fingerprint(), 11_797_154_512_349_749_849);
+
+        let mut more_walls = rules.clone();
+        more_walls.levels[0].extra[0].1 += 1;
+        assert_ne!(more_walls.fingerprint(), rules.fingerprint());
+        let mut ramped = rules.clone();
+        ramped.tiles[3].ramp = Some(Ramp {
+            by: Progress::Turns,
+            every: 20,
+            step: 2,
+            limit: 20,
+        });
+        assert_ne!(ramped.fingerprint(), rules.fingerprint());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn shipped_spawn_rules_should_match_the_default_ones() {
+        let shipped: SpawnRules =
+            ron::from_str(include_str!("../../assets/spawns.ron")).expect("Invalid spawns.ron");
+        assert_eq!(shipped, SpawnRules::default());
+    }
+}
@@ -0,0 +1,208 @@
+#[cfg(feature = "bevy")]
+use bevy_ecs::prelude::Component;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Coins are worth twice as much at each tier : 1, 2, 4, 8, 16... Two coins of the same tier
+/// merge into one of the next tier.
+///
+/// Serialized as the value of the coin, e.g. `Coin(16)`.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, PartialOrd, Ord)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(try_from = "i32", into = "i32")
+)]
+pub struct CoinValue {
+    tier: u8,
+}
+
+impl CoinValue {
+    pub const ONE: CoinValue = CoinValue { tier: 0 };
+    pub const TWO: CoinValue = CoinValue { tier: 1 };
+    pub const FOUR: CoinValue = CoinValue { tier: 2 };
+    pub const EIGHT: CoinValue = CoinValue { tier: 3 };
+    /// Coins stop merging at this tier, so that their value still fits in an `i32`. A turn
+    /// scores at most every cell of the grid, times the depth of the chain reaction, times the
+    /// bonuses, which the `i64` score has room for.
+    pub const MAX_TIER: u8 = 30;
+
+    pub fn from_tier(tier: u8) -> Option<Self> {
+        (tier <= CoinValue::MAX_TIER).then_some(CoinValue { tier })
+    }
+
+    /// From 0, for the coin worth 1 point
+    pub fn tier(&self) -> u8 {
+        self.tier
+    }
+
+    pub fn points(&self) -> i32 {
+        1 << self.tier
+    }
+
+    pub fn next(&self) -> Option<Self> {
+        CoinValue::from_tier(self.tier + 1)
+    }
+}
+
+impl TryFrom<i32> for CoinValue {
+    type Error = String;
+
+    fn try_from(points: i32) -> Result<Self, Self::Error> {
+        if points <= 0 || points.count_ones() != 1 {
+            return Err(format!("A coin is worth a power of two, not {points}"));
+        }
+        CoinValue::from_tier(points.trailing_zeros() as u8)
+            .ok_or_else(|| format!("A coin can't be worth {points}"))
+    }
+}
+
+impl From<CoinValue> for i32 {
+    fn from(value: CoinValue) -> Self {
+        value.points()
+    }
+}
+
+/// Explosions it takes to break a wall inside the grid. The border can't be broken.
+pub const WALL_HIT_POINTS: u8 = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "bevy", derive(Component))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum TileType {
+    Coin(CoinValue),
+    Wall,
+    /// A wall hit by an explosion, with the hit points it has left
+    CrackedWall(u8),
+    Bomb,
+    /// Explodes twice as far as a bomb
+    BigBomb,
+    /// Moves like a coin, but two blocks pushed together set into walls
+    Block,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum CombinationResult {
+    MergeTilesInto(TileType),
+    Explosion,
+    /// Both tiles turn into walls, where they stand
+    Solidify,
+}
+
+pub enum ExplosionResult {
+    ScorePoints(i32),
+    /// The tile stands, but turns into this one
+    Damage(TileType),
+    NoExplosion,
+}
+
+impl TileType {
+    pub fn is_movable(&self) -> bool {
+        match self {
+            TileType::Wall | TileType::CrackedWall(_) => false,
+            TileType::Coin(_) | TileType::Bomb | TileType::BigBomb | TileType::Block => true,
+        }
+    }
+
+    pub fn is_wall(&self) -> bool {
+        matches!(self, TileType::Wall | TileType::CrackedWall(_))
+    }
+
+    /// How far the explosion of this tile reaches, 0 for tiles that don't explode
+    pub fn blast_radius(&self) -> i32 {
+        match self {
+            TileType::Bomb => 1,
+            TileType::BigBomb => 2,
+            TileType::Coin(_) | TileType::Wall | TileType::CrackedWall(_) | TileType::Block => 0,
+        }
+    }
+
+    pub fn explosion_result(&self) -> ExplosionResult {
+        match self {
+            TileType::Wall => Self::CrackedWall(WALL_HIT_POINTS).explosion_result(),
+            // Breaking a wall scores nothing, but frees its cell
+            TileType::CrackedWall(hit_points) if *hit_points <= 1 => {
+                ExplosionResult::ScorePoints(0)
+            }
+            TileType::CrackedWall(hit_points) => {
+                ExplosionResult::Damage(TileType::CrackedWall(hit_points - 1))
+            }
+            TileType::Coin(value) => ExplosionResult::ScorePoints(value.points()),
+            TileType::Bomb => ExplosionResult::ScorePoints(1),
+            TileType::BigBomb => ExplosionResult::ScorePoints(2),
+            TileType::Block => ExplosionResult::ScorePoints(1),
+        }
+    }
+
+    pub fn try_combine_with(&self, other: &TileType) -> Option<CombinationResult> {
+        match (self, other) {
+            (TileType::Coin(value), TileType::Coin(other)) if value == other => value
+                .next()
+                .map(|next| CombinationResult::MergeTilesInto(TileType::Coin(next))),
+            (TileType::Bomb | TileType::BigBomb, TileType::Bomb | TileType::BigBomb) => {
+                Some(CombinationResult::Explosion)
+            }
+            (TileType::Block, TileType::Block) => Some(CombinationResult::Solidify),
+            (
+                TileType::Coin(_)
+                | TileType::Bomb
+                | TileType::BigBomb
+                | TileType::Block
+                | TileType::Wall
+                | TileType::CrackedWall(_),
+                _,
+            ) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use crate::tile::{CoinValue, CombinationResult, ExplosionResult, TileType};
+
+    #[test]
+    fn coins_should_keep_merging_into_higher_tiers() {
+        let eight = TileType::Coin(CoinValue::EIGHT);
+        let sixteen = CoinValue::from_tier(4).unwrap();
+        assert_eq!(
+            eight.try_combine_with(&eight),
+            Some(CombinationResult::MergeTilesInto(TileType::Coin(sixteen)))
+        );
+        assert!(matches!(
+            TileType::Coin(sixteen).explosion_result(),
+            ExplosionResult::ScorePoints(16)
+        ));
+        assert_eq!(eight.try_combine_with(&TileType::Coin(sixteen)), None);
+
+        let last = TileType::Coin(CoinValue::from_tier(CoinValue::MAX_TIER).unwrap());
+        assert_eq!(last.try_combine_with(&last), None);
+        assert_eq!(CoinValue::from_tier(CoinValue::MAX_TIER + 1), None);
+    }
+
+    #[test]
+    fn blocks_should_only_set_against_blocks() {
+        assert_eq!(
+            TileType::Block.try_combine_with(&TileType::Block),
+            Some(CombinationResult::Solidify)
+        );
+        for other in [
+            TileType::Coin(CoinValue::ONE),
+            TileType::Bomb,
+            TileType::BigBomb,
+            TileType::Wall,
+        ] {
+            assert_eq!(TileType::Block.try_combine_with(&other), None);
+            assert_eq!(other.try_combine_with(&TileType::Block), None);
+        }
+    }
+
+    #[test]
+    fn coin_values_should_be_powers_of_two() {
+        assert_eq!(CoinValue::try_from(1), Ok(CoinValue::ONE));
+        assert_eq!(CoinValue::try_from(64).map(|value| value.tier()), Ok(6));
+        assert_eq!(i32::from(CoinValue::EIGHT), 8);
+        for invalid in [0, -4, 12] {
+            assert!(CoinValue::try_from(invalid).is_err());
+        }
+    }
+}
@@ -1,9 +1,13 @@
+use bevy::asset::{AssetLoader, BoxedFuture, Error, LoadContext, LoadedAsset};
 use bevy::math::Vec2;
 use bevy::prelude::{AssetServer, Assets, Handle, Image, Resource};
+use bevy::reflect::{TypePath, TypeUuid};
 use bevy::sprite::TextureAtlas;
 use bevy::text::Font;
 use bevy_asset_loader::asset_collection::AssetCollection;
 
+use am4lgam8_core::{puzzle::Puzzle, spawn::SpawnRules};
+
 #[derive(AssetCollection, Resource)]
 pub struct GameAssets {
     #[asset(texture_atlas(
@@ -32,4 +36,73 @@ pub struct GameAssets {
     pub explosion: Handle<TextureAtlas>,
     #[asset(path = "joystix.otf")]
     pub joystix: Handle<Font>,
+    #[asset(path = "spawns.ron")]
+    pub spawn_rules: Handle<SpawnRulesAsset>,
+    /// In the order of the level select screen
+    #[asset(
+        paths(
+            "puzzles/01-first-blast.puzzle.ron",
+            "puzzles/02-pocket-change.puzzle.ron",
+            "puzzles/03-chain-reaction.puzzle.ron",
+            "puzzles/04-brick-by-brick.puzzle.ron",
+            "puzzles/05-incoming.puzzle.ron",
+        ),
+        collection(typed)
+    )]
+    pub puzzles: Vec<Handle<PuzzleAsset>>,
+}
+
+/// The odds of each tile spawning, from `spawns.ron`
+#[derive(Debug, TypeUuid, TypePath)]
+#[uuid = "5bb2af7a-cd60-4a5e-9c04-e69ff3ff9a62"]
+pub struct SpawnRulesAsset(pub SpawnRules);
+
+#[derive(Default)]
+pub struct SpawnRulesLoader;
+
+impl AssetLoader for SpawnRulesLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), Error>> {
+        Box::pin(async move {
+            let rules: SpawnRules = ron::de::from_bytes(bytes)?;
+            rules.validate().map_err(Error::msg)?;
+            load_context.set_default_asset(LoadedAsset::new(SpawnRulesAsset(rules)));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["ron"]
+    }
+}
+
+/// A handcrafted level, from a `.puzzle.ron` file
+#[derive(Debug, TypeUuid, TypePath)]
+#[uuid = "0f0b8e64-3c1d-4b7e-a4f4-6d0c2a9f5e17"]
+pub struct PuzzleAsset(pub Puzzle);
+
+/// Claims `.puzzle.ron` files before the spawn rules loader can claim them as `.ron`
+#[derive(Default)]
+pub struct PuzzleLoader;
+
+impl AssetLoader for PuzzleLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), Error>> {
+        Box::pin(async move {
+            // Deserializing also validates the puzzle
+            let puzzle: Puzzle = ron::de::from_bytes(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(PuzzleAsset(puzzle)));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["puzzle.ron"]
+    }
 }
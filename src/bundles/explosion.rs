@@ -1,15 +1,16 @@
 use std::time::Duration;
 
 use bevy::{
-    prelude::{Bundle, Transform, Vec3},
+    prelude::{Bundle, Transform, Vec3, Visibility},
     sprite::{SpriteSheetBundle, TextureAtlasSprite},
     time::{Timer, TimerMode},
 };
 
+use am4lgam8_core::grid::GridCoordinates;
+
 use crate::{
     assets::GameAssets,
     constants::TILE_SIZE,
-    game::grid::GridCoordinates,
     systems::{explosion::ExplosionAnimation, marked_for_deletion::MarkedForDeletion},
 };
 
@@ -24,7 +25,8 @@ const ANIMATION_DURATION: f32 = 0.5;
 const ANIMATION_FRAMES: f32 = 6.;
 
 impl ExplosionBundle {
-    pub fn new(assets: &GameAssets, grid_coordinates: GridCoordinates) -> Self {
+    /// Hidden for `delay` seconds, for the later waves of a chain reaction
+    pub fn new(assets: &GameAssets, grid_coordinates: GridCoordinates, delay: f32) -> Self {
         ExplosionBundle {
             sprite_sheet: SpriteSheetBundle {
                 sprite: TextureAtlasSprite::new(0),
@@ -37,16 +39,24 @@ impl ExplosionBundle {
                     ),
                     ..Default::default()
                 },
+                visibility: if delay > 0. {
+                    Visibility::Hidden
+                } else {
+                    Visibility::Inherited
+                },
                 ..Default::default()
             },
             deletion_marker: MarkedForDeletion(Timer::new(
-                Duration::from_secs_f32(ANIMATION_DURATION),
-                TimerMode::Once,
-            )),
-            explosion_anim: ExplosionAnimation(Timer::new(
-                Duration::from_secs_f32(ANIMATION_DURATION / ANIMATION_FRAMES),
+                Duration::from_secs_f32(delay + ANIMATION_DURATION),
                 TimerMode::Once,
             )),
+            explosion_anim: ExplosionAnimation {
+                delay: Timer::new(Duration::from_secs_f32(delay), TimerMode::Once),
+                frame: Timer::new(
+                    Duration::from_secs_f32(ANIMATION_DURATION / ANIMATION_FRAMES),
+                    TimerMode::Once,
+                ),
+            },
         }
     }
 }
@@ -0,0 +1,31 @@
+use bevy::{
+    prelude::{default, Color, Transform, Vec2},
+    sprite::{Sprite, SpriteBundle},
+};
+
+use crate::constants::TILE_SIZE;
+
+/// A plain rectangle of `size` logic pixels, 1 pixel wide ones being lines
+pub fn line_sprite(center: Vec2, size: Vec2, color: Color) -> SpriteBundle {
+    SpriteBundle {
+        sprite: Sprite {
+            color,
+            custom_size: Some(size),
+            ..default()
+        },
+        transform: Transform::from_translation(center.extend(0.)),
+        ..default()
+    }
+}
+
+/// The 4 lines drawn along the inner edges of a tile, relative to its center
+pub fn tile_outline(color: Color) -> [SpriteBundle; 4] {
+    let half = TILE_SIZE / 2.;
+    [
+        (Vec2::new(0., half - 0.5), Vec2::new(TILE_SIZE, 1.)),
+        (Vec2::new(0., -half + 0.5), Vec2::new(TILE_SIZE, 1.)),
+        (Vec2::new(-half + 0.5, 0.), Vec2::new(1., TILE_SIZE)),
+        (Vec2::new(half - 0.5, 0.), Vec2::new(1., TILE_SIZE)),
+    ]
+    .map(|(center, size)| line_sprite(center, size, color))
+}
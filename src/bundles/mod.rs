@@ -1,2 +1,3 @@
 pub mod explosion;
+pub mod line;
 pub mod tile;
@@ -1,16 +1,20 @@
 use std::time::Duration;
 
+use am4lgam8_core::{
+    grid::GridCoordinates,
+    tile::{CoinValue, TileType, WALL_HIT_POINTS},
+};
+
 use crate::{
-    constants::TILE_SIZE,
-    game::{
-        grid::GridCoordinates,
-        tile::{CoinValue, TileType},
-    },
+    assets::GameAssets,
+    bundles::line::line_sprite,
+    constants::{background_color, TILE_SIZE},
     systems::{self, OnPlayingScreen},
 };
 use bevy::{
-    prelude::{Bundle, Commands, Handle, Transform, Vec2, Vec3},
+    prelude::{BuildChildren, Bundle, Color, Commands, Entity, Handle, Transform, Vec2, Vec3},
     sprite::{SpriteSheetBundle, TextureAtlas, TextureAtlasSprite},
+    text::{Text, Text2dBundle, TextAlignment, TextStyle},
 };
 use bevy_easings::{Ease, EaseFunction, EaseMethod, EasingComponent};
 use bevy_mod_picking::PickableBundle;
@@ -21,17 +25,49 @@ pub struct TileBundle {
     pub grid_coords: GridCoordinates,
 }
 
+// The value written over the coins that have no sprite of their own. It is laid out at a bigger
+// size and scaled down, so that the glyphs stay sharp.
+const COIN_LABEL_FONT_SIZE: f32 = 20.;
+const COIN_LABEL_SCALE: f32 = 0.25;
+// Big bombs are bombs painted red, and blocks are walls painted brown
+const BIG_BOMB_COLOR: Color = Color::rgb(1., 0.35, 0.25);
+const BLOCK_COLOR: Color = Color::rgb(0.85, 0.6, 0.35);
+// Blocks setting into walls land with a thud
+const SOLIDIFY_SCALE: f32 = 1.3;
+const SOLIDIFY_DURATION: f32 = 0.3;
+// The cracks drawn over a wall for each hit it took, as pixel lines relative to its center
+const CRACKS: [&[(Vec2, Vec2)]; 2] = [
+    &[
+        (Vec2::new(-3.5, 5.), Vec2::new(1., 2.)),
+        (Vec2::new(-2.5, 3.), Vec2::new(1., 2.)),
+        (Vec2::new(-1.5, 1.), Vec2::new(1., 2.)),
+        (Vec2::new(-0.5, -0.5), Vec2::new(1., 1.)),
+        (Vec2::new(0.5, -2.), Vec2::new(1., 2.)),
+        (Vec2::new(1.5, -4.), Vec2::new(1., 2.)),
+    ],
+    &[
+        (Vec2::new(4.5, 4.), Vec2::new(1., 2.)),
+        (Vec2::new(3.5, 2.5), Vec2::new(1., 1.)),
+        (Vec2::new(2.5, 1.), Vec2::new(1., 2.)),
+        (Vec2::new(-4., -3.5), Vec2::new(2., 1.)),
+        (Vec2::new(-5.5, -4.5), Vec2::new(1., 1.)),
+    ],
+];
+
 pub fn spawn_tile_type_bundle(
     commands: &mut Commands,
-    tileset: Handle<TextureAtlas>,
+    assets: &GameAssets,
     tile_type: TileType,
     x: i32,
     y: i32,
-) {
+) -> Entity {
+    let tileset = assets.tileset.clone();
     match tile_type {
-        TileType::Coin(value) => spawn_coin(commands, tileset, x, y, value),
-        TileType::Wall => spawn_wall(commands, tileset, x, y),
-        TileType::Bomb => spawn_bomb(commands, tileset, x, y),
+        TileType::Coin(value) => spawn_coin(commands, assets, x, y, value),
+        TileType::Wall | TileType::CrackedWall(_) => spawn_wall(commands, tileset, x, y, tile_type),
+        TileType::Bomb | TileType::BigBomb | TileType::Block => {
+            spawn_movable(commands, tileset, x, y, tile_type)
+        }
     }
 }
 
@@ -55,28 +91,41 @@ fn gen_transform_and_easing(x: i32, y: i32) -> (Transform, EasingComponent<Trans
     (transform, easing)
 }
 
+/// The value of a coin in 4 characters at most, e.g. `512` or `64K`
+fn coin_label(value: CoinValue) -> String {
+    let points = value.points();
+    match points {
+        0..=999 => points.to_string(),
+        1_000..=999_999 => format!("{}K", points >> 10),
+        1_000_000..=999_999_999 => format!("{}M", points >> 20),
+        _ => format!("{}G", points >> 30),
+    }
+}
+
 fn spawn_coin(
     commands: &mut Commands,
-    tileset: Handle<TextureAtlas>,
+    assets: &GameAssets,
     x: i32,
     y: i32,
     value: CoinValue,
-) {
+) -> Entity {
+    // The tileset only has the first coins, the next ones are an empty coin with their value on it
     let index = match value {
-        CoinValue::One => 1,
-        CoinValue::Two => 5,
-        CoinValue::Four => 9,
-        CoinValue::Eight => 13,
+        CoinValue::ONE => 1,
+        CoinValue::TWO => 5,
+        CoinValue::FOUR => 9,
+        CoinValue::EIGHT => 13,
+        _ => 8,
     };
 
     let (transform, easing) = gen_transform_and_easing(x, y);
-    commands.spawn((
+    let mut coin = commands.spawn((
         TileBundle {
             sprite: SpriteSheetBundle {
-                texture_atlas: tileset,
+                texture_atlas: assets.tileset.clone(),
                 sprite: TextureAtlasSprite {
                     index,
-                    custom_size: Some(Vec2::new(TILE_SIZE as f32, TILE_SIZE as f32)),
+                    custom_size: Some(Vec2::new(TILE_SIZE, TILE_SIZE)),
                     ..Default::default()
                 },
                 transform,
@@ -87,43 +136,105 @@ fn spawn_coin(
         easing,
         TileType::Coin(value),
         PickableBundle::default(),
+        systems::movables::on_pointer_drag_handler(),
         systems::movables::on_pointer_drag_end_handler(),
         OnPlayingScreen,
     ));
+    if index == 8 {
+        coin.with_children(|parent| {
+            parent.spawn(Text2dBundle {
+                text: Text::from_section(
+                    coin_label(value),
+                    TextStyle {
+                        font: assets.joystix.clone(),
+                        font_size: COIN_LABEL_FONT_SIZE,
+                        color: Color::WHITE,
+                    },
+                )
+                .with_alignment(TextAlignment::Center),
+                // Just above the coin, and under the ghosts and the cursor
+                transform: Transform::from_xyz(0., 0., 0.5)
+                    .with_scale(Vec3::splat(COIN_LABEL_SCALE)),
+                ..Default::default()
+            });
+        });
+    }
+    coin.id()
 }
 
-fn spawn_bomb(commands: &mut Commands, tileset: Handle<TextureAtlas>, x: i32, y: i32) {
+/// Bombs and blocks
+fn spawn_movable(
+    commands: &mut Commands,
+    tileset: Handle<TextureAtlas>,
+    x: i32,
+    y: i32,
+    tile_type: TileType,
+) -> Entity {
+    let (index, color) = match tile_type {
+        TileType::BigBomb => (2, BIG_BOMB_COLOR),
+        TileType::Block => (4, BLOCK_COLOR),
+        _ => (2, Color::WHITE),
+    };
     let (transform, easing) = gen_transform_and_easing(x, y);
-    commands.spawn((
-        TileBundle {
-            sprite: SpriteSheetBundle {
-                texture_atlas: tileset,
-                sprite: TextureAtlasSprite {
-                    index: 2,
-                    custom_size: Some(Vec2::new(TILE_SIZE as f32, TILE_SIZE as f32)),
+    commands
+        .spawn((
+            TileBundle {
+                sprite: SpriteSheetBundle {
+                    texture_atlas: tileset,
+                    sprite: TextureAtlasSprite {
+                        index,
+                        color,
+                        custom_size: Some(Vec2::new(TILE_SIZE, TILE_SIZE)),
+                        ..Default::default()
+                    },
+                    transform,
                     ..Default::default()
                 },
-                transform,
-                ..Default::default()
+                grid_coords: GridCoordinates { x, y },
             },
-            grid_coords: GridCoordinates { x, y },
+            easing,
+            PickableBundle::default(),
+            systems::movables::on_pointer_drag_handler(),
+            systems::movables::on_pointer_drag_end_handler(),
+            tile_type,
+            OnPlayingScreen,
+        ))
+        .id()
+}
+
+/// A wall that was a pair of blocks until now
+pub fn spawn_solidified_wall(commands: &mut Commands, assets: &GameAssets, x: i32, y: i32) {
+    let wall = spawn_wall(commands, assets.tileset.clone(), x, y, TileType::Wall);
+    let transform = Transform::from_xyz(x as f32 * TILE_SIZE, y as f32 * TILE_SIZE, 0.);
+    let easing = transform.with_scale(Vec3::splat(SOLIDIFY_SCALE)).ease_to(
+        transform,
+        EaseMethod::EaseFunction(EaseFunction::BounceOut),
+        bevy_easings::EasingType::Once {
+            duration: Duration::from_secs_f32(SOLIDIFY_DURATION),
         },
-        easing,
-        PickableBundle::default(),
-        systems::movables::on_pointer_drag_end_handler(),
-        TileType::Bomb,
-        OnPlayingScreen,
-    ));
+    );
+    commands.entity(wall).insert(easing);
 }
 
-fn spawn_wall(commands: &mut Commands, tileset: Handle<TextureAtlas>, x: i32, y: i32) {
-    commands.spawn((
+/// Walls that took hits show as many cracks
+fn spawn_wall(
+    commands: &mut Commands,
+    tileset: Handle<TextureAtlas>,
+    x: i32,
+    y: i32,
+    tile_type: TileType,
+) -> Entity {
+    let hits = match tile_type {
+        TileType::CrackedWall(hit_points) => WALL_HIT_POINTS.saturating_sub(hit_points).max(1),
+        _ => 0,
+    };
+    let mut wall = commands.spawn((
         TileBundle {
             sprite: SpriteSheetBundle {
                 texture_atlas: tileset,
                 sprite: TextureAtlasSprite {
                     index: 4,
-                    custom_size: Some(Vec2::new(TILE_SIZE as f32, TILE_SIZE as f32)),
+                    custom_size: Some(Vec2::new(TILE_SIZE, TILE_SIZE)),
                     ..Default::default()
                 },
                 transform: Transform {
@@ -134,7 +245,15 @@ fn spawn_wall(commands: &mut Commands, tileset: Handle<TextureAtlas>, x: i32, y:
             },
             grid_coords: GridCoordinates { x, y },
         },
-        TileType::Wall,
+        tile_type,
         OnPlayingScreen,
     ));
+    wall.with_children(|parent| {
+        for (center, size) in CRACKS.iter().take(hits.into()).copied().flatten() {
+            let mut crack = line_sprite(*center, *size, background_color());
+            crack.transform.translation.z = 0.5;
+            parent.spawn(crack);
+        }
+    });
+    wall.id()
 }
@@ -1,16 +1,16 @@
 use bevy::prelude::Color;
 
-pub const GRID_SIZE: i32 = 4;
 pub const TILE_SIZE_INTEGER: i32 = 16;
 pub const TILE_SIZE: f32 = TILE_SIZE_INTEGER as f32;
-pub const GAME_WINDOW_WIDTH: f32 = GAME_LOGIC_WIDTH * 5.;
-pub const GAME_WINDOW_HEIGHT: f32 = GAME_LOGIC_HEIGHT * 5.;
 
-pub const GAME_LOGIC_WIDTH: f32 = 100.; // The padding is for the UI
-pub const GAME_LOGIC_HEIGHT: f32 = 106.; // The padding is for the UI
+// The padding around the walls is for the UI frame, and the score bar at the bottom
+pub const UI_PADDING_WIDTH: f32 = 4.;
+pub const UI_PADDING_HEIGHT: f32 = 10.;
 
-// pub const GAME_LOGIC_WIDTH: f32 = TILE_SIZE * (GRID_SIZE + 2) as f32 + 12.; // The padding is for the UI
-// pub const GAME_LOGIC_HEIGHT: f32 = TILE_SIZE * (GRID_SIZE + 2) as f32 + 6.; // The padding is for the UI
+// How many screen pixels a logic pixel takes, at most
+pub const MAX_PIXEL_SCALE: f32 = 5.;
+// The window is scaled down (by whole pixels) to stay under this height on big grids
+pub const MAX_WINDOW_HEIGHT: f32 = 900.;
 
 pub fn background_color() -> Color {
     Color::rgb_u8(47, 33, 59)
@@ -19,3 +19,8 @@ pub fn background_color() -> Color {
 pub fn foreground_color() -> Color {
     Color::rgb_u8(192, 209, 204)
 }
+
+/// Used for what goes wrong, like a move that can't be played
+pub fn warning_color() -> Color {
+    Color::rgb_u8(214, 69, 65)
+}
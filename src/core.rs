@@ -1,13 +1,38 @@
+use am4lgam8_core::{
+    grid::{GridConfig, MoveTileEvent, TileGrid},
+    history::MoveHistory,
+    moves::{MergeTilesEvent, SolidifyEvent, ValidMoveEvent},
+    score::GameScore,
+};
+
 use crate::{
-    assets,
-    game::{
-        grid::{MoveTileEvent, TileGrid},
-        moves::{ExplosionEvent, MergeTilesEvent, ValidMoveEvent},
+    assets::{self, PuzzleAsset, PuzzleLoader, SpawnRulesAsset, SpawnRulesLoader},
+    systems::{
+        self,
+        ai::AiPlayer,
+        blitz::ActiveBlitz,
+        cursor::SelectionCursor,
+        daily::{load_daily_history, ActiveDaily},
+        editor::load_editor_draft,
+        gamepad::MenuFocus,
+        grid::{EndlessConfig, SeedSetting, ValidTurnEvent},
+        highscores::{load_high_scores, PendingHighScore},
+        movables::{RequestMoveEvent, TileDragEndEvent, TileDragEvent},
+        preview::MovePreview,
+        progression::Progression,
+        puzzle::{load_puzzle_records, ActivePuzzle, PuzzleMenu},
+        replay::{LastReplay, ReplayPlayback, ReplayRecorder},
+        save::{ResumedRun, SavedRunSlot},
+        score_popup::ScoreBreakdownEvent,
+        tiles::ChainReactionEvent,
     },
-    systems::{self, grid::ValidTurnEvent, movables::RequestMoveEvent, ui::GameScore},
 };
-use bevy::prelude::{
-    in_state, App, IntoSystemConfigs, OnEnter, Plugin, PostUpdate, PreUpdate, States, Update,
+use bevy::{
+    prelude::{
+        in_state, AddAsset, App, Condition, IntoSystemConfigs, Last, OnEnter, OnExit, Plugin,
+        PostUpdate, PreUpdate, States, Update,
+    },
+    ui::UiSystem,
 };
 use bevy_asset_loader::loading_state::{LoadingState, LoadingStateAppExt};
 
@@ -18,54 +43,193 @@ pub enum GameState {
     TitleScreen,
     Playing,
     GameOver,
+    HighScores,
+    LevelSelect,
+    Editor,
 }
 
 pub struct GamePlugin;
 
 impl GamePlugin {
     fn assets(app: &mut App) {
-        app.add_loading_state(
-            LoadingState::new(GameState::Loading).continue_to_state(GameState::TitleScreen),
-        )
-        .add_collection_to_loading_state::<_, assets::GameAssets>(GameState::Loading);
+        app.add_asset::<SpawnRulesAsset>()
+            .init_asset_loader::<SpawnRulesLoader>()
+            .add_asset::<PuzzleAsset>()
+            .init_asset_loader::<PuzzleLoader>()
+            .add_loading_state(
+                LoadingState::new(GameState::Loading).continue_to_state(GameState::TitleScreen),
+            )
+            .add_collection_to_loading_state::<_, assets::GameAssets>(GameState::Loading);
     }
 
     fn resources(app: &mut App) {
         app.add_event::<RequestMoveEvent>()
+            .add_event::<TileDragEvent>()
+            .add_event::<TileDragEndEvent>()
             .add_event::<ValidMoveEvent>()
             .add_event::<MoveTileEvent>()
             .add_event::<MergeTilesEvent>()
-            .add_event::<ExplosionEvent>()
+            .add_event::<ChainReactionEvent>()
+            .add_event::<ScoreBreakdownEvent>()
+            .add_event::<SolidifyEvent>()
             .add_event::<ValidTurnEvent>()
+            .insert_resource(GridConfig::default())
+            .init_resource::<SeedSetting>()
+            .init_resource::<EndlessConfig>()
             .insert_resource(TileGrid::default())
-            .insert_resource(GameScore::default());
+            .insert_resource(GameScore::default())
+            .init_resource::<MoveHistory>()
+            .init_resource::<SelectionCursor>()
+            .init_resource::<MenuFocus>()
+            .init_resource::<MovePreview>()
+            .init_resource::<Progression>()
+            .init_resource::<AiPlayer>()
+            .init_resource::<ReplayRecorder>()
+            .init_resource::<ReplayPlayback>()
+            .init_resource::<LastReplay>()
+            .insert_resource(load_high_scores())
+            .init_resource::<PendingHighScore>()
+            .init_resource::<SavedRunSlot>()
+            .init_resource::<ResumedRun>()
+            .insert_resource(load_puzzle_records())
+            .init_resource::<ActivePuzzle>()
+            .init_resource::<PuzzleMenu>()
+            .insert_resource(load_editor_draft())
+            .insert_resource(load_daily_history())
+            .init_resource::<ActiveDaily>()
+            .init_resource::<ActiveBlitz>();
     }
 
     fn on_enter_title_screen(app: &mut App) {
         app.add_systems(
             OnEnter(GameState::TitleScreen),
-            (systems::camera::setup, systems::title_screen::setup),
+            (
+                systems::camera::setup,
+                systems::save::load_saved_run,
+                systems::grid::restore_endless_config,
+                systems::title_screen::setup
+                    .after(systems::save::load_saved_run)
+                    .after(systems::grid::restore_endless_config),
+                systems::gamepad::reset_menu_focus,
+            ),
         );
     }
 
     fn on_update_title_screen(app: &mut App) {
         app.add_systems(
             Update,
-            systems::title_screen::update_ui.run_if(in_state(GameState::TitleScreen)),
+            (
+                systems::title_screen::update_ui,
+                systems::title_screen::update_continue,
+                systems::title_screen::update_grid_size,
+                systems::title_screen::update_seed,
+                systems::title_screen::update_high_scores,
+                systems::title_screen::update_blitz,
+                systems::title_screen::update_puzzles,
+                systems::title_screen::update_daily,
+                systems::title_screen::update_replay,
+            )
+                .run_if(in_state(GameState::TitleScreen)),
         );
     }
 
     fn on_enter_game_over_screen(app: &mut App) {
         app.add_systems(
             OnEnter(GameState::GameOver),
-            (systems::camera::setup, systems::game_over::setup),
+            (
+                systems::camera::setup,
+                systems::highscores::record_high_score.before(systems::replay::stop_replay),
+                // Daily and blitz runs leave the saved run alone, they were not saved over it
+                systems::save::clear_saved_run
+                    .before(systems::replay::stop_replay)
+                    .run_if(systems::daily::no_daily)
+                    .run_if(systems::blitz::no_blitz),
+                systems::game_over::setup.after(systems::highscores::record_high_score),
+                // Tiles spawned by the clock can't be replayed
+                systems::replay::keep_last_replay
+                    .before(systems::replay::stop_replay)
+                    .run_if(systems::blitz::no_blitz),
+                systems::replay::stop_replay,
+                systems::gamepad::reset_menu_focus,
+            ),
         );
     }
 
     fn on_update_game_over_screen(app: &mut App) {
         app.add_systems(
             Update,
-            systems::game_over::update_ui.run_if(in_state(GameState::GameOver)),
+            (
+                systems::game_over::update_ui,
+                systems::highscores::enter_initials,
+            )
+                .run_if(in_state(GameState::GameOver)),
+        )
+        .add_systems(
+            OnExit(GameState::GameOver),
+            (
+                systems::highscores::save_pending_initials,
+                systems::daily::finish_daily,
+                systems::blitz::finish_blitz,
+            ),
+        );
+    }
+
+    fn on_high_score_screen(app: &mut App) {
+        app.add_systems(
+            OnEnter(GameState::HighScores),
+            (
+                systems::camera::setup,
+                systems::high_score_screen::setup,
+                systems::gamepad::reset_menu_focus,
+            ),
+        )
+        .add_systems(
+            Update,
+            systems::high_score_screen::update_ui.run_if(in_state(GameState::HighScores)),
+        );
+    }
+
+    fn on_level_select_screen(app: &mut App) {
+        app.add_systems(
+            OnEnter(GameState::LevelSelect),
+            (
+                systems::camera::setup,
+                systems::level_select::setup,
+                systems::gamepad::reset_menu_focus,
+            ),
+        )
+        .add_systems(
+            Update,
+            (
+                systems::level_select::update_puzzle_buttons,
+                systems::level_select::update_editor_button,
+                systems::level_select::update_back_button,
+            )
+                .run_if(in_state(GameState::LevelSelect)),
+        );
+    }
+
+    fn on_editor_screen(app: &mut App) {
+        app.add_systems(
+            OnEnter(GameState::Editor),
+            (
+                systems::camera::setup,
+                systems::editor::setup,
+                systems::gamepad::reset_menu_focus,
+            ),
+        )
+        .add_systems(
+            Update,
+            (
+                systems::editor::type_name,
+                systems::editor::update_buttons,
+                systems::editor::update_labels
+                    .after(systems::editor::type_name)
+                    .after(systems::editor::update_buttons),
+                systems::editor::update_brush_outline,
+                systems::editor::poll_solver.before(systems::editor::update_labels),
+            )
+                .run_if(in_state(GameState::Editor)),
         );
     }
 
@@ -77,33 +241,103 @@ impl GamePlugin {
                 systems::grid::setup_grid,
                 systems::ui::spawn_ui,
                 systems::ui::reset_score,
-                systems::grid::spawn_first_tile,
+                systems::progression::reset_progression,
+                systems::history::reset_history,
+                systems::grid::spawn_first_tile
+                    .after(systems::grid::setup_grid)
+                    .run_if(systems::save::no_run_to_resume)
+                    .run_if(systems::puzzle::no_puzzle),
+                systems::replay::start_recording.after(systems::grid::setup_grid),
+                systems::save::resume_run
+                    .after(systems::grid::setup_grid)
+                    .after(systems::grid::spawn_first_tile)
+                    .after(systems::ui::reset_score)
+                    .after(systems::history::reset_history)
+                    .after(systems::replay::start_recording),
+                systems::replay::spawn_replay_label,
+                systems::puzzle::spawn_puzzle_label.after(systems::grid::setup_grid),
+                systems::cursor::reset_cursor,
+                systems::cursor::spawn_cursor,
+                systems::preview::reset_move_preview,
+                systems::ai::stop_autoplay,
             ),
         );
     }
 
     fn on_update_playing_state(app: &mut App) {
         // Pre-Update
+        // Puzzles end on their own terms
         app.add_systems(
             PreUpdate,
-            systems::grid::check_for_game_over.run_if(in_state(GameState::Playing)),
+            (
+                systems::grid::check_for_game_over.run_if(systems::puzzle::no_puzzle),
+                systems::puzzle::check_puzzle_outcome,
+                systems::blitz::check_blitz_clock,
+            )
+                .run_if(in_state(GameState::Playing)),
         );
 
         // Update
         let handle_explosion_events = systems::tiles::handle_explosion_events
-            .after(systems::tiles::handle_requested_move_events);
+            .after(systems::tiles::handle_requested_move_events)
+            .before(systems::tiles::handle_valid_move_events);
         let handle_combine_events = systems::tiles::handle_combine_events
             .after(systems::tiles::handle_requested_move_events);
         let handle_valid_move_events = systems::tiles::handle_valid_move_events
             .after(systems::tiles::handle_requested_move_events);
+        let handle_solidify_events = systems::tiles::handle_solidify_events
+            .after(systems::tiles::handle_requested_move_events);
+
+        let handle_undo_redo = systems::history::handle_undo_redo
+            .before(systems::tiles::handle_requested_move_events)
+            .run_if(systems::replay::no_replay);
+
+        // The replay is playing, not the player
+        let player_input_systems = (
+            systems::keyboard::handle_keyboard_input
+                .before(systems::tiles::handle_requested_move_events),
+            systems::gamepad::handle_gamepad_input
+                .before(systems::tiles::handle_requested_move_events),
+            systems::movables::handle_tile_drags.before(systems::preview::update_move_preview),
+            systems::movables::handle_tile_drag_ends
+                .before(systems::tiles::handle_requested_move_events),
+            systems::ai::handle_ai_input.before(systems::tiles::handle_requested_move_events),
+            systems::ai::play_autoplay.before(systems::tiles::handle_requested_move_events),
+        )
+            .run_if(systems::replay::no_replay);
 
         let update_systems = (
+            handle_undo_redo,
+            systems::replay::play_replay.before(systems::tiles::handle_requested_move_events),
+            player_input_systems,
+            systems::ai::update_autoplay_button.after(systems::ai::handle_ai_input),
+            systems::cursor::update_cursor_overlay
+                .after(systems::keyboard::handle_keyboard_input)
+                .after(systems::gamepad::handle_gamepad_input),
             systems::tiles::handle_requested_move_events,
             handle_explosion_events,
             handle_combine_events,
             handle_valid_move_events,
+            handle_solidify_events,
             systems::ui::update_ui,
+            (
+                systems::blitz::award_time_bonus
+                    .after(systems::tiles::handle_requested_move_events),
+                systems::ui::update_level_label.after(systems::blitz::award_time_bonus),
+                systems::puzzle::update_puzzle_label,
+            ),
+            systems::preview::clear_move_preview_on_turn
+                .after(systems::tiles::handle_requested_move_events),
+            systems::preview::update_move_preview
+                .after(systems::tiles::handle_requested_move_events)
+                .after(systems::preview::clear_move_preview_on_turn)
+                .after(systems::ai::handle_ai_input),
             systems::explosion::animate_explosion,
+            (
+                systems::score_popup::spawn_score_popups
+                    .after(systems::tiles::handle_requested_move_events),
+                systems::score_popup::animate_score_popups,
+            ),
             systems::marked_for_deletion::tick_marked_for_deletion,
         )
             .run_if(in_state(GameState::Playing));
@@ -112,9 +346,46 @@ impl GamePlugin {
         // Post-update
         app.add_systems(
             PostUpdate,
-            systems::grid::spawn_new_tile_on_valid_move.run_if(in_state(GameState::Playing)),
+            (
+                systems::progression::update_progression
+                    .before(systems::grid::spawn_new_tile_on_valid_move),
+                systems::grid::spawn_new_tile_on_valid_move,
+                // Once the tile of the move is placed, so that the clock gets what is left
+                systems::blitz::tick_blitz_clock.after(systems::grid::spawn_new_tile_on_valid_move),
+            )
+                .run_if(in_state(GameState::Playing)),
+        );
+
+        // Last, once the turn is over. Puzzles, daily and blitz runs are not saved, and leave the
+        // saved run alone.
+        app.add_systems(
+            Last,
+            (
+                systems::save::autosave_run
+                    .run_if(systems::puzzle::no_puzzle)
+                    .run_if(systems::daily::no_daily)
+                    .run_if(systems::blitz::no_blitz),
+                systems::daily::track_daily_result,
+            )
+                .run_if(in_state(GameState::Playing)),
         );
     }
+
+    fn gamepad(app: &mut App) {
+        app.add_systems(
+            PreUpdate,
+            systems::gamepad::navigate_menu_with_gamepad
+                .after(UiSystem::Focus)
+                .run_if(
+                    in_state(GameState::TitleScreen)
+                        .or_else(in_state(GameState::GameOver))
+                        .or_else(in_state(GameState::HighScores))
+                        .or_else(in_state(GameState::LevelSelect))
+                        .or_else(in_state(GameState::Editor)),
+                ),
+        )
+        .add_systems(Update, systems::gamepad::handle_gamepad_connections);
+    }
 }
 
 impl Plugin for GamePlugin {
@@ -126,7 +397,11 @@ impl Plugin for GamePlugin {
         GamePlugin::on_update_title_screen(app);
         GamePlugin::on_enter_game_over_screen(app);
         GamePlugin::on_update_game_over_screen(app);
+        GamePlugin::on_high_score_screen(app);
+        GamePlugin::on_level_select_screen(app);
+        GamePlugin::on_editor_screen(app);
         GamePlugin::on_enter_playing_state(app);
         GamePlugin::on_update_playing_state(app);
+        GamePlugin::gamepad(app);
     }
 }
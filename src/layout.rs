@@ -0,0 +1,66 @@
+use bevy::prelude::Vec2;
+
+use am4lgam8_core::grid::GridConfig;
+
+use crate::constants::{
+    MAX_PIXEL_SCALE, MAX_WINDOW_HEIGHT, TILE_SIZE, UI_PADDING_HEIGHT, UI_PADDING_WIDTH,
+};
+
+/// Where things go on screen for a given grid size.
+///
+/// Tiles are placed at `coords * TILE_SIZE`, so the grid (walls included) spans from
+/// `-1.5 * TILE_SIZE` to `(size + 0.5) * TILE_SIZE` on each axis.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GridLayout {
+    pub config: GridConfig,
+    /// Size of the visible area, in logic (art) pixels
+    pub logic_size: Vec2,
+    /// Center of the visible area, in world coordinates
+    pub center: Vec2,
+    /// How many window pixels a logic pixel takes
+    pub pixel_scale: f32,
+}
+
+impl GridLayout {
+    pub fn new(config: GridConfig) -> Self {
+        let logic_size = Vec2::new(
+            TILE_SIZE * (config.width + 2) as f32 + UI_PADDING_WIDTH,
+            TILE_SIZE * (config.height + 2) as f32 + UI_PADDING_HEIGHT,
+        );
+        let center = Vec2::new(
+            TILE_SIZE * (config.width as f32 / 2. - 0.5),
+            TILE_SIZE * (config.height as f32 / 2. - 0.5) - 3.,
+        );
+        let pixel_scale = (MAX_WINDOW_HEIGHT / logic_size.y)
+            .floor()
+            .clamp(1., MAX_PIXEL_SCALE);
+        GridLayout {
+            config,
+            logic_size,
+            center,
+            pixel_scale,
+        }
+    }
+
+    pub fn window_size(&self) -> Vec2 {
+        self.logic_size * self.pixel_scale
+    }
+
+    /// Bounds of the grid, walls included, in world coordinates
+    pub fn grid_min(&self) -> Vec2 {
+        Vec2::splat(-1.5 * TILE_SIZE)
+    }
+
+    pub fn grid_max(&self) -> Vec2 {
+        Vec2::new(
+            (self.config.width as f32 + 0.5) * TILE_SIZE,
+            (self.config.height as f32 + 0.5) * TILE_SIZE,
+        )
+    }
+}
+
+impl Default for GridLayout {
+    fn default() -> Self {
+        GridLayout::new(GridConfig::default())
+    }
+}
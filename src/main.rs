@@ -1,4 +1,9 @@
+// Bevy systems routinely take lots of parameters, with complex query types
+#![allow(clippy::too_many_arguments, clippy::type_complexity)]
+
+use am4lgam8_core::{ai::Strategy, replay::Replay};
 use bevy::{
+    asset::AssetPlugin,
     prelude::{App, ClearColor, DefaultPlugins, ImagePlugin, Msaa, PluginGroup},
     window::{Window, WindowPlugin},
 };
@@ -7,14 +12,17 @@ use bevy_mod_picking::{
     prelude::{DebugPickingPlugin, SpriteBackend},
     DefaultPickingPlugins,
 };
-use constants::{background_color, GAME_WINDOW_HEIGHT, GAME_WINDOW_WIDTH};
+use constants::background_color;
+use layout::GridLayout;
+use systems::{ai::AiPlayer, grid::SeedSetting, replay::LastReplay};
 use texture_atlas_backend::TextureAtlasBackend;
 
 mod assets;
 mod bundles;
 pub mod constants;
 mod core;
-pub mod game;
+mod layout;
+mod storage;
 mod systems;
 mod texture_atlas_backend;
 
@@ -24,14 +32,15 @@ fn main() {
             DefaultPlugins
                 .set(WindowPlugin {
                     primary_window: Some(Window {
-                        resolution: (GAME_WINDOW_WIDTH, GAME_WINDOW_HEIGHT).into(),
+                        resolution: GridLayout::default().window_size().into(),
                         canvas: Some("#bevy".to_string()),
                         ..Default::default()
                     }),
                     ..Default::default()
                 })
                 // This is a pixel-art game
-                .set(ImagePlugin::default_nearest()),
+                .set(ImagePlugin::default_nearest())
+                .set(asset_plugin()),
         )
         .insert_resource(ClearColor(background_color()))
         // MSAA on the tiles has a weird "1 pixel gap" glitch.
@@ -50,6 +59,58 @@ fn main() {
         )
         .add_plugins(TextureAtlasBackend)
         .add_plugins(EasingsPlugin)
+        .insert_resource(SeedSetting(
+            arg_value("--seed").and_then(|seed| seed.parse().ok()),
+        ))
+        .insert_resource(LastReplay(arg_value("--replay").and_then(load_replay)))
+        .insert_resource(AiPlayer::new(
+            arg_value("--ai")
+                .and_then(parse_strategy)
+                .unwrap_or_default(),
+        ))
         .add_plugins(core::GamePlugin)
         .run()
 }
+
+/// Assets are reloaded when they change on disk, except on the Web
+fn asset_plugin() -> AssetPlugin {
+    AssetPlugin {
+        #[cfg(not(target_arch = "wasm32"))]
+        watch_for_changes: bevy::asset::ChangeWatcher::with_delay(
+            std::time::Duration::from_millis(200),
+        ),
+        ..Default::default()
+    }
+}
+
+/// Reads the value of a command line option, e.g. the seed of the first run from `--seed <u64>`
+fn arg_value(name: &str) -> Option<String> {
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == name {
+            return args.next();
+        }
+    }
+    None
+}
+
+/// A replay given with `--replay <file>` can be watched from the title screen
+fn load_replay(path: String) -> Option<Replay> {
+    let replay = std::fs::read_to_string(&path)
+        .map_err(|err| err.to_string())
+        .and_then(|text| text.parse::<Replay>().map_err(|err| err.to_string()));
+    match replay {
+        Ok(replay) => Some(replay),
+        Err(err) => {
+            eprintln!("Failed to load replay {path}: {err}");
+            None
+        }
+    }
+}
+
+/// The AI used for hints and autoplay can be picked with `--ai <strategy>`
+fn parse_strategy(name: String) -> Option<Strategy> {
+    name.parse()
+        .map_err(|err| eprintln!("Ignoring --ai: {err}"))
+        .ok()
+}
@@ -0,0 +1,90 @@
+//! Small text files that outlive a run : a file in the platform's data directory on native
+//! builds, and an entry of the browser's local storage on the web.
+
+use am4lgam8_core::date::Date;
+
+#[cfg(not(target_arch = "wasm32"))]
+fn data_file(name: &str) -> Option<std::path::PathBuf> {
+    directories::ProjectDirs::from("", "", "am4lgam8").map(|dirs| dirs.data_dir().join(name))
+}
+
+/// Returns None if nothing was saved under this name yet
+#[cfg(not(target_arch = "wasm32"))]
+pub fn load(name: &str) -> Option<String> {
+    std::fs::read_to_string(data_file(name)?).ok()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn save(name: &str, contents: &str) {
+    let Some(path) = data_file(name) else {
+        bevy::log::warn!("No data directory to save {name} to");
+        return;
+    };
+    let result = path
+        .parent()
+        .map_or(Ok(()), std::fs::create_dir_all)
+        .and_then(|_| std::fs::write(&path, contents));
+    if let Err(err) = result {
+        bevy::log::warn!("Failed to save {}: {err}", path.display());
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn remove(name: &str) {
+    if let Some(path) = data_file(name).filter(|path| path.exists()) {
+        if let Err(err) = std::fs::remove_file(&path) {
+            bevy::log::warn!("Failed to remove {}: {err}", path.display());
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn local_storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.local_storage().ok()?
+}
+
+/// Returns None if nothing was saved under this name yet
+#[cfg(target_arch = "wasm32")]
+pub fn load(name: &str) -> Option<String> {
+    local_storage()?
+        .get_item(&format!("am4lgam8/{name}"))
+        .ok()?
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn save(name: &str, contents: &str) {
+    let saved = local_storage()
+        .map(|storage| {
+            storage
+                .set_item(&format!("am4lgam8/{name}"), contents)
+                .is_ok()
+        })
+        .unwrap_or_default();
+    if !saved {
+        bevy::log::warn!("Failed to save {name} to the local storage");
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn remove(name: &str) {
+    if let Some(storage) = local_storage() {
+        let _ = storage.remove_item(&format!("am4lgam8/{name}"));
+    }
+}
+
+/// Seconds since the Unix epoch
+pub fn timestamp() -> i64 {
+    #[cfg(not(target_arch = "wasm32"))]
+    let seconds = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|duration| duration.as_secs() as i64)
+        .unwrap_or_default();
+    #[cfg(target_arch = "wasm32")]
+    let seconds = (js_sys::Date::now() / 1000.) as i64;
+    seconds
+}
+
+/// Today's date, in UTC
+pub fn today() -> Date {
+    Date::from_days_since_epoch(timestamp().div_euclid(24 * 60 * 60))
+}
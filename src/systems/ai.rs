@@ -0,0 +1,159 @@
+use std::time::Duration;
+
+use bevy::{
+    prelude::{
+        Changed, Component, DetectChanges, EventWriter, GamepadButton, GamepadButtonType, Gamepads,
+        Input, KeyCode, Parent, Query, Res, ResMut, Resource, With,
+    },
+    text::Text,
+    time::{Time, Timer, TimerMode},
+    ui::Interaction,
+};
+
+use am4lgam8_core::{
+    ai::{AiMove, Strategy},
+    grid::TileGrid,
+    history::MoveHistory,
+    rng::{random_seed, rng_from_seed, GameRng},
+};
+
+use super::{movables::RequestMoveEvent, preview::MovePreview};
+
+// Time between two moves of the autoplay, long enough for the animations to play out
+const AUTOPLAY_INTERVAL: f32 = 0.6;
+
+// Components
+
+#[derive(Component)]
+pub struct HintButton;
+
+#[derive(Component)]
+pub struct AutoplayButton;
+
+// Resources
+
+/// The computer player, which can suggest a move or play the run by itself
+#[derive(Debug, Resource)]
+pub struct AiPlayer {
+    strategy: Strategy,
+    autoplay: bool,
+    timer: Timer,
+    rng: GameRng,
+}
+
+impl AiPlayer {
+    pub fn new(strategy: Strategy) -> Self {
+        AiPlayer {
+            strategy,
+            autoplay: false,
+            timer: Timer::new(
+                Duration::from_secs_f32(AUTOPLAY_INTERVAL),
+                TimerMode::Repeating,
+            ),
+            rng: rng_from_seed(random_seed()),
+        }
+    }
+
+    fn suggest(&mut self, tile_grid: &TileGrid) -> Option<AiMove> {
+        self.strategy.choose_move(tile_grid, &mut self.rng)
+    }
+}
+
+impl Default for AiPlayer {
+    fn default() -> Self {
+        AiPlayer::new(Strategy::default())
+    }
+}
+
+// Systems
+
+pub fn stop_autoplay(mut ai_player: ResMut<AiPlayer>) {
+    ai_player.autoplay = false;
+    ai_player.timer.reset();
+}
+
+/// H (North on a gamepad, or the hint button) previews the move the AI would play, and P (West, or
+/// the auto button) lets the AI play the run. Either makes the run a practice run.
+pub fn handle_ai_input(
+    keys: Res<Input<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    buttons: Res<Input<GamepadButton>>,
+    hint_buttons: Query<&Interaction, (Changed<Interaction>, With<HintButton>)>,
+    autoplay_buttons: Query<&Interaction, (Changed<Interaction>, With<AutoplayButton>)>,
+    mut ai_player: ResMut<AiPlayer>,
+    mut preview: ResMut<MovePreview>,
+    mut history: ResMut<MoveHistory>,
+    tile_grid: Res<TileGrid>,
+) {
+    let pressed = |interaction: &Interaction| interaction == &Interaction::Pressed;
+    let pad_pressed = |button_type| {
+        gamepads
+            .iter()
+            .any(|gamepad| buttons.just_pressed(GamepadButton::new(gamepad, button_type)))
+    };
+    let hint = keys.just_pressed(KeyCode::H)
+        || pad_pressed(GamepadButtonType::North)
+        || hint_buttons.iter().any(pressed);
+    let autoplay = keys.just_pressed(KeyCode::P)
+        || pad_pressed(GamepadButtonType::West)
+        || autoplay_buttons.iter().any(pressed);
+
+    if autoplay {
+        ai_player.autoplay = !ai_player.autoplay;
+        ai_player.timer.reset();
+        if ai_player.autoplay {
+            history.mark_practice();
+        }
+    }
+    if hint && !ai_player.autoplay {
+        if let Some(AiMove {
+            source_coords,
+            move_direction,
+        }) = ai_player.suggest(&tile_grid)
+        {
+            preview.request = Some((source_coords, move_direction));
+            history.mark_practice();
+        }
+    }
+}
+
+/// Plays the moves chosen by the AI at a steady pace, like a replay
+pub fn play_autoplay(
+    time: Res<Time>,
+    mut ai_player: ResMut<AiPlayer>,
+    tile_grid: Res<TileGrid>,
+    mut move_event_tx: EventWriter<RequestMoveEvent>,
+) {
+    if !ai_player.autoplay {
+        return;
+    }
+    if !ai_player.timer.tick(time.delta()).just_finished() {
+        return;
+    }
+    if let Some(AiMove {
+        source_coords,
+        move_direction,
+    }) = ai_player.suggest(&tile_grid)
+    {
+        move_event_tx.send(RequestMoveEvent {
+            move_direction,
+            source_coords,
+        });
+    }
+}
+
+/// The auto button turns into a stop button while the AI plays
+pub fn update_autoplay_button(
+    ai_player: Res<AiPlayer>,
+    autoplay_buttons: Query<(), With<AutoplayButton>>,
+    mut labels: Query<(&Parent, &mut Text)>,
+) {
+    if !ai_player.is_changed() {
+        return;
+    }
+    for (parent, mut text) in labels.iter_mut() {
+        if autoplay_buttons.contains(parent.get()) {
+            text.sections[0].value = if ai_player.autoplay { "STOP" } else { "AUTO" }.to_string();
+        }
+    }
+}
@@ -6,7 +6,9 @@ use bevy::{
 };
 use bevy_easings::{Ease, EaseMethod};
 
-use crate::{constants::TILE_SIZE, game::grid::GridCoordinates};
+use am4lgam8_core::grid::GridCoordinates;
+
+use crate::constants::TILE_SIZE;
 
 use super::marked_for_deletion::MarkedForDeletion;
 
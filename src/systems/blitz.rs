@@ -0,0 +1,81 @@
+use bevy::{
+    prelude::{
+        Commands, DespawnRecursive, Entity, EventReader, NextState, Query, Res, ResMut, Resource,
+        With,
+    },
+    time::Time,
+};
+
+use am4lgam8_core::{
+    blitz::BlitzClock,
+    grid::{SpawnEvent, TileGrid},
+};
+
+use crate::{assets::GameAssets, bundles::tile::spawn_tile_type_bundle, core::GameState};
+
+use super::{tiles::ChainReactionEvent, OnPlayingScreen};
+
+// Resources
+
+/// The clock of the run, if it is a blitz
+#[derive(Debug, Default, Resource)]
+pub struct ActiveBlitz(pub Option<BlitzClock>);
+
+// Conditions
+
+pub fn no_blitz(blitz: Res<ActiveBlitz>) -> bool {
+    blitz.0.is_none()
+}
+
+// Systems
+
+/// Runs the clock, and spawns the tiles it is due, whether moves are played or not
+pub fn tick_blitz_clock(
+    mut commands: Commands,
+    assets: Res<GameAssets>,
+    time: Res<Time>,
+    mut blitz: ResMut<ActiveBlitz>,
+    mut tile_grid: ResMut<TileGrid>,
+) {
+    let Some(clock) = &mut blitz.0 else {
+        return;
+    };
+    for _ in 0..clock.tick(time.delta()) {
+        // A full grid is left for the game over check to find
+        if let Some(SpawnEvent { coords, tile_type }) = tile_grid.try_spawn_new_tile() {
+            spawn_tile_type_bundle(&mut commands, &assets, tile_type, coords.x, coords.y);
+        }
+    }
+}
+
+pub fn award_time_bonus(
+    mut chain_reaction_rx: EventReader<ChainReactionEvent>,
+    mut blitz: ResMut<ActiveBlitz>,
+) {
+    for ChainReactionEvent(chain_reaction) in chain_reaction_rx.iter() {
+        if let Some(clock) = &mut blitz.0 {
+            clock.add_bonus(chain_reaction);
+        }
+    }
+}
+
+/// The run is over once the clock runs out, like when the grid is stuck
+pub fn check_blitz_clock(
+    mut commands: Commands,
+    all_entities_on_screen: Query<Entity, With<OnPlayingScreen>>,
+    blitz: Res<ActiveBlitz>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if !blitz.0.as_ref().is_some_and(BlitzClock::is_over) {
+        return;
+    }
+    for entity in all_entities_on_screen.iter() {
+        commands.add(DespawnRecursive { entity });
+    }
+    next_state.set(GameState::GameOver);
+}
+
+/// Once the game over screen is left, the next run is not timed anymore
+pub fn finish_blitz(mut blitz: ResMut<ActiveBlitz>) {
+    blitz.0 = None;
+}
@@ -1,35 +1,68 @@
 use bevy::{
-    prelude::{Camera2dBundle, Commands, Res, State, Vec3},
+    prelude::{Camera2dBundle, Commands, Query, Res, State, With},
     render::camera::ScalingMode,
+    window::{PrimaryWindow, Window},
 };
 
-use crate::constants::{GAME_LOGIC_HEIGHT, GAME_LOGIC_WIDTH, GRID_SIZE, TILE_SIZE};
+use am4lgam8_core::grid::GridConfig;
+
 use crate::core::GameState;
+use crate::layout::GridLayout;
 
-use super::{game_over::OnGameOverScreen, title_screen::OnTitleScreen, OnPlayingScreen};
+use super::{
+    editor::{OnEditorScreen, EDITOR_LAYOUT},
+    game_over::OnGameOverScreen,
+    high_score_screen::OnHighScoreScreen,
+    level_select::OnLevelSelectScreen,
+    title_screen::OnTitleScreen,
+    OnPlayingScreen,
+};
+
+pub fn setup(
+    mut commands: Commands,
+    state: Res<State<GameState>>,
+    grid_config: Res<GridConfig>,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    // Only the playing field adapts to the grid size, the editor fits the biggest one and the menus
+    // are drawn for the default one
+    let layout = match state.get() {
+        GameState::Playing => GridLayout::new(*grid_config),
+        GameState::Editor => GridLayout::new(EDITOR_LAYOUT),
+        _ => GridLayout::default(),
+    };
+
+    let window_size = layout.window_size();
+    for mut window in windows.iter_mut() {
+        window.resolution.set(window_size.x, window_size.y);
+    }
 
-pub fn setup(mut commands: Commands, state: Res<State<GameState>>) {
     let mut camera_bundle = Camera2dBundle::default();
     camera_bundle.projection.scaling_mode = ScalingMode::Fixed {
-        width: GAME_LOGIC_WIDTH,
-        height: GAME_LOGIC_HEIGHT,
+        width: layout.logic_size.x,
+        height: layout.logic_size.y,
     };
-    camera_bundle.transform.translation = Vec3::new(
-        TILE_SIZE * (GRID_SIZE as f32 / 2. - 0.5),
-        TILE_SIZE * (GRID_SIZE as f32 / 2. - 0.5) - 3.,
-        0.,
-    );
+    camera_bundle.transform.translation = layout.center.extend(0.);
 
     match state.get() {
-        &GameState::TitleScreen => {
+        GameState::TitleScreen => {
             commands.spawn((camera_bundle, OnTitleScreen));
         }
-        &GameState::Playing => {
+        GameState::Playing => {
             commands.spawn((camera_bundle, OnPlayingScreen));
         }
-        &GameState::GameOver => {
+        GameState::GameOver => {
             commands.spawn((camera_bundle, OnGameOverScreen));
         }
+        GameState::HighScores => {
+            commands.spawn((camera_bundle, OnHighScoreScreen));
+        }
+        GameState::LevelSelect => {
+            commands.spawn((camera_bundle, OnLevelSelectScreen));
+        }
+        GameState::Editor => {
+            commands.spawn((camera_bundle, OnEditorScreen));
+        }
         _ => panic!("No camera is required on other states"),
     }
 }
@@ -0,0 +1,189 @@
+use bevy::prelude::{
+    default, BuildChildren, Children, Commands, Component, Query, Res, ResMut, Resource,
+    SpatialBundle, Transform, Vec2, Vec3, Visibility, With, Without,
+};
+use bevy_mod_picking::prelude::Pickable;
+
+use am4lgam8_core::{
+    grid::{GridCoordinates, MoveTileEvent, TileGrid},
+    moves::{MergeTilesEvent, MoveDirection, ValidEvent, ValidatedEventQueue},
+};
+
+use crate::{
+    bundles::line::{line_sprite, tile_outline},
+    constants::{foreground_color, TILE_SIZE},
+};
+
+use super::{movables::RequestMoveEvent, OnPlayingScreen};
+
+/// Length, in logic pixels, of each arm of the cursor corners
+const CORNER_LENGTH: f32 = 4.;
+
+// Components
+
+#[derive(Component)]
+pub struct CursorOverlay;
+
+/// Outline drawn around the selected tile while it is grabbed
+#[derive(Component)]
+pub struct GrabOutline;
+
+// Resources
+
+/// Selection used to play with the keyboard or a gamepad. It stays hidden until one of them is
+/// used, so that it does not get in the way of players using the mouse.
+#[derive(Debug, Default, Resource)]
+pub struct SelectionCursor {
+    pub coords: GridCoordinates,
+    pub grabbed: bool,
+    pub visible: bool,
+}
+
+/// What the player asked the cursor to do, whatever the input device
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CursorAction {
+    /// Moves the cursor, or pushes the grabbed tile
+    Direction(MoveDirection),
+    /// Grabs the selected tile, or lets go of it if it is already grabbed
+    ToggleGrab,
+    Release,
+}
+
+impl SelectionCursor {
+    /// Applies the action to the cursor, returning the move to request if a grabbed tile was
+    /// pushed
+    pub fn apply(
+        &mut self,
+        action: CursorAction,
+        tile_grid: &TileGrid,
+    ) -> Option<RequestMoveEvent> {
+        // The first action only reveals the cursor
+        if !self.visible {
+            self.visible = true;
+            return None;
+        }
+
+        match action {
+            CursorAction::Release => self.grabbed = false,
+            CursorAction::ToggleGrab => {
+                self.grabbed = !self.grabbed
+                    && tile_grid
+                        .get(&self.coords)
+                        .is_some_and(|tile_type| tile_type.is_movable());
+            }
+            CursorAction::Direction(dir) if self.grabbed => {
+                self.grabbed = false;
+                let target = target_of_move(tile_grid, &self.coords, dir)?;
+                let source_coords = std::mem::replace(&mut self.coords, target);
+                return Some(RequestMoveEvent {
+                    move_direction: dir,
+                    source_coords,
+                });
+            }
+            CursorAction::Direction(dir) => {
+                if let Some(coords) = tile_grid.next_selectable_coords(&self.coords, dir) {
+                    self.coords = coords;
+                }
+            }
+        }
+        None
+    }
+}
+
+/// Where the tile at `coords` ends up if it is moved towards `dir`, or None if it can't move
+fn target_of_move(
+    tile_grid: &TileGrid,
+    coords: &GridCoordinates,
+    dir: MoveDirection,
+) -> Option<GridCoordinates> {
+    let candidate_coords = coords.candidate_coords_for_dir(dir, tile_grid.config());
+    match ValidatedEventQueue::validate_move(tile_grid, candidate_coords, dir) {
+        ValidatedEventQueue::InvalidMove => None,
+        ValidatedEventQueue::ValidMove(events) => {
+            let target = events.into_iter().find_map(|event| match event {
+                ValidEvent::Move(MoveTileEvent { source, target })
+                | ValidEvent::Merge(MergeTilesEvent { source, target, .. })
+                    if &source == coords =>
+                {
+                    Some(target)
+                }
+                _ => None,
+            });
+            // The tile exploded, the cursor stays where it was
+            Some(target.unwrap_or_else(|| coords.clone()))
+        }
+    }
+}
+
+// Systems
+
+pub fn reset_cursor(mut cursor: ResMut<SelectionCursor>) {
+    *cursor = SelectionCursor::default();
+}
+
+pub fn spawn_cursor(mut commands: Commands) {
+    let half = TILE_SIZE / 2.;
+    let arm = CORNER_LENGTH;
+
+    commands
+        .spawn((
+            SpatialBundle {
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            CursorOverlay,
+            OnPlayingScreen,
+        ))
+        .with_children(|parent| {
+            for (sx, sy) in [(-1., -1.), (-1., 1.), (1., -1.), (1., 1.)] {
+                // Horizontal then vertical arm of the corner bracket
+                let corner = Vec2::new(sx * (half - 0.5), sy * (half - 0.5));
+                let arms = [
+                    (
+                        corner - Vec2::new(sx * (arm - 1.) / 2., 0.),
+                        Vec2::new(arm, 1.),
+                    ),
+                    (
+                        corner - Vec2::new(0., sy * (arm - 1.) / 2.),
+                        Vec2::new(1., arm),
+                    ),
+                ];
+                for (center, size) in arms {
+                    parent.spawn((
+                        line_sprite(center, size, foreground_color()),
+                        Pickable::IGNORE,
+                    ));
+                }
+            }
+
+            for line in tile_outline(foreground_color()) {
+                parent.spawn((line, GrabOutline, Pickable::IGNORE));
+            }
+        });
+}
+
+pub fn update_cursor_overlay(
+    cursor: Res<SelectionCursor>,
+    mut overlays: Query<(&mut Transform, &mut Visibility, &Children), With<CursorOverlay>>,
+    mut outlines: Query<&mut Visibility, (With<GrabOutline>, Without<CursorOverlay>)>,
+) {
+    for (mut transform, mut visibility, children) in overlays.iter_mut() {
+        let GridCoordinates { x, y } = cursor.coords;
+        transform.translation = Vec3::new(x as f32 * TILE_SIZE, y as f32 * TILE_SIZE, 2.);
+        *visibility = if cursor.visible {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+
+        for child in children.iter() {
+            if let Ok(mut outline_visibility) = outlines.get_mut(*child) {
+                *outline_visibility = if cursor.grabbed {
+                    Visibility::Inherited
+                } else {
+                    Visibility::Hidden
+                };
+            }
+        }
+    }
+}
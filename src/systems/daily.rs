@@ -0,0 +1,102 @@
+use bevy::prelude::{Res, ResMut, Resource};
+
+use am4lgam8_core::{
+    daily::{daily_seed, DailyHistory, DailyResult},
+    date::Date,
+    history::MoveHistory,
+    score::GameScore,
+};
+
+use crate::storage;
+
+use super::replay::ReplayRecorder;
+
+const DAILY_HISTORY_FILE: &str = "daily.txt";
+
+// Resources
+
+/// The daily challenge being played, if the run is one
+#[derive(Debug, Default, Resource)]
+pub struct ActiveDaily {
+    pub date: Option<Date>,
+}
+
+impl ActiveDaily {
+    pub fn seed(&self) -> Option<u64> {
+        self.date.as_ref().map(daily_seed)
+    }
+
+    /// Starts today's run, unless it was played already. Leaving the run still uses up the
+    /// attempt, as it is recorded right away.
+    pub fn start(&mut self, today: Date, history: &mut DailyHistory) -> bool {
+        if history.has_played(&today) {
+            return false;
+        }
+        history.record(DailyResult {
+            date: today,
+            score: 0,
+            moves_played: 0,
+        });
+        save_daily_history(history);
+
+        self.date = Some(today);
+        true
+    }
+}
+
+pub fn load_daily_history() -> DailyHistory {
+    let Some(text) = storage::load(DAILY_HISTORY_FILE) else {
+        return DailyHistory::default();
+    };
+    text.parse().unwrap_or_else(|err| {
+        bevy::log::warn!("Ignoring the saved daily history: {err}");
+        DailyHistory::default()
+    })
+}
+
+fn save_daily_history(history: &DailyHistory) {
+    storage::save(DAILY_HISTORY_FILE, &history.to_string());
+}
+
+// Conditions
+
+pub fn no_daily(daily: Res<ActiveDaily>) -> bool {
+    daily.date.is_none()
+}
+
+// Systems
+
+/// Keeps the result of the day up to date after each turn. Undo or the AI end the attempt: the
+/// score reached before them is the one that counts.
+pub fn track_daily_result(
+    daily: Res<ActiveDaily>,
+    score: Res<GameScore>,
+    recorder: Res<ReplayRecorder>,
+    move_history: Res<MoveHistory>,
+    mut history: ResMut<DailyHistory>,
+) {
+    let Some(date) = daily.date else {
+        return;
+    };
+    if move_history.is_practice() {
+        return;
+    }
+
+    let result = DailyResult {
+        date,
+        score: score.get(),
+        moves_played: recorder
+            .replay()
+            .map(|replay| replay.moves.len() as u32)
+            .unwrap_or_default(),
+    };
+    if history.result(&date) != Some(&result) {
+        history.record(result);
+        save_daily_history(&history);
+    }
+}
+
+/// Once the game over screen is left, the next run is not the daily one anymore
+pub fn finish_daily(mut daily: ResMut<ActiveDaily>) {
+    daily.date = None;
+}
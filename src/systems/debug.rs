@@ -1,47 +1,48 @@
 use bevy::prelude::{Commands, Res};
 
+use am4lgam8_core::tile::{CoinValue, TileType};
+
 use crate::{
     assets::GameAssets,
     bundles::tile::spawn_tile_type_bundle,
-    game::tile::{CoinValue, TileType},
 };
 
 pub fn setup_debug(mut commands: Commands, assets: Res<GameAssets>) {
     spawn_tile_type_bundle(
         &mut commands,
-        assets.tileset.clone(),
-        TileType::Coin(CoinValue::One),
+        &assets,
+        TileType::Coin(CoinValue::ONE),
         0,
         0,
     );
     spawn_tile_type_bundle(
         &mut commands,
-        assets.tileset.clone(),
-        TileType::Coin(CoinValue::One),
+        &assets,
+        TileType::Coin(CoinValue::ONE),
         3,
         0,
     );
     spawn_tile_type_bundle(
         &mut commands,
-        assets.tileset.clone(),
-        TileType::Coin(CoinValue::Two),
+        &assets,
+        TileType::Coin(CoinValue::TWO),
         3,
         1,
     );
     spawn_tile_type_bundle(
         &mut commands,
-        assets.tileset.clone(),
-        TileType::Coin(CoinValue::Four),
+        &assets,
+        TileType::Coin(CoinValue::FOUR),
         3,
         2,
     );
     spawn_tile_type_bundle(
         &mut commands,
-        assets.tileset.clone(),
-        TileType::Coin(CoinValue::Eight),
+        &assets,
+        TileType::Coin(CoinValue::EIGHT),
         3,
         3,
     );
-    spawn_tile_type_bundle(&mut commands, assets.tileset.clone(), TileType::Bomb, 1, 2);
-    spawn_tile_type_bundle(&mut commands, assets.tileset.clone(), TileType::Bomb, 1, 3);
+    spawn_tile_type_bundle(&mut commands, &assets, TileType::Bomb, 1, 2);
+    spawn_tile_type_bundle(&mut commands, &assets, TileType::Bomb, 1, 3);
 }
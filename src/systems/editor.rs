@@ -0,0 +1,674 @@
+use bevy::{
+    prelude::{
+        BuildChildren, ButtonBundle, Changed, ChildBuilder, Color, Commands, Component,
+        DespawnRecursive, DetectChanges, Entity, EventReader, Input, KeyCode, NextState,
+        NodeBundle, Or, Query, Res, ResMut, Resource, SpatialBundle, TextBundle, Transform, Vec2,
+        Vec3, With,
+    },
+    sprite::{SpriteSheetBundle, TextureAtlasSprite},
+    tasks::{AsyncComputeTaskPool, Task},
+    text::{Text, TextStyle},
+    ui::{
+        AlignItems, BackgroundColor, FlexDirection, FlexWrap, Interaction, PositionType, Style,
+        UiRect, Val,
+    },
+    window::ReceivedCharacter,
+};
+use bevy_mod_picking::{
+    prelude::{Click, Drag, DragEnd, Listener, On, Pickable, Pointer, PointerButton},
+    PickableBundle,
+};
+use futures_lite::future;
+
+use am4lgam8_core::{
+    grid::{GridConfig, GridCoordinates},
+    puzzle::{Puzzle, PuzzleRun},
+    solver::{solve, SolverOutcome, DEFAULT_MAX_STATES},
+    spawn::tile_name,
+    tile::{CoinValue, TileType},
+};
+
+use crate::{
+    assets::GameAssets,
+    bundles::{line::tile_outline, tile::spawn_tile_type_bundle},
+    constants::{background_color, foreground_color, TILE_SIZE},
+    core::GameState,
+    layout::GridLayout,
+    storage,
+};
+
+use super::{grid::EndlessConfig, puzzle::ActivePuzzle, OnPlayingScreen};
+
+/// Where the puzzle being edited is saved, in the puzzle file format
+const EDITOR_FILE: &str = "editor.puzzle.ron";
+/// As big as the biggest grid of the title screen
+const MAX_EDITOR_SIZE: i32 = 9;
+/// The view fits the biggest grid, the palette below it, and the panel on its right
+pub const EDITOR_LAYOUT: GridConfig = GridConfig {
+    width: MAX_EDITOR_SIZE + 6,
+    height: MAX_EDITOR_SIZE + 1,
+};
+/// Width of the panel, in logic pixels
+const PANEL_WIDTH: f32 = 100.;
+/// The palette is laid out like a row of tiles, right below the grid
+const PALETTE_ROW: i32 = -1;
+const MAX_NAME_LENGTH: usize = 24;
+
+// Components
+
+#[derive(Component)]
+pub struct OnEditorScreen;
+
+/// An empty cell of the grid, clicked to paint it. The tiles drawn over it let the clicks through.
+#[derive(Component)]
+pub struct EditorCell;
+
+#[derive(Component)]
+pub struct EditorTile;
+
+#[derive(Component)]
+pub struct PaletteTile;
+
+/// Outline around the tile of the palette being painted
+#[derive(Component)]
+pub struct BrushOutline;
+
+/// The solver, running on the task pool for the puzzle as it was when SOLVE was pressed.
+/// Despawning it drops the task, which cancels it.
+#[derive(Component)]
+pub struct SolverTask {
+    puzzle: Puzzle,
+    task: Task<SolverOutcome>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Component)]
+pub enum EditorButton {
+    Narrower,
+    Wider,
+    Shorter,
+    Taller,
+    NextGoal,
+    LowerTarget,
+    RaiseTarget,
+    FewerMoves,
+    MoreMoves,
+    AddSpawn,
+    RemoveSpawn,
+    Test,
+    Solve,
+    Save,
+    Back,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Component)]
+pub enum EditorLabel {
+    Name,
+    Size,
+    Goal,
+    Moves,
+    Spawns,
+    Message,
+}
+
+// Resources
+
+/// The puzzle being edited. It is kept while it is tested, and between two visits.
+#[derive(Debug, Resource)]
+pub struct PuzzleEditor {
+    pub puzzle: Puzzle,
+    /// Painted with the left button, the right one empties the cell
+    pub brush: TileType,
+    /// The puzzle is played from the editor, and goes back to it once over
+    pub testing: bool,
+    pub message: Option<String>,
+}
+
+impl PuzzleEditor {
+    fn new(puzzle: Puzzle) -> Self {
+        PuzzleEditor {
+            puzzle,
+            brush: palette()[0],
+            testing: false,
+            message: None,
+        }
+    }
+}
+
+/// The puzzle saved from the editor, also listed in the level select
+pub fn load_saved_puzzle() -> Option<Puzzle> {
+    storage::load(EDITOR_FILE).and_then(|text| {
+        ron::de::from_str(&text)
+            .map_err(|err| bevy::log::warn!("Ignoring the saved puzzle: {err}"))
+            .ok()
+    })
+}
+
+/// Starts from the last saved puzzle, or from an empty grid
+pub fn load_editor_draft() -> PuzzleEditor {
+    PuzzleEditor::new(
+        load_saved_puzzle().unwrap_or_else(|| Puzzle::new("My puzzle", GridConfig::default())),
+    )
+}
+
+fn palette() -> [TileType; 9] {
+    [
+        TileType::Coin(CoinValue::ONE),
+        TileType::Coin(CoinValue::TWO),
+        TileType::Coin(CoinValue::FOUR),
+        TileType::Coin(CoinValue::EIGHT),
+        TileType::Coin(CoinValue::from_tier(4).unwrap()),
+        TileType::Bomb,
+        TileType::BigBomb,
+        TileType::Block,
+        TileType::Wall,
+    ]
+}
+
+fn palette_position(brush: TileType) -> Vec3 {
+    let x = palette()
+        .iter()
+        .position(|tile_type| *tile_type == brush)
+        .unwrap_or_default();
+    Vec3::new(x as f32 * TILE_SIZE, PALETTE_ROW as f32 * TILE_SIZE, 0.5)
+}
+
+fn label_text(label: EditorLabel, editor: &PuzzleEditor) -> String {
+    let puzzle = &editor.puzzle;
+    match label {
+        EditorLabel::Name => format!("NAME {}_", puzzle.name),
+        EditorLabel::Size => format!("SIZE {}x{}", puzzle.config.width, puzzle.config.height),
+        EditorLabel::Goal => format!("GOAL {}", puzzle.goal),
+        EditorLabel::Moves => format!("MOVES {}", puzzle.moves),
+        EditorLabel::Spawns if puzzle.spawns.is_empty() => "SPAWNS none".to_string(),
+        EditorLabel::Spawns => {
+            let names: Vec<String> = puzzle.spawns.iter().map(tile_name).collect();
+            format!("SPAWNS {}", names.join(" "))
+        }
+        EditorLabel::Message => editor.message.clone().unwrap_or_default(),
+    }
+}
+
+/// Tiles are spawned for the playing field, they are moved to the editor and only clicked
+fn into_editor_tile(commands: &mut Commands, entity: Entity) {
+    commands
+        .entity(entity)
+        .remove::<(OnPlayingScreen, On<Pointer<Drag>>, On<Pointer<DragEnd>>)>()
+        .insert(OnEditorScreen);
+}
+
+fn spawn_tile(commands: &mut Commands, assets: &GameAssets, tile_type: TileType, x: i32, y: i32) {
+    let entity = spawn_tile_type_bundle(commands, assets, tile_type, x, y);
+    into_editor_tile(commands, entity);
+    commands
+        .entity(entity)
+        .insert((EditorTile, Pickable::IGNORE));
+}
+
+/// The cells of the grid and the tiles on them
+fn spawn_grid(commands: &mut Commands, assets: &GameAssets, puzzle: &Puzzle) {
+    let GridConfig { width, height } = puzzle.config;
+    for x in 0..width {
+        for y in 0..height {
+            commands
+                .spawn((
+                    SpriteSheetBundle {
+                        texture_atlas: assets.tileset.clone(),
+                        // Only there to be picked
+                        sprite: TextureAtlasSprite {
+                            color: Color::NONE,
+                            custom_size: Some(Vec2::splat(TILE_SIZE)),
+                            ..Default::default()
+                        },
+                        transform: Transform::from_xyz(
+                            x as f32 * TILE_SIZE,
+                            y as f32 * TILE_SIZE,
+                            -0.5,
+                        ),
+                        ..Default::default()
+                    },
+                    GridCoordinates { x, y },
+                    EditorCell,
+                    PickableBundle::default(),
+                    On::<Pointer<Click>>::run(paint_cell),
+                    OnEditorScreen,
+                ))
+                .with_children(|parent| {
+                    for line in tile_outline(foreground_color().with_a(0.15)) {
+                        parent.spawn(line);
+                    }
+                });
+        }
+    }
+    for (coords, tile_type) in &puzzle.tiles {
+        spawn_tile(commands, assets, *tile_type, coords.x, coords.y);
+    }
+}
+
+fn spawn_button(
+    parent: &mut ChildBuilder,
+    text_style: &TextStyle,
+    label: &str,
+    button: EditorButton,
+) {
+    parent
+        .spawn((
+            ButtonBundle {
+                style: Style {
+                    margin: UiRect::right(Val::Px(text_style.font_size / 2.)),
+                    padding: UiRect::horizontal(Val::Px(text_style.font_size / 5.)),
+                    ..Default::default()
+                },
+                background_color: BackgroundColor(foreground_color()),
+                ..Default::default()
+            },
+            button,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                label,
+                TextStyle {
+                    color: background_color(),
+                    ..text_style.clone()
+                },
+            ));
+        });
+}
+
+pub fn setup(mut commands: Commands, assets: Res<GameAssets>, editor: Res<PuzzleEditor>) {
+    spawn_grid(&mut commands, &assets, &editor.puzzle);
+
+    for (x, tile_type) in palette().into_iter().enumerate() {
+        let entity =
+            spawn_tile_type_bundle(&mut commands, &assets, tile_type, x as i32, PALETTE_ROW);
+        into_editor_tile(&mut commands, entity);
+        commands.entity(entity).insert((
+            PaletteTile,
+            PickableBundle::default(),
+            On::<Pointer<Click>>::run(pick_brush),
+        ));
+    }
+    commands
+        .spawn((
+            SpatialBundle::from_transform(Transform::from_translation(palette_position(
+                editor.brush,
+            ))),
+            BrushOutline,
+            OnEditorScreen,
+        ))
+        .with_children(|parent| {
+            for line in tile_outline(foreground_color()) {
+                parent.spawn(line);
+            }
+        });
+
+    // The panel, on the right of the grid
+    let scale = GridLayout::new(EDITOR_LAYOUT).pixel_scale;
+    let text_style = TextStyle {
+        font_size: 5. * scale,
+        color: foreground_color(),
+        font: assets.joystix.clone(),
+    };
+    let row = || NodeBundle {
+        style: Style {
+            flex_direction: FlexDirection::Row,
+            margin: UiRect::bottom(Val::Px(4. * scale)),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let label = |parent: &mut ChildBuilder, label| {
+        parent.spawn((
+            TextBundle::from_section(label_text(label, &editor), text_style.clone()).with_style(
+                Style {
+                    margin: UiRect::bottom(Val::Px(scale)),
+                    ..Default::default()
+                },
+            ),
+            label,
+        ));
+    };
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(0.),
+                    right: Val::Px(0.),
+                    width: Val::Px(PANEL_WIDTH * scale),
+                    height: Val::Percent(100.),
+                    padding: UiRect::all(Val::Px(4. * scale)),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Start,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            OnEditorScreen,
+        ))
+        .with_children(|parent| {
+            label(parent, EditorLabel::Name);
+            parent.spawn(
+                TextBundle::from_section(
+                    "Type to rename",
+                    TextStyle {
+                        font_size: 4. * scale,
+                        ..text_style.clone()
+                    },
+                )
+                .with_style(Style {
+                    margin: UiRect::bottom(Val::Px(4. * scale)),
+                    ..Default::default()
+                }),
+            );
+
+            let sections = [
+                (
+                    EditorLabel::Size,
+                    vec![
+                        ("W-", EditorButton::Narrower),
+                        ("W+", EditorButton::Wider),
+                        ("H-", EditorButton::Shorter),
+                        ("H+", EditorButton::Taller),
+                    ],
+                ),
+                (
+                    EditorLabel::Goal,
+                    vec![
+                        ("NEXT", EditorButton::NextGoal),
+                        ("-", EditorButton::LowerTarget),
+                        ("+", EditorButton::RaiseTarget),
+                    ],
+                ),
+                (
+                    EditorLabel::Moves,
+                    vec![
+                        ("-", EditorButton::FewerMoves),
+                        ("+", EditorButton::MoreMoves),
+                    ],
+                ),
+                (
+                    EditorLabel::Spawns,
+                    vec![
+                        ("ADD", EditorButton::AddSpawn),
+                        ("DEL", EditorButton::RemoveSpawn),
+                    ],
+                ),
+            ];
+            for (section_label, buttons) in sections {
+                label(parent, section_label);
+                parent.spawn(row()).with_children(|parent| {
+                    for (text, button) in buttons {
+                        spawn_button(parent, &text_style, text, button);
+                    }
+                });
+            }
+
+            parent.spawn(row()).with_children(|parent| {
+                spawn_button(parent, &text_style, "TEST", EditorButton::Test);
+                spawn_button(parent, &text_style, "SOLVE", EditorButton::Solve);
+            });
+            parent.spawn(row()).with_children(|parent| {
+                spawn_button(parent, &text_style, "SAVE", EditorButton::Save);
+                spawn_button(parent, &text_style, "BACK", EditorButton::Back);
+            });
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_wrap: FlexWrap::Wrap,
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                })
+                .with_children(|parent| label(parent, EditorLabel::Message));
+        });
+}
+
+fn solver_text(puzzle: &Puzzle, outcome: SolverOutcome) -> String {
+    match outcome {
+        SolverOutcome::Solved(moves) => {
+            let moves: Vec<String> = moves
+                .iter()
+                .map(|ai_move| {
+                    let GridCoordinates { x, y } = ai_move.source_coords;
+                    format!("{x},{y} {:?}", ai_move.move_direction)
+                })
+                .collect();
+            format!(
+                "Solvable in {} of {} moves: {}",
+                moves.len(),
+                puzzle.moves,
+                moves.join(", ")
+            )
+        }
+        SolverOutcome::Unsolvable => format!("No solution in {} moves", puzzle.moves),
+        SolverOutcome::GaveUp(states) => format!("Gave up after {states} grids"),
+    }
+}
+
+// Picking handlers
+
+/// The left button paints the cell with the brush, the right one empties it
+fn paint_cell(
+    click: Listener<Pointer<Click>>,
+    mut commands: Commands,
+    assets: Res<GameAssets>,
+    cells: Query<&GridCoordinates, With<EditorCell>>,
+    tiles: Query<(Entity, &GridCoordinates), With<EditorTile>>,
+    mut editor: ResMut<PuzzleEditor>,
+) {
+    let Ok(coords) = cells.get(click.target) else {
+        return;
+    };
+    let tile_type = match click.button {
+        PointerButton::Primary => Some(editor.brush),
+        PointerButton::Secondary => None,
+        PointerButton::Middle => return,
+    };
+    if editor.puzzle.tile(coords) == tile_type {
+        return;
+    }
+
+    editor.puzzle.set_tile(coords, tile_type);
+    for (entity, _) in tiles
+        .iter()
+        .filter(|(_, tile_coords)| *tile_coords == coords)
+    {
+        commands.add(DespawnRecursive { entity });
+    }
+    if let Some(tile_type) = tile_type {
+        spawn_tile(&mut commands, &assets, tile_type, coords.x, coords.y);
+    }
+}
+
+fn pick_brush(
+    click: Listener<Pointer<Click>>,
+    palette_tiles: Query<&TileType, With<PaletteTile>>,
+    mut editor: ResMut<PuzzleEditor>,
+) {
+    if let Ok(tile_type) = palette_tiles.get(click.target) {
+        editor.brush = *tile_type;
+    }
+}
+
+// Systems
+
+pub fn update_brush_outline(
+    editor: Res<PuzzleEditor>,
+    mut outlines: Query<&mut Transform, With<BrushOutline>>,
+) {
+    if !editor.is_changed() {
+        return;
+    }
+    for mut transform in outlines.iter_mut() {
+        transform.translation = palette_position(editor.brush);
+    }
+}
+
+/// Shows what the solver found, unless the puzzle was edited since
+pub fn poll_solver(
+    mut commands: Commands,
+    mut tasks: Query<(Entity, &mut SolverTask)>,
+    mut editor: ResMut<PuzzleEditor>,
+) {
+    for (entity, mut solver) in tasks.iter_mut() {
+        if solver.puzzle != editor.puzzle {
+            commands.add(DespawnRecursive { entity });
+            editor.message = None;
+            continue;
+        }
+        let Some(outcome) = future::block_on(future::poll_once(&mut solver.task)) else {
+            continue;
+        };
+        editor.message = Some(solver_text(&solver.puzzle, outcome));
+        commands.add(DespawnRecursive { entity });
+    }
+}
+
+pub fn update_labels(editor: Res<PuzzleEditor>, mut labels: Query<(&mut Text, &EditorLabel)>) {
+    if !editor.is_changed() {
+        return;
+    }
+    for (mut text, label) in labels.iter_mut() {
+        text.sections[0].value = label_text(*label, &editor);
+    }
+}
+
+/// Typing renames the puzzle, the name being what identifies it in the records
+pub fn type_name(
+    mut received_characters: EventReader<ReceivedCharacter>,
+    keys: Res<Input<KeyCode>>,
+    mut editor: ResMut<PuzzleEditor>,
+) {
+    let mut name = editor.puzzle.name.clone();
+    for event in received_characters.iter() {
+        if !event.char.is_control() && name.chars().count() < MAX_NAME_LENGTH {
+            name.push(event.char);
+        }
+    }
+    if keys.just_pressed(KeyCode::Back) {
+        name.pop();
+    }
+    if name != editor.puzzle.name {
+        editor.puzzle.name = name;
+    }
+}
+
+pub fn update_buttons(
+    mut commands: Commands,
+    query: Query<(&Interaction, &EditorButton), Changed<Interaction>>,
+    keys: Res<Input<KeyCode>>,
+    entities_on_screen: Query<Entity, With<OnEditorScreen>>,
+    grid_entities: Query<Entity, Or<(With<EditorCell>, With<EditorTile>)>>,
+    solver_tasks: Query<Entity, With<SolverTask>>,
+    assets: Res<GameAssets>,
+    mut editor: ResMut<PuzzleEditor>,
+    mut endless_config: ResMut<EndlessConfig>,
+    mut active_puzzle: ResMut<ActivePuzzle>,
+    mut grid_config: ResMut<GridConfig>,
+    mut state: ResMut<NextState<GameState>>,
+) {
+    let pressed = query.iter().find_map(|(interaction, button)| {
+        (interaction == &Interaction::Pressed).then_some(*button)
+    });
+    let button = if keys.just_pressed(KeyCode::Escape) {
+        EditorButton::Back
+    } else if let Some(button) = pressed {
+        button
+    } else {
+        return;
+    };
+
+    let editor = &mut *editor;
+    let puzzle = &mut editor.puzzle;
+    let GridConfig { width, height } = puzzle.config;
+    let size = |size: i32| size.clamp(1, MAX_EDITOR_SIZE);
+    let mut message = None;
+    match button {
+        EditorButton::Narrower
+        | EditorButton::Wider
+        | EditorButton::Shorter
+        | EditorButton::Taller => {
+            let config = match button {
+                EditorButton::Narrower => GridConfig {
+                    width: size(width - 1),
+                    height,
+                },
+                EditorButton::Wider => GridConfig {
+                    width: size(width + 1),
+                    height,
+                },
+                EditorButton::Shorter => GridConfig {
+                    width,
+                    height: size(height - 1),
+                },
+                _ => GridConfig {
+                    width,
+                    height: size(height + 1),
+                },
+            };
+            if config == puzzle.config {
+                return;
+            }
+            puzzle.resize(config);
+            for entity in grid_entities.iter() {
+                commands.add(DespawnRecursive { entity });
+            }
+            spawn_grid(&mut commands, &assets, puzzle);
+        }
+        EditorButton::NextGoal => puzzle.goal = puzzle.goal.next_kind(),
+        EditorButton::LowerTarget => puzzle.goal = puzzle.goal.adjust_target(-1),
+        EditorButton::RaiseTarget => puzzle.goal = puzzle.goal.adjust_target(1),
+        EditorButton::FewerMoves => puzzle.moves = puzzle.moves.saturating_sub(1).max(1),
+        EditorButton::MoreMoves => puzzle.moves += 1,
+        EditorButton::AddSpawn => puzzle.spawns.push(editor.brush),
+        EditorButton::RemoveSpawn => {
+            puzzle.spawns.pop();
+        }
+        // Only puzzles that could be shipped are tested, solved or saved
+        EditorButton::Test | EditorButton::Solve | EditorButton::Save => match puzzle.validate() {
+            Err(err) => message = Some(err),
+            // It can search for a while, the editor is left responsive
+            Ok(()) if button == EditorButton::Solve => {
+                for entity in solver_tasks.iter() {
+                    commands.add(DespawnRecursive { entity });
+                }
+                let solving = puzzle.clone();
+                let task = AsyncComputeTaskPool::get()
+                    .spawn(async move { solve(&solving, DEFAULT_MAX_STATES) });
+                commands.spawn((
+                    SolverTask {
+                        puzzle: puzzle.clone(),
+                        task,
+                    },
+                    OnEditorScreen,
+                ));
+                message = Some("Solving...".to_string());
+            }
+            Ok(()) if button == EditorButton::Save => {
+                match ron::ser::to_string_pretty(puzzle, Default::default()) {
+                    Ok(text) => {
+                        storage::save(EDITOR_FILE, &text);
+                        message = Some("Saved, it is listed with the puzzles".to_string());
+                    }
+                    Err(err) => message = Some(err.to_string()),
+                }
+            }
+            Ok(()) => {
+                for entity in entities_on_screen.iter() {
+                    commands.add(DespawnRecursive { entity });
+                }
+                editor.testing = true;
+                endless_config.play_on(&mut grid_config, puzzle.config);
+                active_puzzle.0 = Some(PuzzleRun::new(puzzle.clone()));
+                state.set(GameState::Playing);
+            }
+        },
+        EditorButton::Back => {
+            for entity in entities_on_screen.iter() {
+                commands.add(DespawnRecursive { entity });
+            }
+            state.set(GameState::LevelSelect);
+        }
+    }
+    editor.message = message;
+}
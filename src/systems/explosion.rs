@@ -1,26 +1,42 @@
 use bevy::{
-    prelude::{Component, Query, Res},
+    prelude::{Component, Query, Res, Visibility},
     sprite::TextureAtlasSprite,
     time::{Time, Timer},
 };
 
+#[allow(dead_code)]
 #[derive(Debug, Component)]
 pub struct Explosion(pub Timer);
 
 #[derive(Debug, Component)]
-pub struct ExplosionAnimation(pub Timer);
+pub struct ExplosionAnimation {
+    /// Before the explosion shows up
+    pub delay: Timer,
+    pub frame: Timer,
+}
 
 pub fn animate_explosion(
-    mut query: Query<(&mut ExplosionAnimation, &mut TextureAtlasSprite)>,
+    mut query: Query<(
+        &mut ExplosionAnimation,
+        &mut TextureAtlasSprite,
+        &mut Visibility,
+    )>,
     time: Res<Time>,
 ) {
     let delta = time.delta();
-    for (mut anim, mut sprite) in query.iter_mut() {
-        anim.0.tick(delta);
+    for (mut anim, mut sprite, mut visibility) in query.iter_mut() {
+        if !anim.delay.finished() {
+            anim.delay.tick(delta);
+            if !anim.delay.finished() {
+                continue;
+            }
+            *visibility = Visibility::Inherited;
+        }
+        anim.frame.tick(delta);
 
-        if anim.0.finished() {
+        if anim.frame.finished() {
             sprite.index = (sprite.index + 1).min(5);
-            anim.0.reset();
+            anim.frame.reset();
         }
     }
 }
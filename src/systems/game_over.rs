@@ -3,17 +3,25 @@ use bevy::{
         BuildChildren, ButtonBundle, Commands, Component, DespawnRecursive, Entity, NextState,
         NodeBundle, Query, Res, ResMut, TextBundle,
     },
-    text::TextStyle,
+    text::{TextSection, TextStyle},
     ui::{AlignItems, FlexDirection, Interaction, JustifyContent, PositionType, Style, Val},
 };
 
+use am4lgam8_core::{
+    blitz::BlitzClock, daily::DailyHistory, grid::TileGrid, history::MoveHistory, score::GameScore,
+};
+
 use crate::{
     assets::GameAssets,
     constants::{background_color, foreground_color},
     core::GameState,
 };
 
-use super::ui::GameScore;
+use super::{
+    blitz::ActiveBlitz,
+    daily::ActiveDaily,
+    highscores::{initials_text, PendingHighScore},
+};
 
 #[derive(Component)]
 pub struct OnGameOverScreen;
@@ -21,7 +29,27 @@ pub struct OnGameOverScreen;
 #[derive(Component)]
 pub struct ToTitleScreenButton;
 
-pub fn setup(mut commands: Commands, score: Res<GameScore>, assets: Res<GameAssets>) {
+/// Shows the initials being typed for a new high score
+#[derive(Component)]
+pub struct InitialsLabel;
+
+#[allow(clippy::needless_update)]
+pub fn setup(
+    mut commands: Commands,
+    score: Res<GameScore>,
+    tile_grid: Res<TileGrid>,
+    history: Res<MoveHistory>,
+    pending_high_score: Res<PendingHighScore>,
+    daily: Res<ActiveDaily>,
+    daily_history: Res<DailyHistory>,
+    blitz: Res<ActiveBlitz>,
+    assets: Res<GameAssets>,
+) {
+    let header = if blitz.0.as_ref().is_some_and(BlitzClock::is_over) {
+        "Time's up!"
+    } else {
+        "Game over!"
+    };
     commands
         .spawn((
             NodeBundle {
@@ -39,7 +67,7 @@ pub fn setup(mut commands: Commands, score: Res<GameScore>, assets: Res<GameAsse
         ))
         .with_children(|parent| {
             parent.spawn(TextBundle::from_section(
-                "Game over!",
+                header,
                 TextStyle {
                     font_size: 40.0,
                     color: foreground_color(),
@@ -56,6 +84,75 @@ pub fn setup(mut commands: Commands, score: Res<GameScore>, assets: Res<GameAsse
                     ..Default::default()
                 },
             ));
+            parent.spawn(TextBundle::from_section(
+                format!("Seed: {}", tile_grid.seed()),
+                TextStyle {
+                    font_size: 20.0,
+                    color: foreground_color(),
+                    font: assets.joystix.clone(),
+                },
+            ));
+            if let Some(date) = daily.date {
+                let lines = [
+                    format!("Daily {date}"),
+                    format!(
+                        "Streak {} - best {}",
+                        daily_history.streak(&date),
+                        daily_history.best_streak()
+                    ),
+                ];
+                for line in lines {
+                    parent.spawn(TextBundle::from_section(
+                        line,
+                        TextStyle {
+                            font_size: 20.0,
+                            color: foreground_color(),
+                            font: assets.joystix.clone(),
+                        },
+                    ));
+                }
+            }
+            if history.is_practice() {
+                parent.spawn(TextBundle::from_section(
+                    "Practice run (undo or AI used)",
+                    TextStyle {
+                        font_size: 20.0,
+                        color: foreground_color(),
+                        font: assets.joystix.clone(),
+                    },
+                ));
+            }
+            if let Some(rank) = pending_high_score.rank {
+                parent.spawn(TextBundle::from_section(
+                    format!("New high score! #{}", rank + 1),
+                    TextStyle {
+                        font_size: 20.0,
+                        color: foreground_color(),
+                        font: assets.joystix.clone(),
+                    },
+                ));
+                parent.spawn((
+                    TextBundle::from_sections([
+                        TextSection::new(
+                            initials_text(&pending_high_score.initials),
+                            TextStyle {
+                                font_size: 30.0,
+                                color: foreground_color(),
+                                font: assets.joystix.clone(),
+                            },
+                        ),
+                        TextSection::new(
+                            " Type your initials",
+                            TextStyle {
+                                font_size: 12.0,
+                                color: foreground_color(),
+                                font: assets.joystix.clone(),
+                            },
+                        ),
+                    ]),
+                    InitialsLabel,
+                ));
+            }
             parent
                 .spawn((
                     ButtonBundle {
@@ -87,6 +184,7 @@ pub fn setup(mut commands: Commands, score: Res<GameScore>, assets: Res<GameAsse
         });
 }
 
+#[allow(clippy::single_match)]
 pub fn update_ui(
     mut commands: Commands,
     query: Query<(&Interaction, &ToTitleScreenButton)>,
@@ -0,0 +1,233 @@
+use bevy::{
+    input::gamepad::{GamepadConnection, GamepadConnectionEvent},
+    log::info,
+    prelude::{
+        Axis, Button, DetectChangesMut, Entity, EventReader, EventWriter, Gamepad, GamepadAxis,
+        GamepadAxisType, GamepadButton, GamepadButtonType, Gamepads, GlobalTransform, Input, Local,
+        Query, Res, ResMut, Resource, With,
+    },
+    ui::{BorderColor, Interaction, Style, UiRect, Val},
+    utils::HashMap,
+};
+
+use am4lgam8_core::{grid::TileGrid, moves::MoveDirection};
+
+use crate::constants::background_color;
+
+use super::{
+    cursor::{CursorAction, SelectionCursor},
+    movables::RequestMoveEvent,
+};
+
+/// How far the stick has to be pushed for a flick to count
+const FLICK_THRESHOLD: f32 = 0.6;
+/// How close to the center the stick has to come back before it can flick again
+const RELEASE_THRESHOLD: f32 = 0.3;
+/// Width of the border drawn around the focused menu button
+const FOCUS_BORDER: f32 = 4.;
+
+// Resources
+
+/// Menu button selected with a gamepad, hidden until a gamepad is used
+#[derive(Debug, Default, Resource)]
+pub struct MenuFocus {
+    index: Option<usize>,
+    /// Button pressed with the gamepad during the last frame, which has to be released
+    pressed: Option<Entity>,
+}
+
+/// Direction each gamepad's left stick is currently flicked towards
+type HeldSticks = HashMap<Gamepad, MoveDirection>;
+
+// Helpers
+
+fn stick_direction(x: f32, y: f32) -> MoveDirection {
+    if x.abs() > y.abs() {
+        if x > 0. {
+            MoveDirection::Right
+        } else {
+            MoveDirection::Left
+        }
+    } else if y > 0. {
+        MoveDirection::Up
+    } else {
+        MoveDirection::Down
+    }
+}
+
+/// Returns the direction newly pressed on any gamepad, either on the D-pad or by flicking the
+/// left stick
+fn pad_direction(
+    gamepads: &Gamepads,
+    buttons: &Input<GamepadButton>,
+    axes: &Axis<GamepadAxis>,
+    held: &mut HeldSticks,
+) -> Option<MoveDirection> {
+    // Forget the pads that were unplugged
+    held.retain(|gamepad, _| gamepads.contains(*gamepad));
+
+    let mut new_direction = None;
+    for gamepad in gamepads.iter() {
+        let dpad = [
+            (GamepadButtonType::DPadUp, MoveDirection::Up),
+            (GamepadButtonType::DPadDown, MoveDirection::Down),
+            (GamepadButtonType::DPadLeft, MoveDirection::Left),
+            (GamepadButtonType::DPadRight, MoveDirection::Right),
+        ]
+        .into_iter()
+        .find_map(|(button_type, dir)| {
+            buttons
+                .just_pressed(GamepadButton::new(gamepad, button_type))
+                .then_some(dir)
+        });
+
+        let x = axes
+            .get(GamepadAxis::new(gamepad, GamepadAxisType::LeftStickX))
+            .unwrap_or_default();
+        let y = axes
+            .get(GamepadAxis::new(gamepad, GamepadAxisType::LeftStickY))
+            .unwrap_or_default();
+        let magnitude = x.abs().max(y.abs());
+        let flick = if magnitude > FLICK_THRESHOLD {
+            let dir = stick_direction(x, y);
+            (held.insert(gamepad, dir) != Some(dir)).then_some(dir)
+        } else {
+            if magnitude < RELEASE_THRESHOLD {
+                held.remove(&gamepad);
+            }
+            None
+        };
+
+        new_direction = new_direction.or(dpad).or(flick);
+    }
+    new_direction
+}
+
+fn pad_just_pressed(
+    gamepads: &Gamepads,
+    buttons: &Input<GamepadButton>,
+    button_type: GamepadButtonType,
+) -> bool {
+    gamepads
+        .iter()
+        .any(|gamepad| buttons.just_pressed(GamepadButton::new(gamepad, button_type)))
+}
+
+// Systems
+
+pub fn handle_gamepad_connections(
+    mut connection_events: EventReader<GamepadConnectionEvent>,
+    gamepads: Res<Gamepads>,
+    mut cursor: ResMut<SelectionCursor>,
+) {
+    for event in connection_events.iter() {
+        match &event.connection {
+            GamepadConnection::Connected(pad_info) => {
+                info!("Gamepad {} connected: {}", event.gamepad.id, pad_info.name)
+            }
+            GamepadConnection::Disconnected => {
+                info!("Gamepad {} disconnected", event.gamepad.id);
+                // Nobody is left to push the grabbed tile
+                if gamepads.iter().next().is_none() {
+                    cursor.grabbed = false;
+                }
+            }
+        }
+    }
+}
+
+/// The D-pad or the left stick moves the cursor, South (A on Xbox pads) grabs the selected tile,
+/// and a direction then pushes it. East (B) lets go of the tile.
+pub fn handle_gamepad_input(
+    gamepads: Res<Gamepads>,
+    buttons: Res<Input<GamepadButton>>,
+    axes: Res<Axis<GamepadAxis>>,
+    mut held: Local<HeldSticks>,
+    mut cursor: ResMut<SelectionCursor>,
+    tile_grid: Res<TileGrid>,
+    mut move_event_tx: EventWriter<RequestMoveEvent>,
+) {
+    let direction = pad_direction(&gamepads, &buttons, &axes, &mut held);
+    let action = if pad_just_pressed(&gamepads, &buttons, GamepadButtonType::East) {
+        CursorAction::Release
+    } else if pad_just_pressed(&gamepads, &buttons, GamepadButtonType::South) {
+        CursorAction::ToggleGrab
+    } else if let Some(dir) = direction {
+        CursorAction::Direction(dir)
+    } else {
+        return;
+    };
+
+    if let Some(move_event) = cursor.apply(action, &tile_grid) {
+        move_event_tx.send(move_event);
+    }
+}
+
+pub fn reset_menu_focus(mut focus: ResMut<MenuFocus>) {
+    *focus = MenuFocus::default();
+}
+
+/// Lets the title and game over menus be used with a gamepad : up and down move the focus between
+/// the buttons, from top to bottom, and South presses the focused one.
+///
+/// Runs right after Bevy updates the buttons' `Interaction`, so that a press looks like a click to
+/// the systems handling the buttons.
+pub fn navigate_menu_with_gamepad(
+    gamepads: Res<Gamepads>,
+    buttons: Res<Input<GamepadButton>>,
+    axes: Res<Axis<GamepadAxis>>,
+    mut held: Local<HeldSticks>,
+    mut focus: ResMut<MenuFocus>,
+    mut menu_buttons: Query<
+        (
+            Entity,
+            &GlobalTransform,
+            &mut Interaction,
+            &mut Style,
+            &mut BorderColor,
+        ),
+        With<Button>,
+    >,
+) {
+    // A press only lasts one frame
+    if let Some(entity) = focus.pressed.take() {
+        if let Ok((_, _, mut interaction, _, _)) = menu_buttons.get_mut(entity) {
+            interaction.set_if_neq(Interaction::None);
+        }
+    }
+
+    let mut ordered: Vec<_> = menu_buttons.iter_mut().collect();
+    if ordered.is_empty() {
+        return;
+    }
+    ordered.sort_by(|(_, a, ..), (_, b, ..)| a.translation().y.total_cmp(&b.translation().y));
+
+    let direction = pad_direction(&gamepads, &buttons, &axes, &mut held);
+    let press = pad_just_pressed(&gamepads, &buttons, GamepadButtonType::South);
+    let last = ordered.len() - 1;
+    match (focus.index, direction) {
+        // The first input only reveals the focus
+        (None, Some(_)) => focus.index = Some(0),
+        (None, None) if press => focus.index = Some(0),
+        (Some(index), Some(MoveDirection::Up)) => focus.index = Some(index.saturating_sub(1)),
+        (Some(index), Some(MoveDirection::Down)) => focus.index = Some((index + 1).min(last)),
+        (Some(index), _) if press => {
+            let (entity, _, interaction, ..) = &mut ordered[index.min(last)];
+            **interaction = Interaction::Pressed;
+            focus.pressed = Some(*entity);
+        }
+        _ => (),
+    }
+
+    for (index, (_, _, _, style, border_color)) in ordered.iter_mut().enumerate() {
+        let border = if focus.index.map(|focused| focused.min(last)) == Some(index) {
+            UiRect::all(Val::Px(FOCUS_BORDER))
+        } else {
+            UiRect::all(Val::Px(0.))
+        };
+        if style.border != border {
+            style.border = border;
+            border_color.0 = background_color();
+        }
+    }
+}
@@ -1,39 +1,94 @@
 use bevy::prelude::{
     Commands, DespawnRecursive, Entity, Event, EventReader, EventWriter, NextState, Query, Res,
-    ResMut,
+    ResMut, Resource,
 };
 
-use crate::{
-    assets::GameAssets,
-    bundles::tile::spawn_tile_type_bundle,
-    core::GameState,
-    game::grid::{SpawnEvent, TileGrid},
+use bevy::utils::HashSet;
+
+use am4lgam8_core::{
+    grid::{GridConfig, GridCoordinates, SpawnEvent, TileGrid},
+    rng::random_seed,
+    tile::TileType,
+};
+
+use crate::{assets::GameAssets, bundles::tile::spawn_tile_type_bundle, core::GameState};
+
+use super::{
+    daily::ActiveDaily, puzzle::ActivePuzzle, replay::ReplayPlayback, save::ResumedRun,
+    OnPlayingScreen,
 };
 
-use super::OnPlayingScreen;
+/// Seed of the next run, set from the title screen or the command line.
+/// A random one is picked when it is not set.
+#[derive(Debug, Default, Resource)]
+pub struct SeedSetting(pub Option<u64>);
+
+/// The grid size picked on the title screen, kept while a run is played on a size of its own
+#[derive(Debug, Default, Resource)]
+pub struct EndlessConfig(pub Option<GridConfig>);
+
+impl EndlessConfig {
+    /// Plays the next run on `run_config`, the picked size is given back on the title screen
+    pub fn play_on(&mut self, grid_config: &mut GridConfig, run_config: GridConfig) {
+        self.0.get_or_insert(*grid_config);
+        *grid_config = run_config;
+    }
+}
+
+/// However the run was left, the title screen shows the size that was picked there
+pub fn restore_endless_config(
+    mut endless_config: ResMut<EndlessConfig>,
+    mut grid_config: ResMut<GridConfig>,
+) {
+    if let Some(endless_config) = endless_config.0.take() {
+        *grid_config = endless_config;
+    }
+}
 
 pub fn setup_grid(
     mut commands: Commands,
     assets: Res<GameAssets>,
+    grid_config: Res<GridConfig>,
+    seed_setting: Res<SeedSetting>,
+    playback: Res<ReplayPlayback>,
+    resumed_run: Res<ResumedRun>,
+    puzzle: Res<ActivePuzzle>,
+    daily: Res<ActiveDaily>,
     mut tile_grid: ResMut<TileGrid>,
 ) {
-    let spawn_events = tile_grid.setup_default_grid();
+    let restored_grid = resumed_run
+        .0
+        .as_ref()
+        .map(|saved_run| saved_run.tile_grid.clone())
+        .or_else(|| puzzle.0.as_ref().map(|run| run.puzzle().setup_grid()));
+    let spawn_events = if let Some(restored_grid) = restored_grid {
+        *tile_grid = restored_grid;
+        tile_grid
+            .tiles()
+            .map(|(coords, tile_type)| SpawnEvent {
+                coords: coords.clone(),
+                tile_type: *tile_type,
+            })
+            .collect()
+    } else {
+        let seed = playback
+            .seed()
+            .or(daily.seed())
+            .or(seed_setting.0)
+            .unwrap_or_else(random_seed);
+        *tile_grid = TileGrid::new(*grid_config, seed);
+        tile_grid.setup_default_grid()
+    };
 
     for SpawnEvent { coords, tile_type } in spawn_events {
-        spawn_tile_type_bundle(
-            &mut commands,
-            assets.tileset.clone(),
-            tile_type,
-            coords.x,
-            coords.y,
-        );
+        spawn_tile_type_bundle(&mut commands, &assets, tile_type, coords.x, coords.y);
     }
 }
 
 pub fn check_for_game_over(
     mut commands: Commands,
     all_entities_on_screen: Query<(Entity, &OnPlayingScreen)>,
-    mut tile_grid: ResMut<TileGrid>,
+    tile_grid: Res<TileGrid>,
     mut next_state: ResMut<NextState<GameState>>,
     mut valid_turn_event_tx: EventWriter<ValidTurnEvent>,
 ) {
@@ -49,8 +104,8 @@ pub fn check_for_game_over(
         for (entity, _on_screen) in all_entities_on_screen.iter() {
             commands.add(DespawnRecursive { entity });
         }
+        // The grid is kept around, the game over screen shows its seed
         next_state.set(GameState::GameOver);
-        *tile_grid = TileGrid::default();
     }
 }
 
@@ -61,22 +116,19 @@ pub fn spawn_new_tile_on_valid_move(
     mut commands: Commands,
     game_assets: Res<GameAssets>,
     mut tile_grid: ResMut<TileGrid>,
+    puzzle: Res<ActivePuzzle>,
     mut valid_turn_event_rx: EventReader<ValidTurnEvent>,
 ) {
     for _ in valid_turn_event_rx.iter() {
-        let maybe_spawn_event = tile_grid.try_spawn_new_tile();
-
-        match maybe_spawn_event {
-            Some(SpawnEvent { coords, tile_type }) => {
-                spawn_tile_type_bundle(
-                    &mut commands,
-                    game_assets.tileset.clone(),
-                    tile_type,
-                    coords.x,
-                    coords.y,
-                );
-            }
-            None => panic!("No coordinates to spawn a tile on. This is a bug."),
+        // Puzzles spawn their own sequence, which can run out. Either way the grid can be full,
+        // e.g. after blocks set into walls : the game over check ends the run on the next frame.
+        let maybe_spawn_event = match &puzzle.0 {
+            Some(run) => run.spawn_next(&mut tile_grid),
+            None => tile_grid.try_spawn_new_tile(),
+        };
+
+        if let Some(SpawnEvent { coords, tile_type }) = maybe_spawn_event {
+            spawn_tile_type_bundle(&mut commands, &game_assets, tile_type, coords.x, coords.y);
         }
     }
 }
@@ -89,11 +141,29 @@ pub fn spawn_first_tile(
     let SpawnEvent { coords, tile_type } = tile_grid
         .spawn_first_tile()
         .expect("Failed to spawn first tile. This is a bug.");
-    spawn_tile_type_bundle(
-        &mut commands,
-        game_assets.tileset.clone(),
-        tile_type,
-        coords.x,
-        coords.y,
-    );
+    spawn_tile_type_bundle(&mut commands, &game_assets, tile_type, coords.x, coords.y);
+}
+
+/// Makes the tile entities match the grid, after it was replaced wholesale (e.g. by an undo).
+/// Tiles that did not change are left alone, the others are despawned and respawned.
+pub fn sync_tile_entities<'a>(
+    commands: &mut Commands,
+    assets: &GameAssets,
+    tile_grid: &TileGrid,
+    tile_entities: impl Iterator<Item = (Entity, &'a GridCoordinates, &'a TileType)>,
+) {
+    let mut kept: HashSet<GridCoordinates> = HashSet::default();
+    for (entity, coords, tile_type) in tile_entities {
+        if tile_grid.get(coords) == Some(tile_type) && kept.insert(coords.clone()) {
+            continue;
+        }
+        commands.add(DespawnRecursive { entity });
+    }
+
+    for (coords, tile_type) in tile_grid.tiles() {
+        if kept.contains(coords) {
+            continue;
+        }
+        spawn_tile_type_bundle(commands, assets, *tile_type, coords.x, coords.y);
+    }
 }
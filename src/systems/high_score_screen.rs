@@ -0,0 +1,142 @@
+use bevy::{
+    prelude::{
+        BuildChildren, ButtonBundle, Changed, Commands, Component, DespawnRecursive, Entity, Input,
+        KeyCode, NextState, NodeBundle, Query, Res, ResMut, TextBundle, With,
+    },
+    text::{TextSection, TextStyle},
+    ui::{
+        AlignItems, BackgroundColor, FlexDirection, Interaction, JustifyContent, Style, UiRect, Val,
+    },
+};
+
+use am4lgam8_core::highscores::HighScores;
+
+use crate::{
+    assets::GameAssets,
+    constants::{background_color, foreground_color},
+    core::GameState,
+};
+
+#[derive(Component)]
+pub struct OnHighScoreScreen;
+
+#[derive(Component)]
+pub struct BackButton;
+
+pub fn setup(mut commands: Commands, high_scores: Res<HighScores>, assets: Res<GameAssets>) {
+    let text_style = |font_size: f32| TextStyle {
+        font_size,
+        color: foreground_color(),
+        font: assets.joystix.clone(),
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    flex_direction: FlexDirection::Column,
+                    ..Default::default()
+                },
+                background_color: BackgroundColor(background_color()),
+                ..Default::default()
+            },
+            OnHighScoreScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn(
+                TextBundle::from_section("High scores", text_style(30.)).with_style(Style {
+                    margin: UiRect::bottom(Val::Px(10.)),
+                    ..Default::default()
+                }),
+            );
+
+            if high_scores.entries().is_empty() {
+                parent.spawn(TextBundle::from_section("No runs yet", text_style(14.)));
+            }
+            for (rank, entry) in high_scores.entries().iter().enumerate() {
+                let initials = if entry.initials.is_empty() {
+                    "---"
+                } else {
+                    &entry.initials
+                };
+                // The score on the first line, where the run comes from on the second one
+                parent.spawn(
+                    TextBundle::from_sections([
+                        TextSection::new(
+                            format!(
+                                "{:>2}. {initials:<3} {:>6}  {}x{}\n",
+                                rank + 1,
+                                entry.score,
+                                entry.grid_config.width,
+                                entry.grid_config.height,
+                            ),
+                            text_style(14.),
+                        ),
+                        TextSection::new(
+                            format!(
+                                "{}, {} moves, seed {}",
+                                entry.date, entry.moves_played, entry.seed
+                            ),
+                            text_style(8.),
+                        ),
+                    ])
+                    .with_style(Style {
+                        margin: UiRect::bottom(Val::Px(4.)),
+                        ..Default::default()
+                    }),
+                );
+            }
+
+            parent
+                .spawn((
+                    ButtonBundle {
+                        style: Style {
+                            margin: UiRect::top(Val::Px(10.)),
+                            height: Val::Px(30.0),
+                            padding: UiRect::horizontal(Val::Px(10.)),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..Default::default()
+                        },
+                        background_color: BackgroundColor(foreground_color()),
+                        ..Default::default()
+                    },
+                    BackButton,
+                ))
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section(
+                        "Back",
+                        TextStyle {
+                            font_size: 20.0,
+                            color: background_color(),
+                            font: assets.joystix.clone(),
+                        },
+                    ));
+                });
+        });
+}
+
+/// The back button, or Escape, goes back to the title screen
+pub fn update_ui(
+    mut commands: Commands,
+    query: Query<&Interaction, (Changed<Interaction>, With<BackButton>)>,
+    keys: Res<Input<KeyCode>>,
+    entities_on_screen: Query<Entity, With<OnHighScoreScreen>>,
+    mut state: ResMut<NextState<GameState>>,
+) {
+    let back = keys.just_pressed(KeyCode::Escape)
+        || query
+            .iter()
+            .any(|interaction| interaction == &Interaction::Pressed);
+    if back {
+        for entity in entities_on_screen.iter() {
+            commands.add(DespawnRecursive { entity })
+        }
+
+        state.set(GameState::TitleScreen);
+    }
+}
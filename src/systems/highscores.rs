@@ -0,0 +1,136 @@
+use bevy::{
+    prelude::{EventReader, Input, KeyCode, Query, Res, ResMut, Resource, With},
+    text::Text,
+    window::ReceivedCharacter,
+};
+
+use am4lgam8_core::{
+    grid::TileGrid,
+    highscores::{HighScoreEntry, HighScores, MAX_INITIALS},
+    history::MoveHistory,
+    score::GameScore,
+};
+
+use crate::storage;
+
+use super::{
+    blitz::ActiveBlitz,
+    game_over::InitialsLabel,
+    replay::{ReplayPlayback, ReplayRecorder},
+};
+
+const HIGH_SCORES_FILE: &str = "highscores.txt";
+
+// Resources
+
+/// The run that just made it into the high scores, while its initials are being typed
+#[derive(Debug, Default, Resource)]
+pub struct PendingHighScore {
+    pub rank: Option<usize>,
+    pub initials: String,
+}
+
+pub fn initials_text(initials: &str) -> String {
+    format!("{initials:_<MAX_INITIALS$}")
+}
+
+pub fn load_high_scores() -> HighScores {
+    let Some(text) = storage::load(HIGH_SCORES_FILE) else {
+        return HighScores::default();
+    };
+    text.parse().unwrap_or_else(|err| {
+        bevy::log::warn!("Ignoring the saved high scores: {err}");
+        HighScores::default()
+    })
+}
+
+fn save_high_scores(high_scores: &HighScores) {
+    storage::save(HIGH_SCORES_FILE, &high_scores.to_string());
+}
+
+// Systems
+
+/// Adds the run that just ended to the high scores. Replays, practice and blitz runs don't count.
+pub fn record_high_score(
+    tile_grid: Res<TileGrid>,
+    score: Res<GameScore>,
+    history: Res<MoveHistory>,
+    recorder: Res<ReplayRecorder>,
+    playback: Res<ReplayPlayback>,
+    blitz: Res<ActiveBlitz>,
+    mut high_scores: ResMut<HighScores>,
+    mut pending: ResMut<PendingHighScore>,
+) {
+    *pending = PendingHighScore::default();
+    if playback.is_active() || history.is_practice() || blitz.0.is_some() {
+        return;
+    }
+
+    let moves_played = recorder
+        .replay()
+        .map(|replay| replay.moves.len() as u32)
+        .unwrap_or_default();
+    pending.rank = high_scores.insert(HighScoreEntry {
+        initials: String::new(),
+        score: score.get(),
+        date: storage::today(),
+        seed: tile_grid.seed(),
+        grid_config: *tile_grid.config(),
+        moves_played,
+    });
+    if pending.rank.is_some() {
+        save_high_scores(&high_scores);
+    }
+}
+
+/// Letters are typed into the initials, Backspace erases one and Enter saves them
+pub fn enter_initials(
+    mut received_characters: EventReader<ReceivedCharacter>,
+    keys: Res<Input<KeyCode>>,
+    mut pending: ResMut<PendingHighScore>,
+    mut high_scores: ResMut<HighScores>,
+    mut labels: Query<&mut Text, With<InitialsLabel>>,
+) {
+    if pending.rank.is_none() {
+        received_characters.clear();
+        return;
+    }
+
+    let mut initials = pending.initials.clone();
+    for event in received_characters.iter() {
+        if event.char.is_ascii_alphabetic() && initials.len() < MAX_INITIALS {
+            initials.push(event.char.to_ascii_uppercase());
+        }
+    }
+    if keys.just_pressed(KeyCode::Back) {
+        initials.pop();
+    }
+
+    if initials != pending.initials {
+        for mut text in labels.iter_mut() {
+            text.sections[0].value = initials_text(&initials);
+        }
+        pending.initials = initials;
+    }
+
+    if keys.just_pressed(KeyCode::Return) {
+        save_initials(&mut pending, &mut high_scores);
+    }
+}
+
+/// Keeps the initials typed so far when leaving the game over screen without pressing Enter
+pub fn save_pending_initials(
+    mut pending: ResMut<PendingHighScore>,
+    mut high_scores: ResMut<HighScores>,
+) {
+    save_initials(&mut pending, &mut high_scores);
+}
+
+fn save_initials(pending: &mut PendingHighScore, high_scores: &mut HighScores) {
+    if let Some(rank) = pending.rank.take() {
+        if !pending.initials.is_empty() {
+            high_scores.set_initials(rank, &pending.initials);
+            save_high_scores(high_scores);
+        }
+    }
+}
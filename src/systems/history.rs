@@ -0,0 +1,73 @@
+use bevy::prelude::{Changed, Commands, Entity, Input, KeyCode, Query, Res, ResMut, With, Without};
+use bevy::ui::Interaction;
+
+use am4lgam8_core::{
+    grid::{GridCoordinates, TileGrid},
+    history::{MoveHistory, Snapshot},
+    score::GameScore,
+    tile::TileType,
+};
+
+use crate::assets::GameAssets;
+
+use super::{
+    grid::sync_tile_entities,
+    marked_for_deletion::MarkedForDeletion,
+    puzzle::ActivePuzzle,
+    replay::ReplayRecorder,
+    ui::{RedoButton, UndoButton},
+};
+
+pub fn reset_history(mut history: ResMut<MoveHistory>) {
+    history.clear();
+}
+
+/// Z (or the undo button) goes back one move, Y (or the redo button) goes forward again
+pub fn handle_undo_redo(
+    mut commands: Commands,
+    keys: Res<Input<KeyCode>>,
+    undo_buttons: Query<&Interaction, (Changed<Interaction>, With<UndoButton>)>,
+    redo_buttons: Query<&Interaction, (Changed<Interaction>, With<RedoButton>)>,
+    assets: Res<GameAssets>,
+    mut history: ResMut<MoveHistory>,
+    mut tile_grid: ResMut<TileGrid>,
+    mut game_score: ResMut<GameScore>,
+    mut recorder: ResMut<ReplayRecorder>,
+    mut puzzle: ResMut<ActivePuzzle>,
+    tiles: Query<(Entity, &GridCoordinates, &TileType), Without<MarkedForDeletion>>,
+) {
+    let pressed = |interaction: &Interaction| interaction == &Interaction::Pressed;
+    let undo = keys.just_pressed(KeyCode::Z) || undo_buttons.iter().any(pressed);
+    let redo = keys.just_pressed(KeyCode::Y) || redo_buttons.iter().any(pressed);
+
+    // Only taken once a key or button asks for it, the grid is not cheap to clone
+    let current = || Snapshot {
+        tile_grid: tile_grid.clone(),
+        score: *game_score,
+    };
+    let restored = match (undo, redo) {
+        (true, false) => history.undo(current()).inspect(|_| {
+            recorder.undo();
+            if let Some(run) = &mut puzzle.0 {
+                run.undo();
+            }
+        }),
+        (false, true) => history.redo(current()).inspect(|_| {
+            recorder.redo();
+            if let Some(run) = &mut puzzle.0 {
+                run.redo();
+            }
+        }),
+        _ => None,
+    };
+
+    if let Some(Snapshot {
+        tile_grid: restored_grid,
+        score,
+    }) = restored
+    {
+        *tile_grid = restored_grid;
+        *game_score = score;
+        sync_tile_entities(&mut commands, &assets, &tile_grid, tiles.iter());
+    }
+}
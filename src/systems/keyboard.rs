@@ -0,0 +1,40 @@
+use bevy::prelude::{EventWriter, Input, KeyCode, Res, ResMut};
+
+use am4lgam8_core::{grid::TileGrid, moves::MoveDirection};
+
+use super::{
+    cursor::{CursorAction, SelectionCursor},
+    movables::RequestMoveEvent,
+};
+
+/// Arrows or WASD move the cursor, Space or Enter grabs the selected tile, and a direction then
+/// moves it. Escape lets go of the tile.
+pub fn handle_keyboard_input(
+    keys: Res<Input<KeyCode>>,
+    mut cursor: ResMut<SelectionCursor>,
+    tile_grid: Res<TileGrid>,
+    mut move_event_tx: EventWriter<RequestMoveEvent>,
+) {
+    let direction = [
+        (KeyCode::Up, KeyCode::W, MoveDirection::Up),
+        (KeyCode::Down, KeyCode::S, MoveDirection::Down),
+        (KeyCode::Left, KeyCode::A, MoveDirection::Left),
+        (KeyCode::Right, KeyCode::D, MoveDirection::Right),
+    ]
+    .into_iter()
+    .find_map(|(arrow, letter, dir)| keys.any_just_pressed([arrow, letter]).then_some(dir));
+
+    let action = if keys.just_pressed(KeyCode::Escape) {
+        CursorAction::Release
+    } else if keys.any_just_pressed([KeyCode::Space, KeyCode::Return]) {
+        CursorAction::ToggleGrab
+    } else if let Some(dir) = direction {
+        CursorAction::Direction(dir)
+    } else {
+        return;
+    };
+
+    if let Some(move_event) = cursor.apply(action, &tile_grid) {
+        move_event_tx.send(move_event);
+    }
+}
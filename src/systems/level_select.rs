@@ -0,0 +1,234 @@
+use bevy::{
+    prelude::{
+        Assets, BuildChildren, ButtonBundle, Changed, Commands, Component, DespawnRecursive,
+        Entity, Input, KeyCode, NextState, NodeBundle, Query, Res, ResMut, TextBundle, With,
+    },
+    text::{TextSection, TextStyle},
+    ui::{
+        AlignItems, BackgroundColor, FlexDirection, Interaction, JustifyContent, Style, UiRect, Val,
+    },
+};
+
+use am4lgam8_core::{
+    grid::GridConfig,
+    puzzle::{Puzzle, PuzzleRecords, PuzzleRun},
+};
+
+use crate::{
+    assets::{GameAssets, PuzzleAsset},
+    constants::{background_color, foreground_color},
+    core::GameState,
+};
+
+use super::{
+    editor::load_saved_puzzle,
+    grid::EndlessConfig,
+    puzzle::{ActivePuzzle, PuzzleMenu},
+};
+
+#[derive(Component)]
+pub struct OnLevelSelectScreen;
+
+/// Starts this puzzle, shipped or saved from the editor
+#[derive(Component)]
+pub struct PuzzleButton(Puzzle);
+
+#[derive(Component)]
+pub struct EditorButton;
+
+#[derive(Component)]
+pub struct BackButton;
+
+pub fn setup(
+    mut commands: Commands,
+    assets: Res<GameAssets>,
+    puzzles: Res<Assets<PuzzleAsset>>,
+    records: Res<PuzzleRecords>,
+    menu: Res<PuzzleMenu>,
+) {
+    let text_style = |font_size: f32, color| TextStyle {
+        font_size,
+        color,
+        font: assets.joystix.clone(),
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    flex_direction: FlexDirection::Column,
+                    ..Default::default()
+                },
+                background_color: BackgroundColor(background_color()),
+                ..Default::default()
+            },
+            OnLevelSelectScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn(
+                TextBundle::from_section("Puzzles", text_style(30., foreground_color()))
+                    .with_style(Style {
+                        margin: UiRect::bottom(Val::Px(10.)),
+                        ..Default::default()
+                    }),
+            );
+            if let Some(message) = &menu.message {
+                parent.spawn(
+                    TextBundle::from_section(message, text_style(14., foreground_color()))
+                        .with_style(Style {
+                            margin: UiRect::bottom(Val::Px(10.)),
+                            ..Default::default()
+                        }),
+                );
+            }
+
+            // Puzzles that failed to load are left out. The one saved from the editor comes last,
+            // unless it was edited by hand into one that can't be played.
+            let loaded = assets
+                .puzzles
+                .iter()
+                .enumerate()
+                .filter_map(|(index, handle)| {
+                    let puzzle = &puzzles.get(handle)?.0;
+                    Some((format!("{}. {}", index + 1, puzzle.name), puzzle.clone()))
+                });
+            let saved = load_saved_puzzle()
+                .filter(|puzzle| puzzle.validate().is_ok())
+                .map(|puzzle| (format!("Yours. {}", puzzle.name), puzzle));
+            for (title, puzzle) in loaded.chain(saved) {
+                let best = match records.best_moves(&puzzle.name) {
+                    Some(moves) => format!(" - solved in {moves}"),
+                    None => String::new(),
+                };
+                // The name on the first line, what it takes on the second one
+                parent
+                    .spawn((
+                        ButtonBundle {
+                            style: Style {
+                                width: Val::Px(320.0),
+                                margin: UiRect::bottom(Val::Px(4.)),
+                                padding: UiRect::all(Val::Px(4.)),
+                                justify_content: JustifyContent::Center,
+                                ..Default::default()
+                            },
+                            background_color: BackgroundColor(foreground_color()),
+                            ..Default::default()
+                        },
+                        PuzzleButton(puzzle.clone()),
+                    ))
+                    .with_children(|parent| {
+                        parent.spawn(TextBundle::from_sections([
+                            TextSection::new(
+                                format!("{title}\n"),
+                                text_style(14., background_color()),
+                            ),
+                            TextSection::new(
+                                format!("{} in {} moves{best}", puzzle.goal, puzzle.moves),
+                                text_style(8., background_color()),
+                            ),
+                        ]));
+                    });
+            }
+
+            let menu_button = || ButtonBundle {
+                style: Style {
+                    margin: UiRect::top(Val::Px(10.)),
+                    height: Val::Px(30.0),
+                    padding: UiRect::horizontal(Val::Px(10.)),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..Default::default()
+                },
+                background_color: BackgroundColor(foreground_color()),
+                ..Default::default()
+            };
+            parent
+                .spawn((menu_button(), EditorButton))
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section(
+                        "Editor",
+                        text_style(20., background_color()),
+                    ));
+                });
+            parent
+                .spawn((menu_button(), BackButton))
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section(
+                        "Back",
+                        text_style(20., background_color()),
+                    ));
+                });
+        });
+}
+
+/// Picking a puzzle plays it on a grid of its own size
+pub fn update_puzzle_buttons(
+    mut commands: Commands,
+    query: Query<(&Interaction, &PuzzleButton), Changed<Interaction>>,
+    entities_on_screen: Query<Entity, With<OnLevelSelectScreen>>,
+    mut active_puzzle: ResMut<ActivePuzzle>,
+    mut endless_config: ResMut<EndlessConfig>,
+    mut grid_config: ResMut<GridConfig>,
+    mut state: ResMut<NextState<GameState>>,
+) {
+    for (interaction, PuzzleButton(puzzle)) in query.iter() {
+        if interaction != &Interaction::Pressed {
+            continue;
+        }
+        for entity in entities_on_screen.iter() {
+            commands.add(DespawnRecursive { entity })
+        }
+
+        endless_config.play_on(&mut grid_config, puzzle.config);
+        active_puzzle.0 = Some(PuzzleRun::new(puzzle.clone()));
+        state.set(GameState::Playing);
+        return;
+    }
+}
+
+pub fn update_editor_button(
+    mut commands: Commands,
+    query: Query<&Interaction, (Changed<Interaction>, With<EditorButton>)>,
+    entities_on_screen: Query<Entity, With<OnLevelSelectScreen>>,
+    mut menu: ResMut<PuzzleMenu>,
+    mut state: ResMut<NextState<GameState>>,
+) {
+    if query
+        .iter()
+        .any(|interaction| interaction == &Interaction::Pressed)
+    {
+        for entity in entities_on_screen.iter() {
+            commands.add(DespawnRecursive { entity })
+        }
+
+        menu.message = None;
+        state.set(GameState::Editor);
+    }
+}
+
+/// The back button, or Escape, goes back to the title screen
+pub fn update_back_button(
+    mut commands: Commands,
+    query: Query<&Interaction, (Changed<Interaction>, With<BackButton>)>,
+    keys: Res<Input<KeyCode>>,
+    entities_on_screen: Query<Entity, With<OnLevelSelectScreen>>,
+    mut menu: ResMut<PuzzleMenu>,
+    mut state: ResMut<NextState<GameState>>,
+) {
+    let back = keys.just_pressed(KeyCode::Escape)
+        || query
+            .iter()
+            .any(|interaction| interaction == &Interaction::Pressed);
+    if back {
+        for entity in entities_on_screen.iter() {
+            commands.add(DespawnRecursive { entity })
+        }
+
+        menu.message = None;
+        state.set(GameState::TitleScreen);
+    }
+}
@@ -1,6 +1,5 @@
 use bevy::{
-    ecs::system::Despawn,
-    prelude::{Commands, Component, Entity, Query, Res},
+    prelude::{Commands, Component, DespawnRecursive, Entity, Query, Res},
     time::{Time, Timer},
 };
 
@@ -16,7 +15,7 @@ pub fn tick_marked_for_deletion(
     for (entity, mut marker) in query.iter_mut() {
         marker.0.tick(delta);
         if marker.0.finished() {
-            commands.add(Despawn { entity });
+            commands.add(DespawnRecursive { entity });
         }
     }
 }
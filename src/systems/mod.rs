@@ -1,13 +1,30 @@
 use bevy::prelude::Component;
 
+pub mod ai;
 pub mod animations;
+pub mod blitz;
 pub mod camera;
+pub mod cursor;
+pub mod daily;
 // pub mod debug;
+pub mod editor;
 pub mod explosion;
 pub mod game_over;
+pub mod gamepad;
 pub mod grid;
+pub mod high_score_screen;
+pub mod highscores;
+pub mod history;
+pub mod keyboard;
+pub mod level_select;
 pub mod marked_for_deletion;
 pub mod movables;
+pub mod preview;
+pub mod progression;
+pub mod puzzle;
+pub mod replay;
+pub mod save;
+pub mod score_popup;
 pub mod tiles;
 pub mod title_screen;
 pub mod ui;
@@ -1,7 +1,12 @@
-use bevy::prelude::{Component, Entity, Event, EventWriter, Query};
-use bevy_mod_picking::prelude::{DragEnd, Listener, On, Pointer};
+use bevy::prelude::{Component, Entity, Event, EventReader, EventWriter, Query, ResMut, Vec2};
+use bevy_mod_picking::prelude::{Drag, DragEnd, ListenerInput, On, Pointer};
 
-use crate::game::{grid::GridCoordinates, moves::MoveDirection};
+use am4lgam8_core::{grid::GridCoordinates, moves::MoveDirection};
+
+use super::preview::MovePreview;
+
+/// Drags shorter than this, in screen pixels, are too ambiguous to be previewed
+const MIN_PREVIEW_DISTANCE: f32 = 8.;
 
 #[derive(Debug, Event)]
 pub struct RequestMoveEvent {
@@ -9,45 +14,99 @@ pub struct RequestMoveEvent {
     pub source_coords: GridCoordinates,
 }
 
+/// A tile being dragged, as far as it was from where the drag started
+#[derive(Debug, Event)]
+pub struct TileDragEvent {
+    pub target: Entity,
+    pub distance: Vec2,
+}
+
+impl From<ListenerInput<Pointer<Drag>>> for TileDragEvent {
+    fn from(event: ListenerInput<Pointer<Drag>>) -> Self {
+        TileDragEvent {
+            target: event.target,
+            distance: event.distance,
+        }
+    }
+}
+
+/// A tile let go of, the drag is played as a move
+#[derive(Debug, Event)]
+pub struct TileDragEndEvent {
+    pub target: Entity,
+    pub distance: Vec2,
+}
+
+impl From<ListenerInput<Pointer<DragEnd>>> for TileDragEndEvent {
+    fn from(event: ListenerInput<Pointer<DragEnd>>) -> Self {
+        TileDragEndEvent {
+            target: event.target,
+            distance: event.distance,
+        }
+    }
+}
+
+pub fn on_pointer_drag_handler() -> impl Component {
+    On::<Pointer<Drag>>::send_event::<TileDragEvent>()
+}
+
 pub fn on_pointer_drag_end_handler() -> impl Component {
-    let on_pointer_drag_end = On::<Pointer<DragEnd>>::run(handle_pointer_drag_end);
-    on_pointer_drag_end
+    On::<Pointer<DragEnd>>::send_event::<TileDragEndEvent>()
 }
 
-fn handle_pointer_drag_end(
-    pointer_event: Listener<Pointer<DragEnd>>,
-    query: Query<(Entity, &GridCoordinates)>,
-    mut move_event_tx: EventWriter<RequestMoveEvent>,
-) {
-    let move_direction = {
-        let dx = pointer_event.distance.x;
-        let dy = pointer_event.distance.y;
-        if dx.abs() > dy.abs() {
-            // Move is horizontal
-            if dx > 0. {
-                MoveDirection::Right
-            } else {
-                MoveDirection::Left
-            }
+fn drag_direction(distance: Vec2) -> MoveDirection {
+    let dx = distance.x;
+    let dy = distance.y;
+    if dx.abs() > dy.abs() {
+        // Move is horizontal
+        if dx > 0. {
+            MoveDirection::Right
+        } else {
+            MoveDirection::Left
+        }
+    } else {
+        // Move is vertical
+        if dy > 0. {
+            MoveDirection::Down
         } else {
-            // Move is vertical
-            if dy > 0. {
-                MoveDirection::Down
-            } else {
-                MoveDirection::Up
-            }
+            MoveDirection::Up
         }
-    };
+    }
+}
 
-    let source_coords = query
+fn find_coords(query: &Query<(Entity, &GridCoordinates)>, target: Entity) -> GridCoordinates {
+    query
         .iter()
-        .find_map(|(entity, grid_coordinates)| {
-            (entity == pointer_event.target).then(|| grid_coordinates.clone())
-        })
-        .expect("Failed to find event source");
-
-    move_event_tx.send(RequestMoveEvent {
-        move_direction,
-        source_coords,
-    });
+        .find_map(|(entity, grid_coordinates)| (entity == target).then(|| grid_coordinates.clone()))
+        .expect("Failed to find event source")
+}
+
+pub fn handle_tile_drags(
+    mut drag_event_rx: EventReader<TileDragEvent>,
+    query: Query<(Entity, &GridCoordinates)>,
+    mut preview: ResMut<MovePreview>,
+) {
+    for TileDragEvent { target, distance } in drag_event_rx.iter() {
+        let request = (distance.length() >= MIN_PREVIEW_DISTANCE)
+            .then(|| (find_coords(&query, *target), drag_direction(*distance)));
+        // Only touch the preview when it changes, as it is rebuilt every time it does
+        if preview.request != request {
+            preview.request = request;
+        }
+    }
+}
+
+pub fn handle_tile_drag_ends(
+    mut drag_end_event_rx: EventReader<TileDragEndEvent>,
+    query: Query<(Entity, &GridCoordinates)>,
+    mut move_event_tx: EventWriter<RequestMoveEvent>,
+    mut preview: ResMut<MovePreview>,
+) {
+    for TileDragEndEvent { target, distance } in drag_end_event_rx.iter() {
+        preview.request = None;
+        move_event_tx.send(RequestMoveEvent {
+            move_direction: drag_direction(*distance),
+            source_coords: find_coords(&query, *target),
+        });
+    }
 }
@@ -0,0 +1,190 @@
+use bevy::prelude::{
+    default, BuildChildren, ChildBuilder, Color, Commands, Component, DespawnRecursive,
+    DetectChanges, Entity, EventReader, Query, Res, ResMut, Resource, SpatialBundle, Transform,
+    Vec2, Vec3, With,
+};
+use bevy_mod_picking::prelude::Pickable;
+
+use am4lgam8_core::{
+    grid::{GridCoordinates, MoveTileEvent, TileGrid},
+    moves::{MergeTilesEvent, MoveDirection, SolidifyEvent, ValidEvent, ValidatedEventQueue},
+};
+
+use crate::{
+    bundles::line::{line_sprite, tile_outline},
+    constants::{foreground_color, warning_color, TILE_SIZE},
+};
+
+use super::{grid::ValidTurnEvent, OnPlayingScreen};
+
+// Components
+
+/// Part of the preview of the move being dragged
+#[derive(Component)]
+pub struct MoveGhost;
+
+// Resources
+
+/// The move the player is dragging towards, if the drag is long enough to tell, or the move
+/// suggested by the AI
+#[derive(Debug, Default, Resource)]
+pub struct MovePreview {
+    pub request: Option<(GridCoordinates, MoveDirection)>,
+}
+
+// Systems
+
+pub fn reset_move_preview(mut preview: ResMut<MovePreview>) {
+    *preview = MovePreview::default();
+}
+
+/// A suggested move is only shown until the next turn is played
+pub fn clear_move_preview_on_turn(
+    mut valid_turn_event_rx: EventReader<ValidTurnEvent>,
+    mut preview: ResMut<MovePreview>,
+) {
+    if valid_turn_event_rx.iter().count() > 0 && preview.request.is_some() {
+        preview.request = None;
+    }
+}
+
+/// Shows what the dragged move would do, without applying it : where tiles end up, which ones
+/// merge and which cells explode, or a red arrow if the move is not possible.
+pub fn update_move_preview(
+    mut commands: Commands,
+    preview: Res<MovePreview>,
+    tile_grid: Res<TileGrid>,
+    ghosts: Query<Entity, With<MoveGhost>>,
+) {
+    // The grid can also change during a drag, when a move is undone
+    if !preview.is_changed() && !tile_grid.is_changed() {
+        return;
+    }
+
+    for entity in ghosts.iter() {
+        commands.add(DespawnRecursive { entity });
+    }
+
+    let Some((source_coords, move_direction)) = &preview.request else {
+        return;
+    };
+    let candidate_coords =
+        source_coords.candidate_coords_for_dir(*move_direction, tile_grid.config());
+    let ghost_color = foreground_color().with_a(0.7);
+
+    match ValidatedEventQueue::validate_move(&tile_grid, candidate_coords, *move_direction) {
+        ValidatedEventQueue::InvalidMove => {
+            spawn_ghost(&mut commands, source_coords, |parent| {
+                spawn_outline(parent, warning_color());
+                spawn_arrow(parent, *move_direction, warning_color());
+            });
+        }
+        ValidatedEventQueue::ValidMove(events) => {
+            for event in events {
+                match event {
+                    ValidEvent::Move(MoveTileEvent { source, target }) => {
+                        spawn_ghost(&mut commands, &source, |parent| {
+                            spawn_arrow(parent, *move_direction, ghost_color);
+                        });
+                        spawn_ghost(&mut commands, &target, |parent| {
+                            spawn_outline(parent, ghost_color);
+                        });
+                    }
+                    ValidEvent::Merge(MergeTilesEvent { source, target, .. }) => {
+                        spawn_ghost(&mut commands, &source, |parent| {
+                            spawn_arrow(parent, *move_direction, ghost_color);
+                        });
+                        spawn_ghost(&mut commands, &target, |parent| {
+                            spawn_outline(parent, ghost_color);
+                            spawn_fill(parent, ghost_color.with_a(0.3));
+                        });
+                    }
+                    ValidEvent::Solidify(SolidifyEvent { source, target }) => {
+                        spawn_ghost(&mut commands, &source, |parent| {
+                            spawn_fill(parent, ghost_color.with_a(0.3));
+                            spawn_arrow(parent, *move_direction, ghost_color);
+                        });
+                        spawn_ghost(&mut commands, &target, |parent| {
+                            spawn_fill(parent, ghost_color.with_a(0.3));
+                        });
+                    }
+                    ValidEvent::Explosions(explosion) => {
+                        for coords in explosion
+                            .area()
+                            .into_iter()
+                            .filter(|coords| tile_grid.is_inside(coords))
+                        {
+                            spawn_ghost(&mut commands, &coords, |parent| {
+                                spawn_fill(parent, warning_color().with_a(0.35));
+                            });
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn spawn_ghost(
+    commands: &mut Commands,
+    coords: &GridCoordinates,
+    spawn_children: impl FnOnce(&mut ChildBuilder),
+) {
+    commands
+        .spawn((
+            SpatialBundle {
+                transform: Transform::from_translation(Vec3::new(
+                    coords.x as f32 * TILE_SIZE,
+                    coords.y as f32 * TILE_SIZE,
+                    1.,
+                )),
+                ..default()
+            },
+            MoveGhost,
+            OnPlayingScreen,
+        ))
+        .with_children(spawn_children);
+}
+
+fn spawn_outline(parent: &mut ChildBuilder, color: Color) {
+    for line in tile_outline(color) {
+        parent.spawn((line, Pickable::IGNORE));
+    }
+}
+
+fn spawn_fill(parent: &mut ChildBuilder, color: Color) {
+    parent.spawn((
+        line_sprite(Vec2::ZERO, Vec2::splat(TILE_SIZE - 2.), color),
+        Pickable::IGNORE,
+    ));
+}
+
+/// A pixel arrow going from the center of the tile to the center of the next one
+fn spawn_arrow(parent: &mut ChildBuilder, move_direction: MoveDirection, color: Color) {
+    let forward = match move_direction {
+        MoveDirection::Up => Vec2::Y,
+        MoveDirection::Down => Vec2::NEG_Y,
+        MoveDirection::Left => Vec2::NEG_X,
+        MoveDirection::Right => Vec2::X,
+    };
+    let across = forward.perp().abs();
+
+    // The shaft, then the head, narrowing down to the tip. Tiles have an even size, so the arrow
+    // is 2 pixels wide to stay on the pixel grid.
+    let shaft_length = TILE_SIZE - 6.;
+    let mut parts = vec![(
+        forward * (3. + shaft_length / 2.),
+        forward.abs() * shaft_length + across * 2.,
+    )];
+    for (offset, width) in [
+        (TILE_SIZE - 2.5, 6.),
+        (TILE_SIZE - 1.5, 4.),
+        (TILE_SIZE - 0.5, 2.),
+    ] {
+        parts.push((forward * offset, forward.abs() + across * width));
+    }
+
+    for (center, size) in parts {
+        parent.spawn((line_sprite(center, size, color), Pickable::IGNORE));
+    }
+}
@@ -0,0 +1,60 @@
+use bevy::prelude::{Assets, DetectChangesMut, Res, ResMut, Resource};
+
+use am4lgam8_core::{grid::TileGrid, score::GameScore};
+
+use crate::assets::{GameAssets, SpawnRulesAsset};
+
+use super::replay::ReplayRecorder;
+
+// Resources
+
+/// How far the run has gone. Undoing a move also takes back its turn, so that the move is played
+/// again with the same odds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Resource)]
+pub struct Progression {
+    pub turn: u32,
+    /// From 1, as defined in `spawns.ron`
+    pub level: usize,
+}
+
+impl Default for Progression {
+    fn default() -> Self {
+        Progression { turn: 0, level: 1 }
+    }
+}
+
+// Systems
+
+pub fn reset_progression(mut progression: ResMut<Progression>) {
+    *progression = Progression::default();
+}
+
+/// Follows the turn and the score to find the level of the run, and gives the grid the spawn
+/// table that goes with them. `spawns.ron` is read again every frame, so that its edits apply to
+/// the next spawn.
+pub fn update_progression(
+    game_assets: Res<GameAssets>,
+    spawn_rules: Res<Assets<SpawnRulesAsset>>,
+    recorder: Res<ReplayRecorder>,
+    game_score: Res<GameScore>,
+    mut progression: ResMut<Progression>,
+    mut tile_grid: ResMut<TileGrid>,
+) {
+    let Some(SpawnRulesAsset(rules)) = spawn_rules.get(&game_assets.spawn_rules) else {
+        return;
+    };
+    let turn = recorder.moves_played() as u32;
+    progression.set_if_neq(Progression {
+        turn,
+        level: rules.level_at(turn, game_score.get()),
+    });
+
+    // Same as `GameSession` : if no tile could spawn at this point, the last table is kept
+    let Ok(table) = rules.table_at(turn, game_score.get()) else {
+        return;
+    };
+    // Only touch the grid when the table changes, which would trigger a save
+    if tile_grid.spawn_table() != &table {
+        tile_grid.set_spawn_table(table);
+    }
+}
@@ -0,0 +1,165 @@
+use bevy::{
+    prelude::{
+        Color, Commands, Component, DespawnRecursive, DetectChanges, Entity, NextState, Query, Res,
+        ResMut, Resource, TextBundle, With,
+    },
+    text::{Text, TextStyle},
+    ui::{PositionType, Style, Val},
+};
+
+use am4lgam8_core::{
+    grid::TileGrid,
+    puzzle::{PuzzleGoal, PuzzleOutcome, PuzzleRecords, PuzzleRun},
+    score::GameScore,
+    tile::TileType,
+};
+
+use crate::{assets::GameAssets, constants::foreground_color, core::GameState, storage};
+
+use super::{editor::PuzzleEditor, OnPlayingScreen};
+
+const PUZZLE_RECORDS_FILE: &str = "puzzles.txt";
+
+// Components
+
+/// The goal of the puzzle, and how far it is
+#[derive(Component)]
+pub struct PuzzleLabel;
+
+// Resources
+
+/// The puzzle being played, if the run is one
+#[derive(Debug, Default, Resource)]
+pub struct ActivePuzzle(pub Option<PuzzleRun>);
+
+/// What the level select screen remembers between two puzzles
+#[derive(Debug, Default, Resource)]
+pub struct PuzzleMenu {
+    /// How the last puzzle went
+    pub message: Option<String>,
+}
+
+pub fn load_puzzle_records() -> PuzzleRecords {
+    let Some(text) = storage::load(PUZZLE_RECORDS_FILE) else {
+        return PuzzleRecords::default();
+    };
+    text.parse().unwrap_or_else(|err| {
+        bevy::log::warn!("Ignoring the saved puzzle records: {err}");
+        PuzzleRecords::default()
+    })
+}
+
+fn goal_text(run: &PuzzleRun, tile_grid: &TileGrid) -> String {
+    match run.puzzle().goal {
+        PuzzleGoal::Score(target) => format!("GOAL {target} PTS"),
+        PuzzleGoal::ClearCoins => {
+            let coins = tile_grid
+                .tiles()
+                .filter(|(_, tile_type)| matches!(tile_type, TileType::Coin(_)))
+                .count();
+            format!("GOAL CLEAR {coins} COINS")
+        }
+        PuzzleGoal::ExplodeTiles(target) => {
+            format!("GOAL {}/{target} TILES", run.tiles_exploded())
+        }
+    }
+}
+
+// Conditions
+
+pub fn no_puzzle(puzzle: Res<ActivePuzzle>) -> bool {
+    puzzle.0.is_none()
+}
+
+// Systems
+
+pub fn spawn_puzzle_label(
+    mut commands: Commands,
+    assets: Res<GameAssets>,
+    puzzle: Res<ActivePuzzle>,
+    tile_grid: Res<TileGrid>,
+) {
+    let Some(run) = &puzzle.0 else {
+        return;
+    };
+    // Where the replay label goes, replays and puzzles don't mix
+    commands.spawn((
+        TextBundle::from_section(
+            goal_text(run, &tile_grid),
+            TextStyle {
+                font_size: 20.,
+                color: foreground_color(),
+                font: assets.joystix.clone(),
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(4.),
+            left: Val::Px(4.),
+            ..Default::default()
+        })
+        .with_background_color(Color::rgba(0., 0., 0., 0.5)),
+        PuzzleLabel,
+        OnPlayingScreen,
+    ));
+}
+
+pub fn update_puzzle_label(
+    puzzle: Res<ActivePuzzle>,
+    tile_grid: Res<TileGrid>,
+    mut labels: Query<&mut Text, With<PuzzleLabel>>,
+) {
+    let Some(run) = &puzzle.0 else {
+        return;
+    };
+    if !puzzle.is_changed() && !tile_grid.is_changed() {
+        return;
+    }
+    for mut text in labels.iter_mut() {
+        text.sections[0].value = goal_text(run, &tile_grid);
+    }
+}
+
+/// Back to the level select screen once the puzzle is solved, or can't be anymore. The fewest
+/// moves it was solved in are saved, unless the puzzle was being tested from the editor.
+pub fn check_puzzle_outcome(
+    mut commands: Commands,
+    entities_on_screen: Query<Entity, With<OnPlayingScreen>>,
+    tile_grid: Res<TileGrid>,
+    game_score: Res<GameScore>,
+    mut puzzle: ResMut<ActivePuzzle>,
+    mut records: ResMut<PuzzleRecords>,
+    mut menu: ResMut<PuzzleMenu>,
+    mut editor: ResMut<PuzzleEditor>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let Some(run) = &puzzle.0 else {
+        return;
+    };
+    let name = &run.puzzle().name;
+    let message = match run.outcome(&tile_grid, &game_score) {
+        PuzzleOutcome::Playing => return,
+        PuzzleOutcome::Solved => {
+            let moves = run.moves_played();
+            if !editor.testing && records.record(name, moves) {
+                storage::save(PUZZLE_RECORDS_FILE, &records.to_string());
+            }
+            format!("{name}: solved in {moves} moves!")
+        }
+        PuzzleOutcome::Failed if run.moves_left() == 0 => format!("{name}: out of moves"),
+        PuzzleOutcome::Failed => format!("{name}: stuck"),
+    };
+
+    for entity in entities_on_screen.iter() {
+        commands.add(DespawnRecursive { entity });
+    }
+    puzzle.0 = None;
+    if editor.testing {
+        editor.testing = false;
+        editor.message = Some(message);
+        next_state.set(GameState::Editor);
+    } else {
+        menu.message = Some(message);
+        next_state.set(GameState::LevelSelect);
+    }
+}
@@ -0,0 +1,287 @@
+use std::time::Duration;
+
+use bevy::{
+    prelude::{
+        Assets, Color, Commands, Component, DespawnRecursive, Entity, EventWriter, Input, KeyCode,
+        NextState, Query, Res, ResMut, Resource, TextBundle, With,
+    },
+    text::{Text, TextStyle},
+    time::{Time, Timer, TimerMode},
+    ui::{PositionType, Style, Val},
+};
+use serde::{Deserialize, Serialize};
+
+use am4lgam8_core::{
+    grid::{GridConfig, GridCoordinates, TileGrid},
+    moves::MoveDirection,
+    replay::{Replay, ReplayMove},
+    spawn::SpawnRules,
+};
+
+use crate::{
+    assets::{GameAssets, SpawnRulesAsset},
+    constants::foreground_color,
+    core::GameState,
+    storage,
+};
+
+use super::{movables::RequestMoveEvent, OnPlayingScreen};
+
+// Time between two replayed moves, at normal speed. Long enough for the animations to play out.
+const MOVE_INTERVAL: f32 = 0.6;
+const MIN_SPEED: f32 = 0.25;
+const MAX_SPEED: f32 = 8.;
+
+// Components
+
+#[derive(Component)]
+pub struct ReplayLabel;
+
+// Resources
+
+/// Records the moves of the current run, following undo and redo
+#[derive(Debug, Default, Clone, Resource, Serialize, Deserialize)]
+pub struct ReplayRecorder {
+    replay: Option<Replay>,
+    undone: Vec<ReplayMove>,
+}
+
+impl ReplayRecorder {
+    pub fn start(&mut self, grid_config: GridConfig, seed: u64, spawn_rules: &SpawnRules) {
+        self.replay = Some(Replay::new(grid_config, seed, spawn_rules));
+        self.undone.clear();
+    }
+
+    pub fn record(&mut self, source_coords: GridCoordinates, move_direction: MoveDirection) {
+        if let Some(replay) = &mut self.replay {
+            replay.push(source_coords, move_direction);
+            self.undone.clear();
+        }
+    }
+
+    pub fn undo(&mut self) {
+        if let Some(undone) = self.replay.as_mut().and_then(|replay| replay.moves.pop()) {
+            self.undone.push(undone);
+        }
+    }
+
+    pub fn redo(&mut self) {
+        if let (Some(replay), Some(redone)) = (&mut self.replay, self.undone.pop()) {
+            replay.moves.push(redone);
+        }
+    }
+
+    pub fn replay(&self) -> Option<&Replay> {
+        self.replay.as_ref()
+    }
+
+    pub fn moves_played(&self) -> usize {
+        self.replay
+            .as_ref()
+            .map(|replay| replay.moves.len())
+            .unwrap_or_default()
+    }
+}
+
+/// The replay of the last run, or the one given with `--replay <file>`
+#[derive(Debug, Default, Resource)]
+pub struct LastReplay(pub Option<Replay>);
+
+/// Feeds the moves of a replay back to the game, instead of the player
+#[derive(Debug, Resource)]
+pub struct ReplayPlayback {
+    replay: Option<Replay>,
+    next_move: usize,
+    paused: bool,
+    speed: f32,
+    timer: Timer,
+}
+
+impl Default for ReplayPlayback {
+    fn default() -> Self {
+        ReplayPlayback {
+            replay: None,
+            next_move: 0,
+            paused: false,
+            speed: 1.,
+            timer: Timer::new(Duration::from_secs_f32(MOVE_INTERVAL), TimerMode::Repeating),
+        }
+    }
+}
+
+impl ReplayPlayback {
+    pub fn start(&mut self, replay: Replay) {
+        *self = ReplayPlayback {
+            replay: Some(replay),
+            ..Default::default()
+        };
+    }
+
+    pub fn stop(&mut self) {
+        *self = ReplayPlayback::default();
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.replay.is_some()
+    }
+
+    /// The seed the replayed run must be set up with
+    pub fn seed(&self) -> Option<u64> {
+        self.replay.as_ref().map(|replay| replay.seed)
+    }
+
+    fn next_move(&mut self) -> Option<RequestMoveEvent> {
+        let replay_move = self.replay.as_ref()?.moves.get(self.next_move)?;
+        self.next_move += 1;
+        Some(RequestMoveEvent {
+            move_direction: replay_move.move_direction,
+            source_coords: replay_move.source_coords.clone(),
+        })
+    }
+
+    fn is_finished(&self) -> bool {
+        self.replay
+            .as_ref()
+            .map(|replay| self.next_move >= replay.moves.len())
+            .unwrap_or(true)
+    }
+
+    fn status_text(&self) -> String {
+        if self.is_finished() {
+            "REPLAY END".to_string()
+        } else if self.paused {
+            "PAUSED".to_string()
+        } else {
+            format!("REPLAY x{}", self.speed)
+        }
+    }
+}
+
+// Conditions
+
+/// The replay is playing, not the player
+pub fn no_replay(playback: Res<ReplayPlayback>) -> bool {
+    !playback.is_active()
+}
+
+// Systems
+
+/// The replay is checked against the spawn rules the run starts with, edits of `spawns.ron` made
+/// during the run can't be verified
+pub fn start_recording(
+    game_assets: Res<GameAssets>,
+    spawn_rules: Res<Assets<SpawnRulesAsset>>,
+    tile_grid: Res<TileGrid>,
+    mut recorder: ResMut<ReplayRecorder>,
+) {
+    let default_rules = SpawnRules::default();
+    let rules = spawn_rules
+        .get(&game_assets.spawn_rules)
+        .map_or(&default_rules, |SpawnRulesAsset(rules)| rules);
+    recorder.start(*tile_grid.config(), tile_grid.seed(), rules);
+}
+
+/// Keeps the replay of the run that just ended, and saves it along with the other files of the
+/// game. Watching a replay records the same moves again, it is not saved twice.
+pub fn keep_last_replay(
+    recorder: Res<ReplayRecorder>,
+    playback: Res<ReplayPlayback>,
+    mut last_replay: ResMut<LastReplay>,
+) {
+    if playback.is_active() {
+        return;
+    }
+    let Some(replay) = recorder.replay() else {
+        return;
+    };
+    storage::save(
+        &format!("replays/run-{}.replay", storage::timestamp()),
+        &replay.to_string(),
+    );
+    last_replay.0 = Some(replay.clone());
+}
+
+pub fn spawn_replay_label(
+    mut commands: Commands,
+    assets: Res<GameAssets>,
+    playback: Res<ReplayPlayback>,
+) {
+    if !playback.is_active() {
+        return;
+    }
+    commands.spawn((
+        TextBundle::from_section(
+            playback.status_text(),
+            TextStyle {
+                font_size: 20.,
+                color: foreground_color(),
+                font: assets.joystix.clone(),
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(4.),
+            left: Val::Px(4.),
+            ..Default::default()
+        })
+        .with_background_color(Color::rgba(0., 0., 0., 0.5)),
+        ReplayLabel,
+        OnPlayingScreen,
+    ));
+}
+
+/// Space pauses, N steps one move while paused, up and down change the speed, Escape quits
+pub fn play_replay(
+    mut commands: Commands,
+    keys: Res<Input<KeyCode>>,
+    mut time: ResMut<Time>,
+    mut playback: ResMut<ReplayPlayback>,
+    mut move_event_tx: EventWriter<RequestMoveEvent>,
+    mut labels: Query<&mut Text, With<ReplayLabel>>,
+    entities_on_screen: Query<Entity, With<OnPlayingScreen>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if !playback.is_active() {
+        return;
+    }
+
+    if keys.just_pressed(KeyCode::Escape) {
+        playback.stop();
+        time.set_relative_speed(1.);
+        for entity in entities_on_screen.iter() {
+            commands.add(DespawnRecursive { entity });
+        }
+        next_state.set(GameState::TitleScreen);
+        return;
+    }
+
+    if keys.just_pressed(KeyCode::Space) {
+        playback.paused = !playback.paused;
+    }
+    if keys.just_pressed(KeyCode::Up) {
+        playback.speed = (playback.speed * 2.).min(MAX_SPEED);
+    }
+    if keys.just_pressed(KeyCode::Down) {
+        playback.speed = (playback.speed / 2.).max(MIN_SPEED);
+    }
+    // Scaling time also speeds up the animations
+    time.set_relative_speed(playback.speed);
+
+    let delta = time.delta();
+    let step = playback.paused && keys.just_pressed(KeyCode::N);
+    let tick = !playback.paused && playback.timer.tick(delta).just_finished();
+    if step || tick {
+        if let Some(event) = playback.next_move() {
+            move_event_tx.send(event);
+        }
+    }
+
+    for mut text in labels.iter_mut() {
+        text.sections[0].value = playback.status_text();
+    }
+}
+
+pub fn stop_replay(mut time: ResMut<Time>, mut playback: ResMut<ReplayPlayback>) {
+    playback.stop();
+    time.set_relative_speed(1.);
+}
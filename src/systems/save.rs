@@ -0,0 +1,108 @@
+use bevy::{
+    app::AppExit,
+    prelude::{DetectChanges, EventReader, Res, ResMut, Resource},
+    window::WindowCloseRequested,
+};
+use serde::{Deserialize, Serialize};
+
+use am4lgam8_core::{grid::TileGrid, history::MoveHistory, score::GameScore};
+
+use crate::storage;
+
+use super::replay::{ReplayPlayback, ReplayRecorder};
+
+const SAVE_FILE: &str = "run.ron";
+
+/// A run that was left before it was over
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedRun {
+    /// The grid carries the RNG, so the run goes on with the tiles it would have had
+    pub tile_grid: TileGrid,
+    pub score: GameScore,
+    /// The moves played so far, so that the replay of the run is complete
+    pub recorder: ReplayRecorder,
+    pub practice: bool,
+}
+
+impl SavedRun {
+    pub fn moves_played(&self) -> usize {
+        self.recorder.moves_played()
+    }
+}
+
+// Resources
+
+/// The saved run, offered by the title screen's Continue button
+#[derive(Debug, Default, Resource)]
+pub struct SavedRunSlot(pub Option<SavedRun>);
+
+/// The run to pick back up when entering the playing screen, instead of starting a new one
+#[derive(Debug, Default, Resource)]
+pub struct ResumedRun(pub Option<SavedRun>);
+
+// Systems
+
+pub fn load_saved_run(mut slot: ResMut<SavedRunSlot>) {
+    slot.0 = storage::load(SAVE_FILE).and_then(|text| {
+        ron::from_str(&text)
+            .map_err(|err| bevy::log::warn!("Ignoring the saved run: {err}"))
+            .ok()
+    });
+}
+
+pub fn no_run_to_resume(resumed_run: Res<ResumedRun>) -> bool {
+    resumed_run.0.is_none()
+}
+
+/// Restores what the playing screen reset, once `setup_grid` has restored the grid itself
+pub fn resume_run(
+    mut resumed_run: ResMut<ResumedRun>,
+    mut game_score: ResMut<GameScore>,
+    mut history: ResMut<MoveHistory>,
+    mut recorder: ResMut<ReplayRecorder>,
+) {
+    let Some(saved_run) = resumed_run.0.take() else {
+        return;
+    };
+
+    *game_score = saved_run.score;
+    *recorder = saved_run.recorder;
+    history.clear();
+    if saved_run.practice {
+        history.mark_practice();
+    }
+}
+
+/// Saves the run whenever the grid changes, and when the game is closed. Replays are not saved.
+pub fn autosave_run(
+    mut app_exit_rx: EventReader<AppExit>,
+    mut close_requested_rx: EventReader<WindowCloseRequested>,
+    tile_grid: Res<TileGrid>,
+    game_score: Res<GameScore>,
+    history: Res<MoveHistory>,
+    recorder: Res<ReplayRecorder>,
+    playback: Res<ReplayPlayback>,
+) {
+    let closing = app_exit_rx.iter().count() + close_requested_rx.iter().count() > 0;
+    if playback.is_active() || !(closing || tile_grid.is_changed()) {
+        return;
+    }
+
+    let saved_run = SavedRun {
+        tile_grid: tile_grid.clone(),
+        score: *game_score,
+        recorder: recorder.clone(),
+        practice: history.is_practice(),
+    };
+    match ron::to_string(&saved_run) {
+        Ok(text) => storage::save(SAVE_FILE, &text),
+        Err(err) => bevy::log::warn!("Failed to save the run: {err}"),
+    }
+}
+
+/// A finished run can't be continued
+pub fn clear_saved_run(playback: Res<ReplayPlayback>) {
+    if !playback.is_active() {
+        storage::remove(SAVE_FILE);
+    }
+}
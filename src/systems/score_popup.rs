@@ -0,0 +1,115 @@
+use std::time::Duration;
+
+use bevy::{
+    prelude::{
+        Commands, Component, DespawnRecursive, Entity, Event, EventReader, Query, Res, Transform,
+        Vec3,
+    },
+    text::{Text, Text2dBundle, TextAlignment, TextSection, TextStyle},
+    time::{Time, Timer, TimerMode},
+};
+
+use am4lgam8_core::{grid::GridCoordinates, score::ScoreBreakdown};
+
+use crate::{
+    assets::GameAssets,
+    constants::{foreground_color, warning_color, TILE_SIZE},
+};
+
+use super::OnPlayingScreen;
+
+// Laid out at a bigger size and scaled down, like the labels of the coins
+const POPUP_FONT_SIZE: f32 = 28.;
+const BONUS_FONT_SIZE: f32 = 20.;
+const POPUP_SCALE: f32 = 0.25;
+const POPUP_DURATION: f32 = 1.2;
+// How far the popup floats up before it's gone, in logic pixels
+const POPUP_RISE: f32 = 10.;
+
+// Events
+
+/// What a turn scored, shown over `coords`
+#[derive(Debug, Event)]
+pub struct ScoreBreakdownEvent {
+    pub breakdown: ScoreBreakdown,
+    pub coords: GridCoordinates,
+}
+
+// Components
+
+#[derive(Component)]
+pub struct ScorePopup {
+    timer: Timer,
+    start: Vec3,
+}
+
+// Systems
+
+/// `+N`, with the multiplier of the turn and the bonuses that make it up when there are any
+pub fn spawn_score_popups(
+    mut commands: Commands,
+    mut score_event_rx: EventReader<ScoreBreakdownEvent>,
+    assets: Res<GameAssets>,
+) {
+    for ScoreBreakdownEvent { breakdown, coords } in score_event_rx.iter() {
+        let style = |font_size, color| TextStyle {
+            font: assets.joystix.clone(),
+            font_size,
+            color,
+        };
+        let mut sections = vec![TextSection::new(
+            format!("+{}", breakdown.total()),
+            style(POPUP_FONT_SIZE, foreground_color()),
+        )];
+        if breakdown.multiplier() > 1 {
+            sections.push(TextSection::new(
+                format!(" x{}", breakdown.multiplier()),
+                style(POPUP_FONT_SIZE, warning_color()),
+            ));
+            let bonuses: Vec<String> = breakdown.bonuses.iter().map(|b| b.to_string()).collect();
+            sections.push(TextSection::new(
+                format!("\n{}", bonuses.join(" ")),
+                style(BONUS_FONT_SIZE, foreground_color()),
+            ));
+        }
+
+        // Above the tiles, the explosions and the cursor
+        let start = Vec3::new(
+            coords.x as f32 * TILE_SIZE,
+            (coords.y as f32 + 0.5) * TILE_SIZE,
+            3.,
+        );
+        commands.spawn((
+            Text2dBundle {
+                text: Text::from_sections(sections).with_alignment(TextAlignment::Center),
+                transform: Transform::from_translation(start).with_scale(Vec3::splat(POPUP_SCALE)),
+                ..Default::default()
+            },
+            ScorePopup {
+                timer: Timer::new(Duration::from_secs_f32(POPUP_DURATION), TimerMode::Once),
+                start,
+            },
+            OnPlayingScreen,
+        ));
+    }
+}
+
+/// Floats the popups up while they fade out
+pub fn animate_score_popups(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut popups: Query<(Entity, &mut ScorePopup, &mut Transform, &mut Text)>,
+) {
+    for (entity, mut popup, mut transform, mut text) in popups.iter_mut() {
+        popup.timer.tick(time.delta());
+        if popup.timer.finished() {
+            commands.add(DespawnRecursive { entity });
+            continue;
+        }
+        let progress = popup.timer.percent();
+        transform.translation = popup.start + Vec3::Y * POPUP_RISE * progress;
+        for section in text.sections.iter_mut() {
+            section.style.color.set_a(1. - progress * progress);
+        }
+    }
+}
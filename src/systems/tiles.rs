@@ -1,18 +1,30 @@
 use std::time::Duration;
 
 use bevy::{
-    prelude::{Commands, Entity, EventReader, EventWriter, Query, Res, ResMut, Transform},
+    prelude::{
+        Commands, Entity, Event, EventReader, EventWriter, Query, Res, ResMut, Transform, With,
+        Without,
+    },
     time::{Timer, TimerMode},
     utils::{HashMap, HashSet},
 };
 
+use am4lgam8_core::{
+    grid::{GridCoordinates, MoveTileEvent, TileGrid},
+    history::{MoveHistory, Snapshot},
+    moves::{
+        ChainReaction, ExplosionEvent, MergeTilesEvent, SolidifyEvent, ValidEvent,
+        ValidatedEventQueue,
+    },
+    score::GameScore,
+    tile::TileType,
+};
+
 use crate::{
     assets::GameAssets,
-    bundles::{explosion::ExplosionBundle, tile::spawn_tile_type_bundle},
-    game::{
-        grid::{GridCoordinates, MoveTileEvent, TileGrid},
-        moves::{ExplosionEvent, MergeTilesEvent, ValidEvent, ValidatedEventQueue},
-        tile::{ExplosionResult, TileType},
+    bundles::{
+        explosion::ExplosionBundle,
+        tile::{spawn_solidified_wall, spawn_tile_type_bundle},
     },
 };
 
@@ -21,18 +33,35 @@ use super::{
     grid::ValidTurnEvent,
     marked_for_deletion::MarkedForDeletion,
     movables::RequestMoveEvent,
-    ui::GameScore,
+    puzzle::ActivePuzzle,
+    replay::ReplayRecorder,
+    score_popup::ScoreBreakdownEvent,
     OnPlayingScreen,
 };
 
+// Time between two waves of a chain reaction
+const CHAIN_WAVE_DELAY: f32 = 0.2;
+
+// Events
+
+/// The explosions of a move, once applied to the grid
+#[derive(Debug, Event)]
+pub struct ChainReactionEvent(pub ChainReaction);
+
 /// Validates incoming RequestMoveEvent into ValidMoveEvent
 pub fn handle_requested_move_events(
     mut requested_event_rx: EventReader<RequestMoveEvent>,
     mut move_tile_event_tx: EventWriter<MoveTileEvent>,
     mut combine_event_tx: EventWriter<MergeTilesEvent>,
-    mut explosion_event_tx: EventWriter<ExplosionEvent>,
+    mut chain_reaction_tx: EventWriter<ChainReactionEvent>,
+    mut solidify_event_tx: EventWriter<SolidifyEvent>,
+    mut score_event_tx: EventWriter<ScoreBreakdownEvent>,
     mut valid_turn_tx: EventWriter<ValidTurnEvent>,
     mut tile_grid: ResMut<TileGrid>,
+    mut game_score: ResMut<GameScore>,
+    mut history: ResMut<MoveHistory>,
+    mut recorder: ResMut<ReplayRecorder>,
+    mut puzzle: ResMut<ActivePuzzle>,
 ) {
     for move_event in requested_event_rx.iter() {
         let RequestMoveEvent {
@@ -40,7 +69,8 @@ pub fn handle_requested_move_events(
             source_coords,
         } = move_event;
 
-        let candidate_coords = source_coords.candidate_coords_for_dir(*move_direction);
+        let candidate_coords =
+            source_coords.candidate_coords_for_dir(*move_direction, tile_grid.config());
 
         let validated_event_queue =
             ValidatedEventQueue::validate_move(&tile_grid, candidate_coords, *move_direction);
@@ -48,14 +78,37 @@ pub fn handle_requested_move_events(
         match validated_event_queue {
             ValidatedEventQueue::InvalidMove => (),
             ValidatedEventQueue::ValidMove(events) => {
-                tile_grid.apply_events(&events);
+                history.record(Snapshot {
+                    tile_grid: tile_grid.clone(),
+                    score: *game_score,
+                });
+                let chain_reaction = tile_grid.apply_events(&events);
+                let breakdown = game_score.score_turn(&chain_reaction, &tile_grid);
+                // Shown where the move set off its first explosion
+                let blast_center = chain_reaction
+                    .waves
+                    .first()
+                    .and_then(|wave| wave.explosions.first())
+                    .map(|explosion| explosion.target.clone());
+                if let Some(coords) = blast_center.filter(|_| breakdown.total() > 0) {
+                    score_event_tx.send(ScoreBreakdownEvent { breakdown, coords });
+                }
+                recorder.record(source_coords.clone(), *move_direction);
+                if let Some(run) = &mut puzzle.0 {
+                    run.record_move(&chain_reaction);
+                }
                 for event in events {
                     match event {
                         ValidEvent::Move(e) => move_tile_event_tx.send(e),
                         ValidEvent::Merge(e) => combine_event_tx.send(e),
-                        ValidEvent::Explosions(e) => explosion_event_tx.send(e),
+                        // Sent all at once below, with the bombs they set off
+                        ValidEvent::Explosions(_) => (),
+                        ValidEvent::Solidify(e) => solidify_event_tx.send(e),
                     }
                 }
+                if !chain_reaction.waves.is_empty() {
+                    chain_reaction_tx.send(ChainReactionEvent(chain_reaction));
+                }
                 valid_turn_tx.send(ValidTurnEvent);
             }
         }
@@ -120,50 +173,77 @@ pub fn handle_combine_events(
         }
 
         if let Some(tile_type) = resulting_type {
-            spawn_tile_type_bundle(
-                &mut commands,
-                assets.tileset.clone(),
-                *tile_type,
-                target.x,
-                target.y,
-            );
+            spawn_tile_type_bundle(&mut commands, &assets, *tile_type, target.x, target.y);
         }
     }
 }
 
-pub fn handle_explosion_events(
+/// The blocks are replaced by walls, which land over them
+pub fn handle_solidify_events(
     mut commands: Commands,
-    mut explosion_event_rx: EventReader<ExplosionEvent>,
-    query: Query<(Entity, &GridCoordinates, &TileType)>,
-    mut game_score: ResMut<GameScore>,
+    mut solidify_event_rx: EventReader<SolidifyEvent>,
+    query: Query<(Entity, &GridCoordinates), With<TileType>>,
     assets: Res<GameAssets>,
 ) {
-    let mut grid_coords_to_delete: HashSet<GridCoordinates> = HashSet::default();
-    let mut events = Vec::default();
-    for event in explosion_event_rx.iter() {
-        events.push(event.clone());
-        let ExplosionEvent { target } = event;
-        grid_coords_to_delete.extend(target.explosion_radius());
+    for SolidifyEvent { source, target } in solidify_event_rx.iter() {
+        for (entity, grid_coords) in query.iter() {
+            if grid_coords == source || grid_coords == target {
+                commands.entity(entity).insert(MarkedForDeletion(Timer::new(
+                    Duration::from_secs_f32(0.1),
+                    TimerMode::Once,
+                )));
+            }
+        }
+        for coords in [source, target] {
+            spawn_solidified_wall(&mut commands, &assets, coords.x, coords.y);
+        }
     }
+}
 
+/// Sets off the waves of the chain reaction one after the other. The tiles they destroy are
+/// picked right away, before the tiles of the move slide into place. Damaged walls are replaced
+/// by cracked ones.
+pub fn handle_explosion_events(
+    mut commands: Commands,
+    mut chain_reaction_rx: EventReader<ChainReactionEvent>,
+    query: Query<(Entity, &GridCoordinates), (With<TileType>, Without<MarkedForDeletion>)>,
+    tile_grid: Res<TileGrid>,
+    assets: Res<GameAssets>,
+) {
     let assets = &*assets;
-    for (entity, coords, tile_type) in query.iter() {
-        if !grid_coords_to_delete.contains(coords) {
-            continue;
+    for ChainReactionEvent(chain_reaction) in chain_reaction_rx.iter() {
+        // Kept up to date with the cracked walls spawned by the waves, which a later wave can hit
+        let mut entities_at: HashMap<GridCoordinates, Vec<Entity>> = HashMap::default();
+        for (entity, coords) in query.iter() {
+            entities_at.entry(coords.clone()).or_default().push(entity);
         }
 
-        match tile_type.explosion_result() {
-            ExplosionResult::NoExplosion => continue,
-            ExplosionResult::ScorePoints(points) => {
-                game_score.add(points);
-                commands.spawn((
-                    ExplosionBundle::new(&assets, coords.clone()),
-                    OnPlayingScreen,
-                ));
-                commands.entity(entity).insert(MarkedForDeletion(Timer::new(
-                    Duration::from_secs_f32(0.1),
-                    TimerMode::Once,
-                )));
+        for (depth, wave) in chain_reaction.waves.iter().enumerate() {
+            let delay = depth as f32 * CHAIN_WAVE_DELAY;
+            for (coords, _) in wave.destroyed.iter().chain(&wave.damaged) {
+                for entity in entities_at.remove(coords).unwrap_or_default() {
+                    commands.entity(entity).insert(MarkedForDeletion(Timer::new(
+                        Duration::from_secs_f32(0.1 + delay),
+                        TimerMode::Once,
+                    )));
+                }
+            }
+            for (coords, tile_type) in &wave.damaged {
+                let entity =
+                    spawn_tile_type_bundle(&mut commands, assets, *tile_type, coords.x, coords.y);
+                entities_at.insert(coords.clone(), vec![entity]);
+            }
+
+            // The whole blast is shown, empty cells included, so that its shape can be seen. The
+            // border stands, and is left alone.
+            let blast: HashSet<GridCoordinates> = wave
+                .explosions
+                .iter()
+                .flat_map(ExplosionEvent::area)
+                .filter(|coords| tile_grid.is_inside(coords))
+                .collect();
+            for coords in blast {
+                commands.spawn((ExplosionBundle::new(assets, coords, delay), OnPlayingScreen));
             }
         }
     }
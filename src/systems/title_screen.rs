@@ -1,20 +1,33 @@
 use bevy::{
     prelude::{
-        BuildChildren, ButtonBundle, Commands, Component, DespawnRecursive, Entity, NextState,
-        NodeBundle, Query, Rect, Res, ResMut, TextBundle, Transform, Vec3,
+        BuildChildren, ButtonBundle, Changed, Commands, Component, DespawnRecursive, Entity,
+        EventReader, Input, KeyCode, NextState, NodeBundle, Query, Rect, Res, ResMut, TextBundle,
+        Transform, With,
     },
     sprite::{Sprite, SpriteBundle},
-    text::TextStyle,
-    ui::{AlignItems, Interaction, JustifyContent, PositionType, Style, Val},
+    text::{Text, TextStyle},
+    ui::{
+        AlignItems, FlexDirection, Interaction, JustifyContent, PositionType, Style, UiRect, Val,
+    },
+    window::ReceivedCharacter,
 };
 
+use am4lgam8_core::{blitz::BlitzClock, daily::DailyHistory, grid::GridConfig};
+
 use crate::{
     assets::GameAssets,
-    constants::{
-        background_color, foreground_color, GAME_LOGIC_HEIGHT, GAME_LOGIC_WIDTH, GRID_SIZE,
-        TILE_SIZE,
-    },
+    constants::{background_color, foreground_color},
     core::GameState,
+    layout::GridLayout,
+    storage,
+};
+
+use super::{
+    blitz::ActiveBlitz,
+    daily::ActiveDaily,
+    grid::{EndlessConfig, SeedSetting},
+    replay::{LastReplay, ReplayPlayback},
+    save::{ResumedRun, SavedRunSlot},
 };
 
 #[derive(Component)]
@@ -23,21 +36,75 @@ pub struct OnTitleScreen;
 #[derive(Component)]
 pub struct PlayButton;
 
-pub fn setup(mut commands: Commands, assets: Res<GameAssets>) {
+/// Only shown when a run was left before it was over
+#[derive(Component)]
+pub struct ContinueButton;
+
+#[derive(Component)]
+pub struct GridSizeButton;
+
+#[derive(Component)]
+pub struct GridSizeLabel;
+
+/// Clicking it goes back to a random seed, typing digits sets the seed
+#[derive(Component)]
+pub struct SeedButton;
+
+#[derive(Component)]
+pub struct SeedLabel;
+
+#[derive(Component)]
+pub struct HighScoresButton;
+
+/// The run is played against the clock
+#[derive(Component)]
+pub struct BlitzButton;
+
+#[derive(Component)]
+pub struct PuzzlesButton;
+
+/// Only one attempt a day, the button says when it was used
+#[derive(Component)]
+pub struct DailyButton;
+
+/// Only shown when there is a replay to watch
+#[derive(Component)]
+pub struct ReplayButton;
+
+fn grid_size_text(grid_config: &GridConfig) -> String {
+    format!("{}x{}", grid_config.width, grid_config.height)
+}
+
+fn seed_text(seed_setting: &SeedSetting) -> String {
+    match seed_setting.0 {
+        Some(seed) => format!("Seed: {seed}"),
+        None => "Seed: random".to_string(),
+    }
+}
+
+#[allow(clippy::needless_update)]
+pub fn setup(
+    mut commands: Commands,
+    assets: Res<GameAssets>,
+    grid_config: Res<GridConfig>,
+    seed_setting: Res<SeedSetting>,
+    last_replay: Res<LastReplay>,
+    saved_run: Res<SavedRunSlot>,
+    daily_history: Res<DailyHistory>,
+) {
+    // The buttons below Continue move down to make room for it. The last rows sit closer to the
+    // ones above, so that everything fits.
+    let offset = if saved_run.0.is_some() { 10. } else { 0. };
+
+    // The title screen is drawn for the default grid size
+    let layout = GridLayout::default();
     commands.spawn((
         SpriteBundle {
             sprite: Sprite {
-                rect: Some(Rect::new(0., 0., GAME_LOGIC_WIDTH, GAME_LOGIC_HEIGHT)),
-                ..Default::default()
-            },
-            transform: Transform {
-                translation: Vec3::new(
-                    TILE_SIZE * (GRID_SIZE as f32 / 2. - 0.5),
-                    TILE_SIZE * (GRID_SIZE as f32 / 2. - 0.5) - 3.,
-                    1.,
-                ),
+                rect: Some(Rect::new(0., 0., layout.logic_size.x, layout.logic_size.y)),
                 ..Default::default()
             },
+            transform: Transform::from_translation(layout.center.extend(1.)),
             texture: assets.title_screen.clone(),
             ..Default::default()
         },
@@ -52,6 +119,7 @@ pub fn setup(mut commands: Commands, assets: Res<GameAssets>) {
                     height: Val::Percent(100.0),
                     align_items: AlignItems::Center,
                     justify_content: JustifyContent::Center,
+                    flex_direction: FlexDirection::Column,
                     ..Default::default()
                 },
                 ..Default::default()
@@ -87,9 +155,189 @@ pub fn setup(mut commands: Commands, assets: Res<GameAssets>) {
                         },
                     ));
                 });
+            if let Some(saved_run) = &saved_run.0 {
+                parent
+                    .spawn((
+                        ButtonBundle {
+                            style: Style {
+                                position_type: PositionType::Relative,
+                                top: Val::Px(100.),
+                                height: Val::Px(30.0),
+                                padding: UiRect::horizontal(Val::Px(10.)),
+                                justify_content: JustifyContent::Center,
+                                align_items: AlignItems::Center,
+                                ..Default::default()
+                            },
+                            background_color: bevy::ui::BackgroundColor(foreground_color()),
+                            ..Default::default()
+                        },
+                        ContinueButton,
+                    ))
+                    .with_children(|parent| {
+                        parent.spawn(TextBundle::from_section(
+                            format!("Continue ({} moves)", saved_run.moves_played()),
+                            TextStyle {
+                                font_size: 20.0,
+                                color: background_color(),
+                                font: assets.joystix.clone(),
+                            },
+                        ));
+                    });
+            }
+            parent
+                .spawn((
+                    ButtonBundle {
+                        style: Style {
+                            position_type: PositionType::Relative,
+                            top: Val::Px(100. + offset),
+                            width: Val::Px(150.0),
+                            height: Val::Px(30.0),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..Default::default()
+                        },
+                        background_color: bevy::ui::BackgroundColor(foreground_color()),
+                        ..Default::default()
+                    },
+                    GridSizeButton,
+                ))
+                .with_children(|parent| {
+                    parent.spawn((
+                        TextBundle::from_section(
+                            grid_size_text(&grid_config),
+                            TextStyle {
+                                font_size: 20.0,
+                                color: background_color(),
+                                font: assets.joystix.clone(),
+                            },
+                        ),
+                        GridSizeLabel,
+                    ));
+                });
+            parent
+                .spawn((
+                    ButtonBundle {
+                        style: Style {
+                            position_type: PositionType::Relative,
+                            top: Val::Px(110. + offset),
+                            height: Val::Px(30.0),
+                            padding: UiRect::horizontal(Val::Px(10.)),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..Default::default()
+                        },
+                        background_color: bevy::ui::BackgroundColor(foreground_color()),
+                        ..Default::default()
+                    },
+                    SeedButton,
+                ))
+                .with_children(|parent| {
+                    parent.spawn((
+                        TextBundle::from_section(
+                            seed_text(&seed_setting),
+                            TextStyle {
+                                font_size: 20.0,
+                                color: background_color(),
+                                font: assets.joystix.clone(),
+                            },
+                        ),
+                        SeedLabel,
+                    ));
+                });
+            // Side by side, two to a row, there is no room left for more rows
+            let row = |top| NodeBundle {
+                style: Style {
+                    position_type: PositionType::Relative,
+                    top: Val::Px(top),
+                    flex_direction: FlexDirection::Row,
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+            let button = |margin| ButtonBundle {
+                style: Style {
+                    height: Val::Px(30.0),
+                    margin,
+                    padding: UiRect::horizontal(Val::Px(10.)),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..Default::default()
+                },
+                background_color: bevy::ui::BackgroundColor(foreground_color()),
+                ..Default::default()
+            };
+            let text = |label| {
+                TextBundle::from_section(
+                    label,
+                    TextStyle {
+                        font_size: 20.0,
+                        color: background_color(),
+                        font: assets.joystix.clone(),
+                    },
+                )
+            };
+            parent.spawn(row(120. + offset)).with_children(|parent| {
+                parent
+                    .spawn((button(UiRect::default()), HighScoresButton))
+                    .with_children(|parent| {
+                        parent.spawn(text("High scores"));
+                    });
+                parent
+                    .spawn((button(UiRect::left(Val::Px(10.))), BlitzButton))
+                    .with_children(|parent| {
+                        parent.spawn(text("Blitz"));
+                    });
+            });
+            let daily_label = if daily_history.has_played(&storage::today()) {
+                "Daily done"
+            } else {
+                "Daily"
+            };
+            parent.spawn(row(125. + offset)).with_children(|parent| {
+                parent
+                    .spawn((button(UiRect::default()), PuzzlesButton))
+                    .with_children(|parent| {
+                        parent.spawn(text("Puzzles"));
+                    });
+                parent
+                    .spawn((button(UiRect::left(Val::Px(10.))), DailyButton))
+                    .with_children(|parent| {
+                        parent.spawn(text(daily_label));
+                    });
+            });
+            if last_replay.0.is_some() {
+                parent
+                    .spawn((
+                        ButtonBundle {
+                            style: Style {
+                                position_type: PositionType::Relative,
+                                top: Val::Px(130. + offset),
+                                height: Val::Px(30.0),
+                                padding: UiRect::horizontal(Val::Px(10.)),
+                                justify_content: JustifyContent::Center,
+                                align_items: AlignItems::Center,
+                                ..Default::default()
+                            },
+                            background_color: bevy::ui::BackgroundColor(foreground_color()),
+                            ..Default::default()
+                        },
+                        ReplayButton,
+                    ))
+                    .with_children(|parent| {
+                        parent.spawn(TextBundle::from_section(
+                            "Watch replay",
+                            TextStyle {
+                                font_size: 20.0,
+                                color: background_color(),
+                                font: assets.joystix.clone(),
+                            },
+                        ));
+                    });
+            }
         });
 }
 
+#[allow(clippy::single_match)]
 pub fn update_ui(
     mut commands: Commands,
     query: Query<(&Interaction, &PlayButton)>,
@@ -110,3 +358,187 @@ pub fn update_ui(
         }
     }
 }
+
+pub fn update_continue(
+    mut commands: Commands,
+    query: Query<&Interaction, (Changed<Interaction>, With<ContinueButton>)>,
+    entities_on_title_screen: Query<Entity, With<OnTitleScreen>>,
+    mut saved_run: ResMut<SavedRunSlot>,
+    mut resumed_run: ResMut<ResumedRun>,
+    mut endless_config: ResMut<EndlessConfig>,
+    mut grid_config: ResMut<GridConfig>,
+    mut state: ResMut<NextState<GameState>>,
+) {
+    for interaction in query.iter() {
+        if interaction == &Interaction::Pressed {
+            let Some(run) = saved_run.0.take() else {
+                return;
+            };
+            for entity in entities_on_title_screen.iter() {
+                commands.add(DespawnRecursive { entity })
+            }
+
+            endless_config.play_on(&mut grid_config, *run.tile_grid.config());
+            resumed_run.0 = Some(run);
+            state.set(GameState::Playing);
+        }
+    }
+}
+
+pub fn update_grid_size(
+    query: Query<&Interaction, (Changed<Interaction>, With<GridSizeButton>)>,
+    mut labels: Query<&mut Text, With<GridSizeLabel>>,
+    mut grid_config: ResMut<GridConfig>,
+) {
+    for interaction in query.iter() {
+        if interaction == &Interaction::Pressed {
+            *grid_config = grid_config.next_preset();
+            for mut text in labels.iter_mut() {
+                text.sections[0].value = grid_size_text(&grid_config);
+            }
+        }
+    }
+}
+
+pub fn update_seed(
+    query: Query<&Interaction, (Changed<Interaction>, With<SeedButton>)>,
+    mut received_characters: EventReader<ReceivedCharacter>,
+    keys: Res<Input<KeyCode>>,
+    mut labels: Query<&mut Text, With<SeedLabel>>,
+    mut seed_setting: ResMut<SeedSetting>,
+) {
+    let mut digits = seed_setting
+        .0
+        .map(|seed| seed.to_string())
+        .unwrap_or_default();
+    for event in received_characters.iter() {
+        if event.char.is_ascii_digit() {
+            digits.push(event.char);
+        }
+    }
+    if keys.just_pressed(KeyCode::Back) {
+        digits.pop();
+    }
+    if query
+        .iter()
+        .any(|interaction| interaction == &Interaction::Pressed)
+    {
+        digits.clear();
+    }
+
+    // Digits that would overflow the seed are ignored
+    let new_seed = if digits.is_empty() {
+        None
+    } else {
+        digits.parse().ok().or(seed_setting.0)
+    };
+    if new_seed != seed_setting.0 {
+        seed_setting.0 = new_seed;
+        for mut text in labels.iter_mut() {
+            text.sections[0].value = seed_text(&seed_setting);
+        }
+    }
+}
+
+pub fn update_high_scores(
+    mut commands: Commands,
+    query: Query<&Interaction, (Changed<Interaction>, With<HighScoresButton>)>,
+    entities_on_title_screen: Query<Entity, With<OnTitleScreen>>,
+    mut state: ResMut<NextState<GameState>>,
+) {
+    for interaction in query.iter() {
+        if interaction == &Interaction::Pressed {
+            for entity in entities_on_title_screen.iter() {
+                commands.add(DespawnRecursive { entity })
+            }
+
+            state.set(GameState::HighScores);
+        }
+    }
+}
+
+pub fn update_blitz(
+    mut commands: Commands,
+    query: Query<&Interaction, (Changed<Interaction>, With<BlitzButton>)>,
+    entities_on_title_screen: Query<Entity, With<OnTitleScreen>>,
+    mut blitz: ResMut<ActiveBlitz>,
+    mut state: ResMut<NextState<GameState>>,
+) {
+    for interaction in query.iter() {
+        if interaction == &Interaction::Pressed {
+            for entity in entities_on_title_screen.iter() {
+                commands.add(DespawnRecursive { entity })
+            }
+
+            blitz.0 = Some(BlitzClock::default());
+            state.set(GameState::Playing);
+        }
+    }
+}
+
+pub fn update_puzzles(
+    mut commands: Commands,
+    query: Query<&Interaction, (Changed<Interaction>, With<PuzzlesButton>)>,
+    entities_on_title_screen: Query<Entity, With<OnTitleScreen>>,
+    mut state: ResMut<NextState<GameState>>,
+) {
+    for interaction in query.iter() {
+        if interaction == &Interaction::Pressed {
+            for entity in entities_on_title_screen.iter() {
+                commands.add(DespawnRecursive { entity })
+            }
+
+            state.set(GameState::LevelSelect);
+        }
+    }
+}
+
+/// Today's run, on the default grid and the seed of the day
+pub fn update_daily(
+    mut commands: Commands,
+    query: Query<&Interaction, (Changed<Interaction>, With<DailyButton>)>,
+    entities_on_title_screen: Query<Entity, With<OnTitleScreen>>,
+    mut daily: ResMut<ActiveDaily>,
+    mut daily_history: ResMut<DailyHistory>,
+    mut endless_config: ResMut<EndlessConfig>,
+    mut grid_config: ResMut<GridConfig>,
+    mut state: ResMut<NextState<GameState>>,
+) {
+    for interaction in query.iter() {
+        if interaction == &Interaction::Pressed && daily.start(storage::today(), &mut daily_history)
+        {
+            endless_config.play_on(&mut grid_config, GridConfig::default());
+            for entity in entities_on_title_screen.iter() {
+                commands.add(DespawnRecursive { entity })
+            }
+
+            state.set(GameState::Playing);
+        }
+    }
+}
+
+pub fn update_replay(
+    mut commands: Commands,
+    query: Query<&Interaction, (Changed<Interaction>, With<ReplayButton>)>,
+    entities_on_title_screen: Query<Entity, With<OnTitleScreen>>,
+    last_replay: Res<LastReplay>,
+    mut playback: ResMut<ReplayPlayback>,
+    mut endless_config: ResMut<EndlessConfig>,
+    mut grid_config: ResMut<GridConfig>,
+    mut state: ResMut<NextState<GameState>>,
+) {
+    let Some(replay) = &last_replay.0 else {
+        return;
+    };
+    for interaction in query.iter() {
+        if interaction == &Interaction::Pressed {
+            for entity in entities_on_title_screen.iter() {
+                commands.add(DespawnRecursive { entity })
+            }
+
+            endless_config.play_on(&mut grid_config, replay.grid_config);
+            playback.start(replay.clone());
+            state.set(GameState::Playing);
+        }
+    }
+}
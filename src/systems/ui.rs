@@ -1,42 +1,47 @@
+use std::time::Duration;
+
 use bevy::{
     prelude::{
-        Commands, Component, Query, Rect, Res, ResMut, Resource, TextBundle, Transform, Vec3,
+        BuildChildren, ButtonBundle, Commands, Component, DetectChanges, NodeBundle, Query, Rect,
+        Res, ResMut, TextBundle, Transform, Vec2, Vec3, With,
     },
     sprite::{Sprite, SpriteBundle},
     text::{Text, TextStyle},
-    ui::{Style, Val},
+    ui::{AlignItems, BackgroundColor, JustifyContent, PositionType, Style, UiRect, Val},
 };
 
+use am4lgam8_core::{grid::GridConfig, score::GameScore};
+
 use crate::{
     assets::GameAssets,
-    constants::{foreground_color, GAME_LOGIC_HEIGHT, GAME_LOGIC_WIDTH, TILE_SIZE},
+    constants::{background_color, foreground_color, warning_color},
+    layout::GridLayout,
+};
+
+use super::{
+    ai::{AutoplayButton, HintButton},
+    blitz::ActiveBlitz,
+    progression::Progression,
+    puzzle::ActivePuzzle,
+    OnPlayingScreen,
 };
 
-use super::OnPlayingScreen;
+/// The time left turns red under this
+const BLITZ_WARNING: Duration = Duration::from_secs(10);
 
 // Components
 
 #[derive(Component)]
 pub struct ScoreLabel;
 
-// Resource
-
-#[derive(Debug, Default, Resource)]
-pub struct GameScore(i32);
-
-impl GameScore {
-    pub fn add(&mut self, points: i32) {
-        self.0 += points;
-    }
+#[derive(Component)]
+pub struct LevelLabel;
 
-    pub fn reset(&mut self) {
-        self.0 = 0;
-    }
+#[derive(Component)]
+pub struct UndoButton;
 
-    pub fn get(&self) -> i32 {
-        self.0
-    }
-}
+#[derive(Component)]
+pub struct RedoButton;
 
 // Systems
 
@@ -44,42 +49,216 @@ pub fn reset_score(mut game_score: ResMut<GameScore>) {
     game_score.reset();
 }
 
-pub fn spawn_ui(mut commands: Commands, assets: Res<GameAssets>) {
-    commands.spawn((
-        SpriteBundle {
-            sprite: Sprite {
-                rect: Some(Rect::new(0., 0., GAME_LOGIC_WIDTH, GAME_LOGIC_HEIGHT)),
-                ..Default::default()
-            },
-            texture: assets.ui.clone(),
-            transform: Transform {
-                translation: Vec3::new(TILE_SIZE * 1.5, TILE_SIZE * 1.5 - 3., -1.),
-                ..Default::default()
-            },
-            ..Default::default()
-        },
-        OnPlayingScreen,
-    ));
+pub fn spawn_ui(mut commands: Commands, assets: Res<GameAssets>, grid_config: Res<GridConfig>) {
+    let layout = GridLayout::new(*grid_config);
+    spawn_frame(&mut commands, &assets, &layout);
 
+    let scale = layout.pixel_scale;
     commands.spawn((
         TextBundle::from_section(
             "0",
             TextStyle {
-                font_size: 45.,
+                font_size: 9. * scale,
                 color: foreground_color(),
                 font: assets.joystix.clone(),
-                ..Default::default()
             },
         )
         .with_style(Style {
-            position_type: bevy::ui::PositionType::Absolute,
-            bottom: Val::Px(-2.),
-            left: Val::Px(120.),
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(-0.4 * scale),
+            left: Val::Px(24. * scale),
             ..Default::default()
         }),
         ScoreLabel,
         OnPlayingScreen,
     ));
+
+    // In the score bar
+    let bar = |right: f32| UiRect {
+        right: Val::Px(right * scale),
+        bottom: Val::Px(0.),
+        ..UiRect::all(Val::Auto)
+    };
+    spawn_small_button(&mut commands, &assets, scale, "REDO", bar(1.), RedoButton);
+    spawn_small_button(&mut commands, &assets, scale, "UNDO", bar(20.), UndoButton);
+
+    // Over the top wall row
+    let top = |left: Val, right: Val| UiRect {
+        left,
+        right,
+        top: Val::Px(6. * scale),
+        bottom: Val::Auto,
+    };
+    spawn_small_button(
+        &mut commands,
+        &assets,
+        scale,
+        "HINT",
+        top(Val::Px(4. * scale), Val::Auto),
+        HintButton,
+    );
+    spawn_small_button(
+        &mut commands,
+        &assets,
+        scale,
+        "AUTO",
+        top(Val::Auto, Val::Px(4. * scale)),
+        AutoplayButton,
+    );
+
+    // Between them, there is no room left in the score bar
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    left: Val::Px(0.),
+                    right: Val::Px(0.),
+                    top: Val::Px(6. * scale),
+                    justify_content: JustifyContent::Center,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            OnPlayingScreen,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        height: Val::Px(7. * scale),
+                        padding: UiRect::horizontal(Val::Px(scale)),
+                        align_items: AlignItems::Center,
+                        ..Default::default()
+                    },
+                    background_color: BackgroundColor(background_color()),
+                    ..Default::default()
+                })
+                .with_children(|parent| {
+                    parent.spawn((
+                        TextBundle::from_section(
+                            level_text(1),
+                            TextStyle {
+                                font_size: 5. * scale,
+                                color: foreground_color(),
+                                font: assets.joystix.clone(),
+                            },
+                        ),
+                        LevelLabel,
+                    ));
+                });
+        });
+}
+
+fn level_text(level: usize) -> String {
+    format!("LV {level}")
+}
+
+fn moves_left_text(moves_left: u32) -> String {
+    format!("MOVES {moves_left}")
+}
+
+/// Whole seconds, rounded up so that the clock only shows 0:00 once it ran out
+fn time_left_text(remaining: Duration) -> String {
+    let seconds = remaining.as_secs_f32().ceil() as u64;
+    format!("TIME {}:{:02}", seconds / 60, seconds % 60)
+}
+
+/// Spawns a small text button, placed by the sides of `position` that are not `Val::Auto`
+fn spawn_small_button(
+    commands: &mut Commands,
+    assets: &GameAssets,
+    scale: f32,
+    label: &str,
+    position: UiRect,
+    marker: impl Component,
+) {
+    commands
+        .spawn((
+            ButtonBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    left: position.left,
+                    right: position.right,
+                    top: position.top,
+                    bottom: position.bottom,
+                    height: Val::Px(7. * scale),
+                    padding: UiRect::horizontal(Val::Px(scale)),
+                    align_items: AlignItems::Center,
+                    ..Default::default()
+                },
+                background_color: BackgroundColor(background_color()),
+                ..Default::default()
+            },
+            marker,
+            OnPlayingScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                label,
+                TextStyle {
+                    font_size: 5. * scale,
+                    color: foreground_color(),
+                    font: assets.joystix.clone(),
+                },
+            ));
+        });
+}
+
+/// The frame in ui.png is drawn for a 4x4 grid, so it is rebuilt from its parts : the border is
+/// made of plain lines, and the score bar is cut out of the bottom of the image.
+fn spawn_frame(commands: &mut Commands, assets: &GameAssets, layout: &GridLayout) {
+    let min = layout.grid_min() - Vec2::splat(1.);
+    let max = layout.grid_max() + Vec2::splat(1.);
+    let size = max - min;
+
+    let lines = [
+        // Top, bottom, left, right
+        (Vec2::new(min.x, max.y - 1.), Vec2::new(size.x, 1.)),
+        (min, Vec2::new(size.x, 1.)),
+        (min, Vec2::new(1., size.y)),
+        (Vec2::new(max.x - 1., min.y), Vec2::new(1., size.y)),
+    ];
+    for (bottom_left, line_size) in lines {
+        commands.spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: foreground_color(),
+                    custom_size: Some(line_size),
+                    ..Default::default()
+                },
+                transform: Transform::from_translation((bottom_left + line_size / 2.).extend(-1.)),
+                ..Default::default()
+            },
+            OnPlayingScreen,
+        ));
+    }
+
+    // The score bar sits right below the frame, "SCORE:" on the left and the gauge on the right
+    let bar_top = min.y;
+    let parts = [
+        (Rect::new(0., 99., 22., 106.), min.x - 1.),
+        (Rect::new(50., 99., 100., 106.), max.x + 1. - 50.),
+    ];
+    for (rect, left) in parts {
+        let part_size = rect.size();
+        commands.spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    rect: Some(rect),
+                    ..Default::default()
+                },
+                texture: assets.ui.clone(),
+                transform: Transform::from_translation(Vec3::new(
+                    left + part_size.x / 2.,
+                    bar_top - part_size.y / 2.,
+                    -1.,
+                )),
+                ..Default::default()
+            },
+            OnPlayingScreen,
+        ));
+    }
 }
 
 pub fn update_ui(game_score: Res<GameScore>, mut query: Query<(&mut Text, &ScoreLabel)>) {
@@ -87,3 +266,27 @@ pub fn update_ui(game_score: Res<GameScore>, mut query: Query<(&mut Text, &Score
         text.sections[0].value = game_score.get().to_string();
     }
 }
+
+/// Puzzles count the moves left, and blitz runs the time left, instead of the level
+pub fn update_level_label(
+    progression: Res<Progression>,
+    puzzle: Res<ActivePuzzle>,
+    blitz: Res<ActiveBlitz>,
+    mut labels: Query<&mut Text, With<LevelLabel>>,
+) {
+    if !progression.is_changed() && !puzzle.is_changed() && !blitz.is_changed() {
+        return;
+    }
+    let (label, color) = match (&puzzle.0, &blitz.0) {
+        (Some(run), _) => (moves_left_text(run.moves_left()), foreground_color()),
+        (None, Some(clock)) if clock.remaining() < BLITZ_WARNING => {
+            (time_left_text(clock.remaining()), warning_color())
+        }
+        (None, Some(clock)) => (time_left_text(clock.remaining()), foreground_color()),
+        (None, None) => (level_text(progression.level), foreground_color()),
+    };
+    for mut text in labels.iter_mut() {
+        text.sections[0].value = label.clone();
+        text.sections[0].style.color = color;
+    }
+}